@@ -12,7 +12,7 @@ let
   
   # Convert nix config to YAML format
   configFile = pkgs.writeText "webex-tui-config.yml" (generators.toYAML {} {
-    inherit (cfg) theme messages_to_load debug;
+    inherit (cfg) theme messages_to_load debug download_dir markdown send_typing vim_mode forward_header mentions_break_mute sections recent_filter_hours inactive_filter_days room_filters;
   });
   
 in {
@@ -37,6 +37,143 @@ in {
       description = "Enable debug logging by default";
     };
 
+    download_dir = mkOption {
+      type = types.nullOr types.str;
+      default = null;
+      description = "Directory where attachments are downloaded, the system download directory if not set";
+    };
+
+    markdown = mkOption {
+      type = types.bool;
+      default = false;
+      description = "Send messages as markdown by default";
+    };
+
+    send_typing = mkOption {
+      type = types.bool;
+      default = false;
+      description = "Let room members know when you are typing";
+    };
+
+    vim_mode = mkOption {
+      type = types.bool;
+      default = false;
+      description = "Edit messages with vim-style normal, insert and visual modes";
+    };
+
+    forward_header = mkOption {
+      type = types.bool;
+      default = true;
+      description = "Start forwarded messages with their author and room";
+    };
+
+    mentions_break_mute = mkOption {
+      type = types.bool;
+      default = true;
+      description = "Mark muted and snoozed rooms unread when you are mentioned";
+    };
+
+    sections = mkOption {
+      type = types.listOf (types.submodule {
+        options = {
+          name = mkOption {
+            type = types.str;
+            description = "Name shown above the rooms of the section";
+          };
+          rooms = mkOption {
+            type = types.listOf types.str;
+            default = [];
+            description = "Ids of the rooms in the section";
+          };
+          teams = mkOption {
+            type = types.listOf types.str;
+            default = [];
+            description = "Names or ids of the teams whose rooms are in the section";
+          };
+          title_pattern = mkOption {
+            type = types.nullOr types.str;
+            default = null;
+            description = "Pattern matching room titles, where * matches any text";
+          };
+        };
+      });
+      default = [];
+      description = "Sections grouping the rooms in the list, below the favourites";
+      example = [
+        { name = "Engineering"; teams = [ "Engineering" ]; }
+        { name = "Standups"; title_pattern = "*standup*"; }
+      ];
+    };
+
+    recent_filter_hours = mkOption {
+      type = types.int;
+      default = 24;
+      description = "Rooms with activity in the last hours are shown by the Recent filter";
+    };
+
+    inactive_filter_days = mkOption {
+      type = types.int;
+      default = 365;
+      description = "Spaces without activity for days are shown by the InactiveSpaces filter";
+    };
+
+    room_filters = mkOption {
+      type = types.listOf (types.submodule {
+        options = {
+          name = mkOption {
+            type = types.str;
+            description = "Name shown in the title of the rooms list";
+          };
+          room_type = mkOption {
+            type = types.nullOr (types.enum [ "direct" "space" ]);
+            default = null;
+            description = "Only direct conversations or only spaces";
+          };
+          teams = mkOption {
+            type = types.listOf types.str;
+            default = [];
+            description = "Names or ids of the teams of the rooms";
+          };
+          title_regex = mkOption {
+            type = types.nullOr types.str;
+            default = null;
+            description = "Regular expression matching room titles";
+          };
+          unread = mkOption {
+            type = types.nullOr types.bool;
+            default = null;
+            description = "Only rooms with, or without, unread messages";
+          };
+          favourite = mkOption {
+            type = types.nullOr types.bool;
+            default = null;
+            description = "Only favourite rooms, or rooms that are not favourites";
+          };
+          muted = mkOption {
+            type = types.nullOr types.bool;
+            default = null;
+            description = "Only muted or snoozed rooms, or rooms that are not";
+          };
+          active_within_hours = mkOption {
+            type = types.nullOr types.int;
+            default = null;
+            description = "Only rooms with activity in the last hours";
+          };
+          inactive_for_days = mkOption {
+            type = types.nullOr types.int;
+            default = null;
+            description = "Only rooms without activity for days";
+          };
+        };
+      });
+      default = [];
+      description = "Filters of the rooms list, cycled through after the built-in ones";
+      example = [
+        { name = "Engineering unread"; room_type = "space"; teams = [ "Engineering" ]; unread = true; }
+        { name = "Standups"; title_regex = "(?i)standup|retro"; }
+      ];
+    };
+
     themes = mkOption {
       type = types.attrsOf types.str;
       default = {};
@@ -54,6 +54,71 @@ pub(crate) enum Action {
     DeleteSelectedRooms,
     UnselectMessage,
     JumpToLastMessage,
+    ShowAttachments,
+    DownloadAllAttachments,
+    PickerNext,
+    PickerPrevious,
+    PickerConfirm,
+    PickerClose,
+    ToggleMarkdown,
+    StartMessageSearch,
+    NextSearchResult,
+    PreviousSearchResult,
+    OpenSearchResult,
+    SearchOlderMessages,
+    EndMessageSearch,
+    ToggleThreadsCollapsed,
+    ToggleThreadExpanded,
+    FocusThread,
+    LeaveThread,
+    ShowLinks,
+    CopyLink,
+    FillCard,
+    NextCardField,
+    PreviousCardField,
+    ActivateCardField,
+    CloseCardForm,
+    OpenExternalEditor,
+    SendLater,
+    ScheduleMessage,
+    CloseSendTimePrompt,
+    ShowScheduledMessages,
+    NextScheduledMessage,
+    PreviousScheduledMessage,
+    EditScheduledMessage,
+    CancelScheduledMessage,
+    CloseScheduledMessages,
+    ShowOutbox,
+    NextOutboxEntry,
+    PreviousOutboxEntry,
+    ResendOutboxEntry,
+    DiscardOutboxEntry,
+    CloseOutbox,
+    ForwardMessage,
+    ToggleBookmark,
+    ShowBookmarks,
+    NextBookmark,
+    PreviousBookmark,
+    OpenBookmark,
+    DeleteBookmark,
+    CloseBookmarks,
+    ToggleFavourite,
+    NewDirectChat,
+    NewSpace,
+    ConfirmPrompt,
+    ClosePrompt,
+    ShowMembers,
+    NextMember,
+    PreviousMember,
+    AddMember,
+    RemoveMember,
+    CloseMembers,
+    ShowRoomDetails,
+    RenameRoom,
+    ToggleRoomLock,
+    CloseRoomDetails,
+    ToggleMute,
+    SnoozeRoom,
 }
 
 impl Action {
@@ -104,6 +169,71 @@ impl Action {
             Action::DeleteSelectedRooms => &[Key::Char('X')],
             Action::UnselectMessage => &[Key::Esc],
             Action::JumpToLastMessage => &[Key::Char('G')],
+            Action::ShowAttachments => &[Key::Char('a')],
+            Action::DownloadAllAttachments => &[Key::Char('a')],
+            Action::PickerNext => &[Key::Down],
+            Action::PickerPrevious => &[Key::Up],
+            Action::PickerConfirm => &[Key::Enter],
+            Action::PickerClose => &[Key::Esc],
+            Action::ToggleMarkdown => &[Key::Alt('m')],
+            Action::StartMessageSearch => &[Key::Char('s')],
+            Action::NextSearchResult => &[Key::Down],
+            Action::PreviousSearchResult => &[Key::Up],
+            Action::OpenSearchResult => &[Key::Enter],
+            Action::SearchOlderMessages => &[Key::Ctrl('o')],
+            Action::EndMessageSearch => &[Key::Esc],
+            Action::ToggleThreadsCollapsed => &[Key::Char('c')],
+            Action::ToggleThreadExpanded => &[Key::Char('x')],
+            Action::FocusThread => &[Key::Char('f')],
+            Action::LeaveThread => &[Key::Char('f')],
+            Action::ShowLinks => &[Key::Char('o')],
+            Action::CopyLink => &[Key::Char('y')],
+            Action::FillCard => &[Key::Char('i')],
+            Action::NextCardField => &[Key::Down, Key::Tab],
+            Action::PreviousCardField => &[Key::Up, Key::ShiftTab],
+            Action::ActivateCardField => &[Key::Enter],
+            Action::CloseCardForm => &[Key::Esc],
+            Action::OpenExternalEditor => &[Key::Alt('e')],
+            Action::SendLater => &[Key::Alt('s')],
+            Action::ScheduleMessage => &[Key::Enter],
+            Action::CloseSendTimePrompt => &[Key::Esc],
+            Action::ShowScheduledMessages => &[Key::Char('S')],
+            Action::NextScheduledMessage => &[Key::Down],
+            Action::PreviousScheduledMessage => &[Key::Up],
+            Action::EditScheduledMessage => &[Key::Enter, Key::Char('e')],
+            Action::CancelScheduledMessage => &[Key::Char('d'), Key::Delete],
+            Action::CloseScheduledMessages => &[Key::Esc],
+            Action::ShowOutbox => &[Key::Char('O')],
+            Action::NextOutboxEntry => &[Key::Down],
+            Action::PreviousOutboxEntry => &[Key::Up],
+            Action::ResendOutboxEntry => &[Key::Enter, Key::Char('r')],
+            Action::DiscardOutboxEntry => &[Key::Char('d'), Key::Delete],
+            Action::CloseOutbox => &[Key::Esc],
+            Action::ForwardMessage => &[Key::Char('F')],
+            Action::ToggleBookmark => &[Key::Char('b')],
+            Action::ShowBookmarks => &[Key::Char('B')],
+            Action::NextBookmark => &[Key::Down],
+            Action::PreviousBookmark => &[Key::Up],
+            Action::OpenBookmark => &[Key::Enter],
+            Action::DeleteBookmark => &[Key::Char('d'), Key::Delete],
+            Action::CloseBookmarks => &[Key::Esc],
+            Action::ToggleFavourite => &[Key::Char('f')],
+            Action::NewDirectChat => &[Key::Char('n')],
+            Action::NewSpace => &[Key::Char('N')],
+            Action::ConfirmPrompt => &[Key::Enter],
+            Action::ClosePrompt => &[Key::Esc],
+            Action::ShowMembers => &[Key::Char('M')],
+            Action::NextMember => &[Key::Down],
+            Action::PreviousMember => &[Key::Up],
+            Action::AddMember => &[Key::Char('a')],
+            Action::RemoveMember => &[Key::Char('d'), Key::Delete],
+            Action::CloseMembers => &[Key::Esc],
+            Action::ShowRoomDetails => &[Key::Char('I')],
+            Action::RenameRoom => &[Key::Char('r')],
+            Action::ToggleRoomLock => &[Key::Char('L')],
+            Action::CloseRoomDetails => &[Key::Esc],
+            Action::ToggleMute => &[Key::Char('m')],
+            Action::SnoozeRoom => &[Key::Char('z')],
         }
     }
 }
@@ -129,7 +259,7 @@ impl Display for Action {
             Action::LogSelectPreviousTarget => "Select previous",
             Action::LogToggleFilteredTargets => "Toggle filtered",
             Action::LogToggleTargetSelector => "Toggle selector",
-            Action::MarkRead => "Mark read (locally)",
+            Action::MarkRead => "Mark read",
             Action::NextMessage => "Next message",
             Action::NextPane => "Next pane",
             Action::NextRoom => "Next room",
@@ -156,6 +286,71 @@ impl Display for Action {
             Action::DeleteSelectedRooms => "Delete selected rooms",
             Action::UnselectMessage => "Unselect message",
             Action::JumpToLastMessage => "Jump to last message",
+            Action::ShowAttachments => "Attachments",
+            Action::DownloadAllAttachments => "Download all",
+            Action::PickerNext => "Next item",
+            Action::PickerPrevious => "Previous item",
+            Action::PickerConfirm => "Choose item",
+            Action::PickerClose => "Close",
+            Action::ToggleMarkdown => "Toggle markdown",
+            Action::StartMessageSearch => "Search messages",
+            Action::NextSearchResult => "Next result",
+            Action::PreviousSearchResult => "Previous result",
+            Action::OpenSearchResult => "Go to message",
+            Action::SearchOlderMessages => "Load older in room",
+            Action::EndMessageSearch => "End search",
+            Action::ToggleThreadsCollapsed => "Collapse/expand threads",
+            Action::ToggleThreadExpanded => "Expand/collapse thread",
+            Action::FocusThread => "Focus thread",
+            Action::LeaveThread => "Show all threads",
+            Action::ShowLinks => "Links",
+            Action::CopyLink => "Copy link",
+            Action::FillCard => "Fill card",
+            Action::NextCardField => "Next field",
+            Action::PreviousCardField => "Previous field",
+            Action::ActivateCardField => "Switch/submit",
+            Action::CloseCardForm => "Close card",
+            Action::OpenExternalEditor => "External editor",
+            Action::SendLater => "Send later",
+            Action::ScheduleMessage => "Schedule",
+            Action::CloseSendTimePrompt => "Back to message",
+            Action::ShowScheduledMessages => "Scheduled messages",
+            Action::NextScheduledMessage => "Next message",
+            Action::PreviousScheduledMessage => "Previous message",
+            Action::EditScheduledMessage => "Edit message",
+            Action::CancelScheduledMessage => "Cancel message",
+            Action::CloseScheduledMessages => "Close",
+            Action::ShowOutbox => "Outbox",
+            Action::NextOutboxEntry => "Next",
+            Action::PreviousOutboxEntry => "Previous",
+            Action::ResendOutboxEntry => "Resend now",
+            Action::DiscardOutboxEntry => "Discard",
+            Action::CloseOutbox => "Close",
+            Action::ForwardMessage => "Forward to another room",
+            Action::ToggleBookmark => "Bookmark or remove bookmark",
+            Action::ShowBookmarks => "Show bookmarks",
+            Action::NextBookmark => "Next bookmark",
+            Action::PreviousBookmark => "Previous bookmark",
+            Action::OpenBookmark => "Go to message",
+            Action::DeleteBookmark => "Remove bookmark",
+            Action::CloseBookmarks => "Close",
+            Action::ToggleFavourite => "Add or remove favourite",
+            Action::NewDirectChat => "Chat with someone",
+            Action::NewSpace => "New space",
+            Action::ConfirmPrompt => "Confirm",
+            Action::ClosePrompt => "Cancel",
+            Action::ShowMembers => "Members",
+            Action::NextMember => "Next member",
+            Action::PreviousMember => "Previous member",
+            Action::AddMember => "Add member",
+            Action::RemoveMember => "Remove member",
+            Action::CloseMembers => "Close",
+            Action::ShowRoomDetails => "Room details",
+            Action::RenameRoom => "Rename room",
+            Action::ToggleRoomLock => "Lock or unlock room",
+            Action::CloseRoomDetails => "Close",
+            Action::ToggleMute => "Mute or unmute room",
+            Action::SnoozeRoom => "Snooze room",
         };
         write!(f, "{str}")
     }
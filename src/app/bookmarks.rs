@@ -0,0 +1,261 @@
+// app/bookmarks.rs
+
+//! Messages bookmarked by the user, kept across restarts.
+
+use chrono::{DateTime, Utc};
+use log::*;
+use ratatui::widgets::TableState;
+use serde::{Deserialize, Serialize};
+
+use super::cache::room::RoomId;
+use super::cache::MessageId;
+use super::persistence::Persisted;
+
+/// Name of the file where bookmarks are saved, in the cache directory.
+const BOOKMARKS_FILE: &str = "bookmarks.json";
+
+/// Maximum number of characters of the message kept in a bookmark.
+const SNIPPET_LENGTH: usize = 200;
+
+/// A bookmarked message, with enough of it to be listed without loading the room.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub(crate) struct Bookmark {
+    pub(crate) room_id: RoomId,
+    pub(crate) message_id: MessageId,
+    pub(crate) author: String,
+    /// The beginning of the message, on a single line
+    pub(crate) snippet: String,
+    /// When the message was created, as given by Webex
+    pub(crate) created: Option<String>,
+    /// When the message was bookmarked
+    pub(crate) added: DateTime<Utc>,
+}
+
+impl Bookmark {
+    /// Returns a bookmark of the message added now, with a snippet of its text.
+    pub(crate) fn new(
+        room_id: RoomId,
+        message_id: MessageId,
+        author: String,
+        text: &str,
+        created: Option<String>,
+    ) -> Self {
+        let line = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let snippet = match line.chars().count() > SNIPPET_LENGTH {
+            true => format!("{}…", line.chars().take(SNIPPET_LENGTH).collect::<String>()),
+            false => line,
+        };
+        Self {
+            room_id,
+            message_id,
+            author,
+            snippet,
+            created,
+            added: Utc::now(),
+        }
+    }
+}
+
+/// Bookmarked messages, most recently added first, and the selection in the list shown to the
+/// user.
+#[derive(Debug, Default)]
+pub(crate) struct Bookmarks {
+    bookmarks: Persisted<Vec<Bookmark>>,
+    table_state: TableState,
+}
+
+impl Bookmarks {
+    pub(crate) fn load() -> Self {
+        Self::new(Persisted::load(BOOKMARKS_FILE))
+    }
+
+    /// Restores the saved bookmarks. Creation dates that are not valid, e.g. in a file
+    /// edited by hand, are dropped.
+    fn new(mut bookmarks: Persisted<Vec<Bookmark>>) -> Self {
+        for bookmark in bookmarks.iter_mut() {
+            let is_valid = |created: &String| DateTime::parse_from_rfc3339(created).is_ok();
+            if let Some(created) = bookmark.created.take_if(|created| !is_valid(created)) {
+                warn!("Invalid date {created} in bookmark {}", bookmark.message_id);
+            }
+        }
+        Self {
+            bookmarks,
+            table_state: TableState::default(),
+        }
+    }
+
+    /// Returns the bookmarks, most recently added first.
+    pub(crate) fn bookmarks(&self) -> &[Bookmark] {
+        &self.bookmarks
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.bookmarks.is_empty()
+    }
+
+    /// Returns true if the message with given id is bookmarked.
+    pub(crate) fn contains(&self, message_id: &MessageId) -> bool {
+        self.bookmarks.iter().any(|b| b.message_id == *message_id)
+    }
+
+    /// Adds the bookmark, or removes the existing bookmark of the same message.
+    /// Returns true if the bookmark was added.
+    pub(crate) fn toggle(&mut self, bookmark: Bookmark) -> bool {
+        let added = match self.remove(&bookmark.message_id) {
+            Some(_) => false,
+            None => {
+                self.bookmarks.insert(0, bookmark);
+                true
+            }
+        };
+        self.bookmarks.save();
+        added
+    }
+
+    /// Removes the bookmark of the message with given id and returns it.
+    pub(crate) fn remove(&mut self, message_id: &MessageId) -> Option<Bookmark> {
+        let index = self
+            .bookmarks
+            .iter()
+            .position(|b| b.message_id == *message_id)?;
+        let bookmark = self.bookmarks.remove(index);
+        self.bookmarks.save();
+        self.clamp_selection();
+        Some(bookmark)
+    }
+
+    /// Returns the bookmark selected in the list.
+    pub(crate) fn selected(&self) -> Option<&Bookmark> {
+        self.table_state
+            .selected()
+            .and_then(|index| self.bookmarks.get(index))
+    }
+
+    /// Selects the first bookmark, if any.
+    pub(crate) fn select_first(&mut self) {
+        let first = (!self.bookmarks.is_empty()).then_some(0);
+        self.table_state.select(first);
+    }
+
+    /// Selects the next bookmark, wrapping around at the end of the list.
+    pub(crate) fn select_next(&mut self) {
+        let next = match self.table_state.selected() {
+            _ if self.bookmarks.is_empty() => None,
+            Some(selected) if selected + 1 < self.bookmarks.len() => Some(selected + 1),
+            _ => Some(0),
+        };
+        self.table_state.select(next);
+    }
+
+    /// Selects the previous bookmark, wrapping around at the start of the list.
+    pub(crate) fn select_previous(&mut self) {
+        let previous = match self.table_state.selected() {
+            _ if self.bookmarks.is_empty() => None,
+            Some(selected) if selected > 0 => Some(selected - 1),
+            _ => Some(self.bookmarks.len() - 1),
+        };
+        self.table_state.select(previous);
+    }
+
+    /// Keeps the selection within the list after bookmarks are removed.
+    fn clamp_selection(&mut self) {
+        if let Some(selected) = self.table_state.selected() {
+            let last = self.bookmarks.len().checked_sub(1);
+            self.table_state.select(last.map(|last| selected.min(last)));
+        }
+    }
+
+    pub(crate) fn table_state_mut(&mut self) -> &mut TableState {
+        &mut self.table_state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::persistence;
+    use tempfile::tempdir;
+
+    fn bookmark(message_id: &str) -> Bookmark {
+        Bookmark::new(
+            "room".to_string(),
+            message_id.to_string(),
+            "Ann".to_string(),
+            "We decided\nto ship on Friday",
+            None,
+        )
+    }
+
+    #[test]
+    fn test_snippet_is_a_single_short_line() {
+        assert_eq!(bookmark("1").snippet, "We decided to ship on Friday");
+        let long = Bookmark::new(
+            "room".to_string(),
+            "1".to_string(),
+            "Ann".to_string(),
+            &"a".repeat(SNIPPET_LENGTH + 10),
+            None,
+        );
+        assert_eq!(long.snippet.chars().count(), SNIPPET_LENGTH + 1);
+        assert!(long.snippet.ends_with('…'));
+    }
+
+    #[test]
+    fn test_toggle_adds_most_recent_first_and_removes() {
+        let mut bookmarks = Bookmarks::default();
+        assert!(bookmarks.toggle(bookmark("1")));
+        assert!(bookmarks.toggle(bookmark("2")));
+        let ids: Vec<_> = bookmarks
+            .bookmarks()
+            .iter()
+            .map(|b| &b.message_id)
+            .collect();
+        assert_eq!(ids, vec!["2", "1"]);
+        assert!(!bookmarks.toggle(bookmark("2")));
+        assert!(!bookmarks.contains(&"2".to_string()));
+        assert!(bookmarks.contains(&"1".to_string()));
+    }
+
+    #[test]
+    fn test_remove_keeps_selection_in_list() {
+        let mut bookmarks = Bookmarks::default();
+        bookmarks.toggle(bookmark("1"));
+        bookmarks.toggle(bookmark("2"));
+        bookmarks.select_next();
+        bookmarks.select_next();
+        assert_eq!(bookmarks.selected().unwrap().message_id, "1");
+        bookmarks.remove(&"1".to_string());
+        assert_eq!(bookmarks.selected().unwrap().message_id, "2");
+        bookmarks.remove(&"2".to_string());
+        assert!(bookmarks.selected().is_none());
+    }
+
+    #[test]
+    fn test_bookmarks_are_kept_across_restarts() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(BOOKMARKS_FILE);
+        let mut bookmarks = Bookmarks::new(Persisted::load_from(path.clone()));
+        bookmarks.toggle(bookmark("1"));
+        let reloaded = Bookmarks::new(Persisted::load_from(path));
+        assert_eq!(reloaded.bookmarks(), bookmarks.bookmarks());
+    }
+
+    #[test]
+    fn test_invalid_dates_are_dropped_on_load() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(BOOKMARKS_FILE);
+        let created = |created: &str| Bookmark {
+            created: Some(created.to_string()),
+            ..bookmark(created)
+        };
+        let saved = vec![created("2025-10-15T10:00:00.000Z"), created("yesterday")];
+        persistence::save(&path, &saved).unwrap();
+        let loaded = Bookmarks::new(Persisted::load_from(path));
+        let dates: Vec<_> = loaded
+            .bookmarks()
+            .iter()
+            .map(|b| b.created.as_deref())
+            .collect();
+        assert_eq!(dates, vec![Some("2025-10-15T10:00:00.000Z"), None]);
+    }
+}
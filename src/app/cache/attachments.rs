@@ -0,0 +1,117 @@
+// app/cache/attachments.rs
+
+//! Information about files attached to messages.
+
+use std::collections::{HashMap, HashSet};
+
+/// Messages reference their files by content url.
+pub(crate) type AttachmentUrl = String;
+
+/// Metadata of an attached file, obtained without downloading its content.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct AttachmentInfo {
+    pub(crate) file_name: Option<String>,
+    pub(crate) size: Option<u64>,
+    pub(crate) content_type: Option<String>,
+}
+
+impl AttachmentInfo {
+    /// Returns a short description with the file name and size, if known.
+    pub(crate) fn description(&self) -> String {
+        let name = self
+            .file_name
+            .clone()
+            .unwrap_or_else(|| String::from("unnamed file"));
+        match self.size {
+            Some(size) => format!("{name} ({})", human_size(size)),
+            None => name,
+        }
+    }
+}
+
+/// Cache of `AttachmentInfo` by content url
+#[derive(Debug, Default)]
+pub(crate) struct Attachments {
+    by_url: HashMap<AttachmentUrl, AttachmentInfo>,
+    requested: HashSet<AttachmentUrl>,
+}
+
+impl Attachments {
+    pub(crate) fn get(&self, url: &str) -> Option<&AttachmentInfo> {
+        self.by_url.get(url)
+    }
+
+    pub(crate) fn insert(&mut self, url: AttachmentUrl, info: AttachmentInfo) {
+        self.requested.remove(&url);
+        self.by_url.insert(url, info);
+    }
+
+    pub(crate) fn add_requested(&mut self, url: &str) {
+        self.requested.insert(url.to_string());
+    }
+
+    pub(crate) fn exists_or_requested(&self, url: &str) -> bool {
+        self.by_url.contains_key(url) || self.requested.contains(url)
+    }
+}
+
+/// Formats a number of bytes for humans, e.g. 1.2 MB
+pub(crate) fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["kB", "MB", "GB", "TB"];
+    if bytes < 1000 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64 / 1000.0;
+    let mut unit = 0;
+    while size >= 1000.0 && unit < UNITS.len() - 1 {
+        size /= 1000.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[test]
+    fn test_attachments() {
+        let mut attachments = Attachments::default();
+        let info = AttachmentInfo {
+            file_name: Some("report.pdf".to_string()),
+            ..Default::default()
+        };
+        attachments.add_requested("url1");
+        assert!(attachments.exists_or_requested("url1"));
+        assert_eq!(attachments.get("url1"), None);
+        attachments.insert("url1".to_string(), info.clone());
+        assert_eq!(attachments.get("url1"), Some(&info));
+        assert!(!attachments.requested.contains("url1"));
+        assert!(!attachments.exists_or_requested("url2"));
+    }
+
+    #[rstest(
+        bytes,
+        expected,
+        case(0, "0 B"),
+        case(999, "999 B"),
+        case(1000, "1.0 kB"),
+        case(1_234_567, "1.2 MB"),
+        case(5_000_000_000, "5.0 GB")
+    )]
+    fn test_human_size(bytes: u64, expected: &str) {
+        assert_eq!(human_size(bytes), expected);
+    }
+
+    #[test]
+    fn test_description() {
+        let info = AttachmentInfo {
+            file_name: Some("logs.txt".to_string()),
+            size: Some(2048),
+            content_type: None,
+        };
+        assert_eq!(info.description(), "logs.txt (2.0 kB)");
+        assert_eq!(AttachmentInfo::default().description(), "unnamed file");
+    }
+}
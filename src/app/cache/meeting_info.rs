@@ -0,0 +1,32 @@
+// app/cache/meeting_info.rs
+
+//! How to join the meeting of a room, as returned by the meeting info API.
+
+use serde::Deserialize;
+
+/// Links to join the meeting of a room.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct MeetingInfo {
+    /// Link to join the meeting in a browser or a Webex client
+    pub(crate) meeting_link: Option<String>,
+    /// Address to join the meeting from a video device
+    pub(crate) sip_address: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_meeting_info() {
+        let json = r#"{"roomId":"r1","meetingLink":"https://example.webex.com/m/1",
+            "sipAddress":"1@example.webex.com","meetingNumber":"123","callInTollFreeNumber":""}"#;
+        let info: MeetingInfo = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            info.meeting_link.as_deref(),
+            Some("https://example.webex.com/m/1")
+        );
+        assert_eq!(info.sip_address.as_deref(), Some("1@example.webex.com"));
+    }
+}
@@ -0,0 +1,185 @@
+// app/cache/members.rs
+
+//! Members of rooms, as returned by the memberships API.
+
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+
+use super::persons::PersonId;
+use super::room::RoomId;
+use super::MessageId;
+
+pub(crate) type MembershipId = String;
+
+/// A person member of a room.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Member {
+    /// Id of the membership, used to remove the member from the room.
+    #[serde(default)]
+    pub(crate) id: MembershipId,
+    pub(crate) person_id: PersonId,
+    pub(crate) person_email: Option<String>,
+    #[serde(rename = "personDisplayName")]
+    pub(crate) display_name: Option<String>,
+    #[serde(default)]
+    pub(crate) is_moderator: bool,
+    pub(crate) room_id: Option<RoomId>,
+    /// Last message seen by the member, only returned for our own memberships.
+    pub(crate) last_seen_id: Option<MessageId>,
+    /// Date the member last read the room, in RFC 3339 format.
+    pub(crate) last_seen_date: Option<String>,
+}
+
+impl Member {
+    /// Returns the display name, or the email if the name is unknown.
+    pub(crate) fn name(&self) -> String {
+        self.display_name
+            .clone()
+            .or_else(|| self.person_email.clone())
+            .unwrap_or_else(|| String::from("Unknown"))
+    }
+}
+
+/// Cache of the members of each room
+#[derive(Debug, Default)]
+pub(crate) struct Members {
+    by_room: HashMap<RoomId, Vec<Member>>,
+    requested: HashSet<RoomId>,
+}
+
+impl Members {
+    /// Returns the members of the room, if they were retrieved.
+    pub(crate) fn get(&self, room_id: &str) -> Option<&[Member]> {
+        self.by_room.get(room_id).map(Vec::as_slice)
+    }
+
+    /// Sets the members of a room, sorted by name.
+    pub(crate) fn insert(&mut self, room_id: RoomId, mut members: Vec<Member>) {
+        members.sort_by_key(|member| member.name().to_lowercase());
+        self.requested.remove(&room_id);
+        self.by_room.insert(room_id, members);
+    }
+
+    /// Adds a member to a room whose members were retrieved, keeping them sorted by name.
+    pub(crate) fn add(&mut self, room_id: &str, member: Member) {
+        if let Some(members) = self.by_room.get_mut(room_id) {
+            members.retain(|m| m.person_id != member.person_id);
+            members.push(member);
+            members.sort_by_key(|member| member.name().to_lowercase());
+        }
+    }
+
+    /// Removes the member with given membership id from a room.
+    pub(crate) fn remove(&mut self, room_id: &str, membership_id: &MembershipId) {
+        if let Some(members) = self.by_room.get_mut(room_id) {
+            members.retain(|member| member.id != *membership_id);
+        }
+    }
+
+    pub(crate) fn add_requested(&mut self, room_id: &str) {
+        self.requested.insert(room_id.to_string());
+    }
+
+    /// Forgets that the members of the room were requested, so that they are requested again.
+    pub(crate) fn remove_requested(&mut self, room_id: &str) {
+        self.requested.remove(room_id);
+    }
+
+    pub(crate) fn exists_or_requested(&self, room_id: &str) -> bool {
+        self.by_room.contains_key(room_id) || self.requested.contains(room_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(id: &str, name: Option<&str>, email: Option<&str>) -> Member {
+        Member {
+            person_id: id.to_string(),
+            person_email: email.map(str::to_string),
+            display_name: name.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_member_name() {
+        assert_eq!(member("1", Some("Ann"), Some("a@x.com")).name(), "Ann");
+        assert_eq!(member("1", None, Some("a@x.com")).name(), "a@x.com");
+        assert_eq!(member("1", None, None).name(), "Unknown");
+    }
+
+    #[test]
+    fn test_members_sorted_by_name() {
+        let mut members = Members::default();
+        members.add_requested("room");
+        assert!(members.exists_or_requested("room"));
+        members.remove_requested("room");
+        assert!(!members.exists_or_requested("room"));
+        members.add_requested("room");
+        assert_eq!(members.get("room"), None);
+        members.insert(
+            "room".to_string(),
+            vec![
+                member("2", Some("bob"), None),
+                member("1", Some("Ann"), None),
+            ],
+        );
+        let names: Vec<_> = members
+            .get("room")
+            .unwrap()
+            .iter()
+            .map(Member::name)
+            .collect();
+        assert_eq!(names, vec!["Ann", "bob"]);
+        assert!(members.exists_or_requested("room"));
+    }
+
+    #[test]
+    fn test_add_and_remove_members() {
+        let mut members = Members::default();
+        let mut ann = member("1", Some("Ann"), None);
+        ann.id = "m1".to_string();
+        // Members of rooms not retrieved yet are not kept
+        members.add("room", ann.clone());
+        assert_eq!(members.get("room"), None);
+        members.insert("room".to_string(), vec![member("2", Some("bob"), None)]);
+        members.add("room", ann);
+        let names: Vec<_> = members
+            .get("room")
+            .unwrap()
+            .iter()
+            .map(Member::name)
+            .collect();
+        assert_eq!(names, vec!["Ann", "bob"]);
+        members.remove("room", &"m1".to_string());
+        assert_eq!(members.get("room").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_deserialize_membership() {
+        let json = r#"{"id":"m1","roomId":"r1","personId":"p1","personEmail":"ann@example.com",
+            "personDisplayName":"Ann","isModerator":true,"created":"2024-01-01T00:00:00.000Z"}"#;
+        let member: Member = serde_json::from_str(json).unwrap();
+        assert_eq!(member.id, "m1");
+        assert_eq!(member.person_id, "p1");
+        assert_eq!(member.display_name.as_deref(), Some("Ann"));
+        assert!(member.is_moderator);
+        assert_eq!(member.room_id.as_deref(), Some("r1"));
+        assert_eq!(member.last_seen_date, None);
+    }
+
+    #[test]
+    fn test_deserialize_own_membership() {
+        let json = r#"{"id":"m1","roomId":"r1","personId":"p1","isModerator":false,
+            "lastSeenId":"msg1","lastSeenDate":"2024-01-02T03:04:05.678Z"}"#;
+        let member: Member = serde_json::from_str(json).unwrap();
+        assert_eq!(member.last_seen_id.as_deref(), Some("msg1"));
+        assert_eq!(
+            member.last_seen_date.as_deref(),
+            Some("2024-01-02T03:04:05.678Z")
+        );
+    }
+}
@@ -2,13 +2,16 @@
 
 //! A caching store for Webex messages and context.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use chrono::{DateTime, Utc};
 use color_eyre::{eyre::eyre, Result};
 use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
 use webex::{Message, Person};
 
+pub(crate) mod attachments;
+pub(crate) mod meeting_info;
+pub(crate) mod members;
 pub(crate) mod msg_thread;
 pub(crate) mod persons;
 pub(crate) mod room;
@@ -17,16 +20,43 @@ pub(crate) mod room_content;
 pub(crate) mod room_list_filter;
 pub(crate) mod rooms;
 pub(crate) mod teams;
+pub(crate) mod thread_view;
+pub(crate) mod typing;
 
+use self::attachments::Attachments;
+use self::meeting_info::MeetingInfo;
+use self::members::Members;
 use self::room_and_team_title::RoomAndTeamTitle;
 use self::room_content::RoomContent;
 use persons::Persons;
 use room::RoomId;
 use rooms::Rooms;
 use teams::Teams;
+use thread_view::{DisplayedMessage, ThreadView};
+use typing::Typing;
 
 pub(crate) type MessageId = String;
 
+/// Prefix of the ids given to our messages until Webex gives them theirs.
+const LOCAL_MESSAGE_ID_PREFIX: &str = "local-";
+
+/// Returns the id of a message shown while it is being sent by the given outbox entry.
+pub(crate) fn local_message_id(outbox_id: u64) -> MessageId {
+    format!("{LOCAL_MESSAGE_ID_PREFIX}{outbox_id}")
+}
+
+/// Returns the outbox entry sending the message, if it is only shown locally.
+pub(crate) fn outbox_id_of_local_message(msg_id: &MessageId) -> Option<u64> {
+    msg_id.strip_prefix(LOCAL_MESSAGE_ID_PREFIX)?.parse().ok()
+}
+
+/// Returns whether the message is only shown locally, while it is being sent.
+pub(crate) fn is_local_message(msg: &Message) -> bool {
+    msg.id
+        .as_ref()
+        .is_some_and(|id| outbox_id_of_local_message(id).is_some())
+}
+
 /// `TeamsStore` maintains a local cache of room information,
 /// messages in some of those rooms, and other state information
 /// directly related to them.
@@ -40,6 +70,13 @@ pub(crate) struct Cache {
     pub(crate) teams: Teams,
     pub(crate) me: Option<webex::Person>,
     pub(crate) persons: Persons,
+    pub(crate) attachments: Attachments,
+    pub(crate) members: Members,
+    /// How to join the meeting of the rooms whose details were shown
+    pub(crate) meeting_infos: HashMap<RoomId, MeetingInfo>,
+    pub(crate) typing: Typing,
+    /// Messages whose card we submitted, from this or another client
+    pub(crate) submitted_cards: HashSet<MessageId>,
 }
 
 impl Cache {
@@ -57,6 +94,20 @@ impl Cache {
         }
     }
 
+    /// Returns true if the message mentions us, or everyone in the room.
+    pub(crate) fn mentions_me(&self, msg: &Message) -> bool {
+        let mentions_me = self.me.as_ref().is_some_and(|me| {
+            msg.mentioned_people
+                .as_ref()
+                .is_some_and(|people| people.contains(&me.id))
+        });
+        let mentions_all = msg
+            .mentioned_groups
+            .as_ref()
+            .is_some_and(|groups| groups.iter().any(|group| group == "all"));
+        mentions_me || mentions_all
+    }
+
     /// Adds a message to the store, respecting the thread order.
     pub(crate) fn add_message(&mut self, msg: &Message) -> Result<()> {
         let room_id = msg.room_id.clone().ok_or(eyre!("message has no room id"))?;
@@ -91,21 +142,22 @@ impl Cache {
         }
     }
 
-    /// Returns whether there are any messages in the room.
-    pub(crate) fn room_is_empty(&self, id: &RoomId) -> bool {
+    /// Returns an iterator over the messages of the room shown with the given thread view.
+    pub(crate) fn displayed_messages_in_room<'a>(
+        &'a self,
+        id: &RoomId,
+        view: &'a ThreadView,
+    ) -> Box<dyn Iterator<Item = DisplayedMessage<'a>> + 'a> {
         match self.rooms_content.get(id) {
-            Some(content) => content.is_empty(),
-            None => true,
+            Some(content) => Box::new(content.displayed_messages(view)),
+            None => Box::new(::std::iter::empty()),
         }
     }
 
-    /// Returns the number of messages in the room.
-    /// More efficient than `messages_in_room` if only the count is needed.
-    pub(crate) fn nb_messages_in_room(&self, id: &RoomId) -> usize {
-        match self.rooms_content.get(id) {
-            Some(content) => content.len(),
-            None => 0,
-        }
+    /// Returns whether there are any messages from Webex in the room, messages being sent
+    /// do not count.
+    pub(crate) fn room_is_empty(&self, id: &RoomId) -> bool {
+        self.messages_in_room(id).all(is_local_message)
     }
 
     /// Deletes message with `msg_id` in `room_id` if it exists.
@@ -119,7 +171,7 @@ impl Cache {
     pub(crate) fn nth_message_in_room(&self, index: usize, room_id: &str) -> Result<&Message> {
         self.rooms_content
             .get(room_id)
-            .ok_or(eyre!("Room {} not found", index))?
+            .ok_or(eyre!("Room {} not found", room_id))?
             .nth_message(index)
     }
 
@@ -186,16 +238,14 @@ impl Cache {
 
     /// Returns an iterator over rooms that match the search query using fuzzy matching.
     /// Searches both room titles and team names.
-    /// Only searches within rooms that match the given filter.
+    /// Only searches within the given rooms.
     pub(crate) fn rooms_matching_search<'a>(
         &'a self,
         query: &'a str,
-        filter: &'a room_list_filter::RoomsListFilter,
+        rooms: impl Iterator<Item = &'a room::Room>,
     ) -> impl Iterator<Item = (&'a room::Room, i64)> {
         let matcher = SkimMatcherV2::default();
-        let mut scored_rooms: Vec<_> = self
-            .rooms
-            .rooms_filtered_by(filter)
+        let mut scored_rooms: Vec<_> = rooms
             .filter_map(|room| {
                 let room_and_team_title = self.room_and_team_title(&room.id).ok()?;
                 let search_text = format!(
@@ -213,6 +263,91 @@ impl Cache {
         scored_rooms.sort_by(|a, b| b.1.cmp(&a.1));
         scored_rooms.into_iter()
     }
+
+    /// Returns the known persons whose name or email match the query using fuzzy matching,
+    /// best match first. We are not included.
+    pub(crate) fn persons_matching_search(&self, query: &str) -> Vec<&Person> {
+        let matcher = SkimMatcherV2::default();
+        let mut scored_persons: Vec<_> = self
+            .persons
+            .by_id
+            .values()
+            .filter(|person| self.me.as_ref().is_none_or(|me| me.id != person.id))
+            .filter_map(|person| {
+                let search_text = format!("{} {}", person.display_name, person.emails.join(" "));
+                matcher
+                    .fuzzy_match(&search_text, query)
+                    .map(|score| (person, score))
+            })
+            .collect();
+        // Sort by score (highest first), then by name for stable results
+        scored_persons.sort_by(|a, b| {
+            b.1.cmp(&a.1)
+                .then_with(|| a.0.display_name.cmp(&b.0.display_name))
+        });
+        scored_persons
+            .into_iter()
+            .map(|(person, _)| person)
+            .collect()
+    }
+
+    /// Returns the messages containing all the words of the query, ignoring case,
+    /// most recent first.
+    pub(crate) fn messages_matching_search(&self, query: &str) -> Vec<(&RoomId, &Message)> {
+        let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        if terms.is_empty() {
+            return vec![];
+        }
+        let mut found: Vec<_> = self
+            .rooms_content
+            .iter()
+            .flat_map(|(room_id, content)| content.messages().map(move |msg| (room_id, msg)))
+            .filter(|(_, msg)| {
+                let text = searchable_text(msg).to_lowercase();
+                terms.iter().all(|term| text.contains(term))
+            })
+            .collect();
+        found.sort_by(|a, b| b.1.created.cmp(&a.1.created));
+        found
+    }
+}
+
+/// Returns the searchable content of a message: its text, markdown and the text of its HTML.
+pub(crate) fn searchable_text(msg: &Message) -> String {
+    let html_text = msg.html.as_deref().map(strip_html_tags);
+    [
+        msg.text.as_deref(),
+        msg.markdown.as_deref(),
+        html_text.as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+/// Returns the text of an HTML fragment, without the tags and with the common entities decoded.
+fn strip_html_tags(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                // Tags often separate words, e.g. <br/>
+                text.push(' ');
+            }
+            c if !in_tag => text.push(c),
+            _ => (),
+        }
+    }
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
 }
 
 #[cfg(test)]
@@ -231,6 +366,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn local_messages_do_not_fill_room() {
+        let mut store = Cache::default();
+        let room_id = "room".to_string();
+        let local_id = local_message_id(3);
+        assert_eq!(outbox_id_of_local_message(&local_id), Some(3));
+        assert_eq!(outbox_id_of_local_message(&"message1".to_string()), None);
+        store
+            .add_message(&make_message(&local_id, &room_id, None))
+            .unwrap();
+        assert!(store.room_is_empty(&room_id));
+        store
+            .add_message(&make_message("message1", &room_id, None))
+            .unwrap();
+        assert!(!store.room_is_empty(&room_id));
+    }
+
     #[test]
     fn should_add_message_with_unknown_room() {
         let mut store = Cache::default();
@@ -276,6 +428,36 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_messages_matching_search() {
+        let mut store = Cache::default();
+        let older = Message {
+            text: Some("See the report at https://example.com".to_string()),
+            created: Some("2024-01-01T00:00:00.000Z".to_string()),
+            ..make_message("older", "room1", None)
+        };
+        let newer = Message {
+            html: Some("<p>The <strong>final</strong> report &amp; notes</p>".to_string()),
+            created: Some("2024-01-02T00:00:00.000Z".to_string()),
+            ..make_message("newer", "room2", None)
+        };
+        store.add_message(&older).unwrap();
+        store.add_message(&newer).unwrap();
+        let ids = |query: &str| -> Vec<String> {
+            store
+                .messages_matching_search(query)
+                .into_iter()
+                .map(|(_, msg)| msg.id.clone().unwrap())
+                .collect()
+        };
+        assert_eq!(ids("REPORT"), vec!["newer", "older"]);
+        assert_eq!(ids("final report"), vec!["newer"]);
+        assert_eq!(ids("notes & report"), vec!["newer"]);
+        assert_eq!(ids("example.com"), vec!["older"]);
+        assert!(ids("strong").is_empty());
+        assert!(ids("  ").is_empty());
+    }
+
     #[test]
     fn test_room_and_team_title() {
         const TEAM_ID: &str = "some_new_team_id";
@@ -422,20 +604,6 @@ mod tests {
         assert_eq!(messages.next().unwrap().id.as_ref().unwrap(), "message3");
     }
 
-    // nb_messages_in_room
-    #[test]
-    fn test_nb_messages_in_room() {
-        let mut store = Cache::default();
-        let room_id = String::from("some_new_room_id") as RoomId;
-        let message1 = make_message("message1", &room_id, None);
-        store.add_message(&message1).unwrap();
-        let message2 = make_message("message2", &room_id, None);
-        store.add_message(&message2).unwrap();
-        let message3 = make_message("message3", &room_id, None);
-        store.add_message(&message3).unwrap();
-        assert_eq!(store.nb_messages_in_room(&room_id), 3);
-    }
-
     // nth_message_in_room
     #[test]
     fn test_nth_message_in_room() {
@@ -468,4 +636,52 @@ mod tests {
         store.add_message(&message1).unwrap();
         assert!(!store.room_is_empty(&room_id));
     }
+
+    #[test]
+    fn test_persons_matching_search_excludes_me() {
+        let mut store = Cache::default();
+        let person = |id: &str, name: &str, email: &str| Person {
+            id: id.to_string(),
+            display_name: name.to_string(),
+            emails: vec![email.to_string()],
+            ..Default::default()
+        };
+        let me = person("me", "Ann Me", "ann@example.com");
+        store.set_me(&me);
+        store.persons.insert(me);
+        store
+            .persons
+            .insert(person("1", "Annie Smith", "annie@example.com"));
+        store
+            .persons
+            .insert(person("2", "Bob Jones", "bj@example.com"));
+        let ids = |query| {
+            store
+                .persons_matching_search(query)
+                .iter()
+                .map(|p| p.id.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids("ann"), vec!["1"]);
+        assert_eq!(ids("bj@"), vec!["2"]);
+        assert_eq!(ids(""), vec!["1", "2"]);
+    }
+
+    #[test]
+    fn test_mentions_me() {
+        let mut store = Cache::default();
+        let mut msg = make_message("message1", "room", None);
+        assert!(!store.mentions_me(&msg));
+        msg.mentioned_people = Some(vec!["me".to_string()]);
+        assert!(!store.mentions_me(&msg));
+        store.set_me(&Person {
+            id: "me".to_string(),
+            ..Default::default()
+        });
+        assert!(store.mentions_me(&msg));
+        msg.mentioned_people = Some(vec!["someone".to_string()]);
+        assert!(!store.mentions_me(&msg));
+        msg.mentioned_groups = Some(vec!["all".to_string()]);
+        assert!(store.mentions_me(&msg));
+    }
 }
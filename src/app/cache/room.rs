@@ -1,6 +1,8 @@
 use chrono::{DateTime, Duration, Utc};
 use webex::Room as WebexRoom;
 
+use super::MessageId;
+
 pub(crate) type RoomId = String;
 
 /// `Room` is a wrapper around the webex Room type, adding some extra information.
@@ -13,9 +15,13 @@ pub(crate) struct Room {
     pub(crate) is_locked: bool,
     pub(crate) team_id: Option<String>,
     pub(crate) last_activity: DateTime<Utc>,
-    // creator_id: String,
-    // created: String,
+    /// Id of the person who created the room.
+    pub(crate) creator_id: String,
+    /// When the room was created, if Webex gave a valid date.
+    pub(crate) created: Option<DateTime<Utc>>,
     pub(crate) unread: bool,
+    /// Last message we told the server we have seen, if known.
+    pub(crate) last_seen_id: Option<MessageId>,
 }
 
 impl Room {
@@ -34,10 +40,20 @@ impl Room {
         self.is_locked
     }
 
+    /// Returns whether the room is in the team with the given id, or the given name ignoring
+    /// case, knowing the name of its team if any.
+    pub(crate) fn is_in_team(&self, team: &str, team_name: Option<&str>) -> bool {
+        self.team_id.as_deref() == Some(team)
+            || team_name.is_some_and(|name| name.eq_ignore_ascii_case(team))
+    }
+
     /// Returns whether the room has seen any activity in the past specified period.
     /// Panics if room is not known.
     pub(crate) fn has_activity_since(&self, duration: Duration) -> bool {
-        self.last_activity > (Utc::now() - duration)
+        // Going back further than time can be represented includes any activity
+        Utc::now()
+            .checked_sub_signed(duration)
+            .is_none_or(|since| self.last_activity > since)
     }
 
     /// Updates the last activity of the room if the new activity is more recent.
@@ -57,14 +73,17 @@ impl From<WebexRoom> for Room {
             room_type: webex_room.room_type,
             is_locked: webex_room.is_locked,
             team_id: webex_room.team_id,
-            // creator_id: webex_room.creator_id,
-            // created: webex_room.created,
+            creator_id: webex_room.creator_id,
+            created: DateTime::parse_from_rfc3339(&webex_room.created)
+                .ok()
+                .map(|created| created.with_timezone(&Utc)),
 
             // fields added or modified by this crate
             last_activity: DateTime::parse_from_rfc3339(&webex_room.last_activity)
                 .unwrap()
                 .with_timezone(&Utc),
             unread: false,
+            last_seen_id: None,
         }
     }
 }
@@ -97,6 +116,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_room_from_webex_room() {
+        let webex_room = WebexRoom {
+            id: "id".to_string(),
+            title: Some("Planning".to_string()),
+            room_type: "group".to_string(),
+            is_locked: true,
+            team_id: None,
+            last_activity: "2024-01-02T03:04:05.678Z".to_string(),
+            creator_id: "creator".to_string(),
+            created: "2024-01-01T00:00:00.000Z".to_string(),
+        };
+        let room = Room::from(webex_room);
+        assert_eq!(room.creator_id, "creator");
+        assert_eq!(
+            room.created,
+            Some(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap())
+        );
+        assert!(room.is_moderated());
+    }
+
     #[test]
     fn test_room_has_activity_since() {
         let room = Room {
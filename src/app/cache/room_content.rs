@@ -4,7 +4,9 @@ use color_eyre::{eyre::eyre, Result};
 use log::*;
 use webex::Message;
 
-use super::{msg_thread::MsgThread, MessageId};
+use super::msg_thread::MsgThread;
+use super::thread_view::{DisplayedMessage, ThreadView};
+use super::MessageId;
 
 /// `RoomContent` is a list of `MsgThread`s (conversations)
 /// ordered by creation time. Messages within a same thread
@@ -22,6 +24,31 @@ impl RoomContent {
         self.threads.iter().flat_map(|thread| thread.messages())
     }
 
+    /// Returns the messages shown with the given thread view, in display order.
+    /// A collapsed thread only shows its first message, with the number of hidden replies.
+    pub(crate) fn displayed_messages<'a>(
+        &'a self,
+        view: &'a ThreadView,
+    ) -> impl Iterator<Item = DisplayedMessage<'a>> {
+        self.threads
+            .iter()
+            .filter(|thread| view.shows_thread(thread.id()))
+            .flat_map(|thread| {
+                let (nb_shown, hidden_replies) = match view.is_expanded(thread.id()) {
+                    true => (thread.len(), 0),
+                    false => (1, thread.len().saturating_sub(1)),
+                };
+                thread
+                    .messages()
+                    .take(nb_shown)
+                    .enumerate()
+                    .map(move |(index, message)| DisplayedMessage {
+                        message,
+                        hidden_replies: if index == 0 { hidden_replies } else { 0 },
+                    })
+            })
+    }
+
     /// Returns the message with the given index in display order, if any.
     pub(crate) fn nth_message(&self, index: usize) -> Result<&Message> {
         self.messages()
@@ -77,23 +104,23 @@ impl RoomContent {
         Ok(())
     }
 
+    /// Deletes a message, and its thread if it was the only message in it.
     pub(crate) fn delete_message(&mut self, msg_id: &MessageId) -> Result<()> {
-        for thread in self.threads.iter_mut() {
-            if thread.delete_message(msg_id) {
-                return Ok(());
-            }
+        let index = self
+            .threads
+            .iter_mut()
+            .position(|thread| thread.delete_message(msg_id))
+            .ok_or(eyre!("Message not found"))?;
+        if self.threads[index].len() == 0 {
+            self.threads.remove(index);
         }
-        Err(eyre!("Message not found"))
+        Ok(())
     }
 
     pub(crate) fn len(&self) -> usize {
         self.threads.iter().map(|thread| thread.len()).sum()
     }
 
-    pub(crate) fn is_empty(&self) -> bool {
-        self.len() == 0
-    }
-
     /// Removes all messages from the room content.
     /// Returns the number of messages removed.
     pub(crate) fn wipe(&mut self) -> usize {
@@ -172,6 +199,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_displayed_messages() {
+        let mut room_content = RoomContent::default();
+        room_content.add(&make_message("parent", None)).unwrap();
+        room_content
+            .add(&make_message("child1", Some("parent")))
+            .unwrap();
+        room_content
+            .add(&make_message("child2", Some("parent")))
+            .unwrap();
+        room_content.add(&make_message("other", None)).unwrap();
+        let displayed = |view: &ThreadView| -> Vec<(String, usize)> {
+            room_content
+                .displayed_messages(view)
+                .map(|displayed| {
+                    (
+                        displayed.message.id.clone().unwrap(),
+                        displayed.hidden_replies,
+                    )
+                })
+                .collect()
+        };
+        let mut view = ThreadView::default();
+        assert_eq!(displayed(&view).len(), 4);
+        view.toggle_collapsed();
+        assert_eq!(
+            displayed(&view),
+            vec![("parent".to_string(), 2), ("other".to_string(), 0)]
+        );
+        view.set_focused(Some("parent".to_string()));
+        assert_eq!(
+            displayed(&view),
+            vec![
+                ("parent".to_string(), 0),
+                ("child1".to_string(), 0),
+                ("child2".to_string(), 0)
+            ]
+        );
+    }
+
     #[test]
     fn test_delete_message() {
         let mut room_content = RoomContent::default();
@@ -184,5 +251,6 @@ mod tests {
         assert_eq!(room_content.len(), 1);
         room_content.delete_message(&"child".into()).unwrap();
         assert_eq!(room_content.len(), 0);
+        assert!(room_content.threads.is_empty());
     }
 }
@@ -1,19 +1,363 @@
-use enum_iterator::Sequence;
+use chrono::Duration;
+use color_eyre::Result;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display};
+
+use super::room::Room;
+use crate::config::config_duration;
 
 /// Filters used to present a subset of all available rooms.
-#[derive(Clone, Debug, PartialEq, Default, Sequence)]
+#[derive(Clone, Debug, PartialEq, Default)]
 pub(crate) enum RoomsListFilter {
     /// All available rooms
     #[default]
     All,
+    /// Only rooms pinned as favourites
+    Favourites,
     /// Only direct messages
     Direct,
-    /// Only rooms with recent activity
+    /// Only rooms with recent activity (configurable threshold)
     Recent,
     /// Only spaces
     Spaces,
     /// Only rooms with unread messages
     Unread,
+    /// Only rooms muted or snoozed
+    Muted,
     /// Only spaces (not 1-1 chats) with no activity for a long time (configurable threshold)
     InactiveSpaces,
+    /// A filter defined in the config
+    Custom(CustomFilter),
+}
+
+impl RoomsListFilter {
+    /// The filters always available, in the order they are cycled through.
+    const BUILT_IN: [RoomsListFilter; 8] = [
+        RoomsListFilter::All,
+        RoomsListFilter::Favourites,
+        RoomsListFilter::Direct,
+        RoomsListFilter::Recent,
+        RoomsListFilter::Spaces,
+        RoomsListFilter::Unread,
+        RoomsListFilter::Muted,
+        RoomsListFilter::InactiveSpaces,
+    ];
+
+    /// Returns the filter after this one, the built-in filters coming before the custom ones.
+    pub(crate) fn next(&self, custom_filters: &[CustomFilter]) -> RoomsListFilter {
+        let filters = Self::all(custom_filters);
+        let next = self.position_in(&filters).map_or(0, |index| index + 1);
+        filters[next % filters.len()].clone()
+    }
+
+    /// Returns the filter before this one, the built-in filters coming before the custom ones.
+    pub(crate) fn previous(&self, custom_filters: &[CustomFilter]) -> RoomsListFilter {
+        let filters = Self::all(custom_filters);
+        let index = self.position_in(&filters).unwrap_or(0);
+        filters[(index + filters.len() - 1) % filters.len()].clone()
+    }
+
+    fn all(custom_filters: &[CustomFilter]) -> Vec<RoomsListFilter> {
+        Self::BUILT_IN
+            .into_iter()
+            .chain(custom_filters.iter().cloned().map(RoomsListFilter::Custom))
+            .collect()
+    }
+
+    fn position_in(&self, filters: &[RoomsListFilter]) -> Option<usize> {
+        filters.iter().position(|filter| filter == self)
+    }
+
+    /// Returns whether the room is shown with this filter.
+    pub(crate) fn matches(
+        &self,
+        room: &Room,
+        marks: &RoomMarks,
+        thresholds: &ActivityThresholds,
+    ) -> bool {
+        match self {
+            RoomsListFilter::All => true,
+            RoomsListFilter::Favourites => marks.is_favourite,
+            RoomsListFilter::Direct => room.is_direct(),
+            RoomsListFilter::Recent => room.has_activity_since(thresholds.recent),
+            RoomsListFilter::Spaces => room.is_space(),
+            RoomsListFilter::Unread => room.unread,
+            RoomsListFilter::Muted => marks.is_muted,
+            RoomsListFilter::InactiveSpaces => {
+                !room.is_direct()
+                    && !room.has_activity_since(thresholds.inactive)
+                    && !room.is_moderated()
+            }
+            RoomsListFilter::Custom(filter) => filter.matches(room, marks),
+        }
+    }
+}
+
+impl Display for RoomsListFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RoomsListFilter::Custom(filter) => write!(f, "{}", filter.name),
+            filter => write!(f, "{filter:?}"),
+        }
+    }
+}
+
+/// How long without activity a room is recent or inactive, for the built-in filters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct ActivityThresholds {
+    /// Rooms with activity within this duration are recent
+    pub(crate) recent: Duration,
+    /// Rooms without activity for this duration are inactive
+    pub(crate) inactive: Duration,
+}
+
+impl Default for ActivityThresholds {
+    fn default() -> Self {
+        Self {
+            recent: Duration::hours(24),
+            inactive: Duration::days(365),
+        }
+    }
+}
+
+/// The type of the rooms matched by a custom filter.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum RoomKind {
+    Direct,
+    Space,
+}
+
+/// A regular expression matching room titles, given as text in the config.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub(crate) struct TitleRegex(Regex);
+
+impl TryFrom<String> for TitleRegex {
+    type Error = regex::Error;
+
+    fn try_from(regex: String) -> Result<Self, Self::Error> {
+        Regex::new(&regex).map(Self)
+    }
+}
+
+impl From<TitleRegex> for String {
+    fn from(regex: TitleRegex) -> Self {
+        regex.0.as_str().to_string()
+    }
+}
+
+impl PartialEq for TitleRegex {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
+/// A filter defined in the config, listing the rooms that meet all its conditions.
+/// Conditions that are not set match any room.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct CustomFilter {
+    /// Name shown in the title of the rooms list
+    pub(crate) name: String,
+    /// Only direct conversations or only spaces
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) room_type: Option<RoomKind>,
+    /// Names or ids of the teams of the rooms, ignoring case
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) teams: Vec<String>,
+    /// Regular expression matching the titles of the rooms, e.g. `(?i)standup|retro`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) title_regex: Option<TitleRegex>,
+    /// Only rooms with, or without, unread messages
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) unread: Option<bool>,
+    /// Only favourite rooms, or rooms that are not favourites
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) favourite: Option<bool>,
+    /// Only muted or snoozed rooms, or rooms that are not
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) muted: Option<bool>,
+    /// Only rooms with activity in the last hours
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) active_within_hours: Option<u32>,
+    /// Only rooms without activity for days
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) inactive_for_days: Option<u32>,
+}
+
+/// The state of a room that is not kept in the room itself.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct RoomMarks<'a> {
+    /// Name of the team of the room, if known
+    pub(crate) team_name: Option<&'a str>,
+    pub(crate) is_favourite: bool,
+    pub(crate) is_muted: bool,
+}
+
+impl CustomFilter {
+    /// Checks that the durations of the filter are not too long.
+    pub(crate) fn validate(&self) -> Result<()> {
+        if let Some(hours) = self.active_within_hours {
+            config_duration("active_within_hours", Duration::try_hours(hours.into()))?;
+        }
+        if let Some(days) = self.inactive_for_days {
+            config_duration("inactive_for_days", Duration::try_days(days.into()))?;
+        }
+        Ok(())
+    }
+
+    /// Returns true if the room meets all the conditions of the filter.
+    /// Durations too long to be valid are as long as possible.
+    pub(crate) fn matches(&self, room: &Room, marks: &RoomMarks) -> bool {
+        let is_of_kind = |kind: &RoomKind| match kind {
+            RoomKind::Direct => room.is_direct(),
+            RoomKind::Space => room.is_space(),
+        };
+        self.room_type.as_ref().is_none_or(is_of_kind)
+            && (self.teams.is_empty()
+                || self
+                    .teams
+                    .iter()
+                    .any(|team| room.is_in_team(team, marks.team_name)))
+            && self.title_regex.as_ref().is_none_or(|regex| {
+                room.title
+                    .as_ref()
+                    .is_some_and(|title| regex.0.is_match(title))
+            })
+            && self.unread.is_none_or(|unread| room.unread == unread)
+            && self
+                .favourite
+                .is_none_or(|favourite| marks.is_favourite == favourite)
+            && self.muted.is_none_or(|muted| marks.is_muted == muted)
+            && self.active_within_hours.is_none_or(|hours| {
+                room.has_activity_since(Duration::try_hours(hours.into()).unwrap_or(Duration::MAX))
+            })
+            && self.inactive_for_days.is_none_or(|days| {
+                !room.has_activity_since(Duration::try_days(days.into()).unwrap_or(Duration::MAX))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn filter(name: &str) -> CustomFilter {
+        CustomFilter {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_custom_filters_are_cycled_after_built_in_ones() {
+        let custom = [filter("Work"), filter("Standups")];
+        let work = RoomsListFilter::Custom(custom[0].clone());
+        let standups = RoomsListFilter::Custom(custom[1].clone());
+        assert_eq!(RoomsListFilter::InactiveSpaces.next(&custom), work);
+        assert_eq!(work.next(&custom), standups);
+        assert_eq!(standups.next(&custom), RoomsListFilter::All);
+        assert_eq!(RoomsListFilter::All.previous(&custom), standups);
+        assert_eq!(
+            RoomsListFilter::InactiveSpaces.next(&[]),
+            RoomsListFilter::All
+        );
+        // A filter no longer in the config restarts the cycle
+        assert_eq!(
+            RoomsListFilter::Custom(filter("Gone")).next(&custom),
+            RoomsListFilter::All
+        );
+        assert_eq!(standups.to_string(), "Standups");
+        assert_eq!(RoomsListFilter::Unread.to_string(), "Unread");
+    }
+
+    #[test]
+    fn test_custom_filter_matches_all_conditions() {
+        let room = Room {
+            id: "room".to_string(),
+            title: Some("Daily Standup".to_string()),
+            room_type: "group".to_string(),
+            team_id: Some("team".to_string()),
+            last_activity: Utc::now() - Duration::days(2),
+            unread: true,
+            ..Default::default()
+        };
+        let marks = RoomMarks {
+            team_name: Some("Engineering"),
+            is_favourite: false,
+            is_muted: true,
+        };
+        let yaml = r#"
+name: Standups
+room_type: space
+teams: [engineering]
+title_regex: "(?i)standup"
+unread: true
+muted: true
+inactive_for_days: 1
+"#;
+        let standups: CustomFilter = serde_yaml::from_str(yaml).unwrap();
+        assert!(standups.matches(&room, &marks));
+        assert!(filter("Any").matches(&room, &marks));
+
+        let mismatches = [
+            CustomFilter {
+                room_type: Some(RoomKind::Direct),
+                ..filter("Direct")
+            },
+            CustomFilter {
+                teams: vec!["Sales".to_string()],
+                ..filter("Sales")
+            },
+            CustomFilter {
+                title_regex: Some(TitleRegex::try_from("^Retro".to_string()).unwrap()),
+                ..filter("Retros")
+            },
+            CustomFilter {
+                favourite: Some(true),
+                ..filter("Favourites")
+            },
+            CustomFilter {
+                active_within_hours: Some(24),
+                ..filter("Recent")
+            },
+        ];
+        for filter in mismatches {
+            assert!(!filter.matches(&room, &marks), "{}", filter.name);
+        }
+    }
+
+    #[test]
+    fn test_durations_out_of_range() {
+        let room = Room {
+            last_activity: Utc::now(),
+            ..Default::default()
+        };
+        let marks = RoomMarks::default();
+        let active = CustomFilter {
+            active_within_hours: Some(u32::MAX),
+            ..filter("Active")
+        };
+        let inactive = CustomFilter {
+            inactive_for_days: Some(u32::MAX),
+            ..filter("Inactive")
+        };
+        assert!(active.matches(&room, &marks));
+        assert!(!inactive.matches(&room, &marks));
+        assert!(active.validate().is_err());
+        assert!(inactive.validate().is_err());
+        let year = CustomFilter {
+            inactive_for_days: Some(365),
+            ..filter("Year")
+        };
+        assert!(year.validate().is_ok());
+    }
+
+    #[test]
+    fn test_invalid_title_regex_is_rejected() {
+        let yaml = "name: Broken\ntitle_regex: \"(unclosed\"\n";
+        assert!(serde_yaml::from_str::<CustomFilter>(yaml).is_err());
+    }
 }
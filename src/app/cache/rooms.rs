@@ -1,9 +1,10 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use super::room::{Room, RoomId};
-use super::room_list_filter::RoomsListFilter;
+use super::room_list_filter::{ActivityThresholds, RoomMarks, RoomsListFilter};
+use super::MessageId;
 
-use chrono::Duration;
+use chrono::{DateTime, Utc};
 use log::*;
 #[derive(Default, Debug)]
 pub(crate) struct Rooms {
@@ -11,11 +12,16 @@ pub(crate) struct Rooms {
     sorted_rooms: Vec<Room>,
     /// Set of rooms for which we requested room info.
     requested_rooms: HashSet<RoomId>,
+    /// Read status received for rooms that are not loaded yet: the last message seen and when.
+    pending_read_status: HashMap<RoomId, (Option<MessageId>, DateTime<Utc>)>,
+    /// Incremented when rooms change, to tell when lists of rooms are outdated.
+    generation: u64,
 }
 
 impl Rooms {
     /// Returns a mutable reference to the room for given id, if found.
     pub(crate) fn room_with_id_mut(&mut self, id: &RoomId) -> Option<&mut Room> {
+        self.generation += 1;
         self.sorted_rooms.iter_mut().find(|room| room.id == *id)
     }
 
@@ -32,13 +38,20 @@ impl Rooms {
     /// Adds or updates a `Room` in the store. If the room already exists, it is updated.
     /// The list is kept in order of last_activity.
     pub(crate) fn update_with_room(&mut self, room: &Room) {
+        self.generation += 1;
         let mut room = room.clone();
         // If the room is already in the list
         if let Some(index) = self.sorted_rooms.iter().position(|r| r.id == room.id) {
-            // Conserve the unread attribute
+            // Conserve the read status
             room.unread = self.sorted_rooms[index].unread;
+            room.last_seen_id = self.sorted_rooms[index].last_seen_id.clone();
             // Remove the room from the store
             self.sorted_rooms.remove(index);
+        } else if let Some((last_seen_id, last_seen_date)) =
+            self.pending_read_status.remove(&room.id)
+        {
+            room.unread = room.last_activity > last_seen_date;
+            room.last_seen_id = last_seen_id;
         }
         // The room is being added, remove it from the requested list
         self.requested_rooms.remove(&room.id);
@@ -49,6 +62,7 @@ impl Rooms {
     /// Adds a room to the list of rooms, keeping the list sorted by last activity.
     /// It is an error to use this if the room already exists.
     fn add_room_sorted(&mut self, room: Room) {
+        self.generation += 1;
         let pos = self
             .sorted_rooms
             .partition_point(|r| r.last_activity > room.last_activity);
@@ -71,6 +85,7 @@ impl Rooms {
     /// Mark a room as unread.
     pub(crate) fn mark_unread(&mut self, id: &RoomId) {
         debug!("Marking room {id} unread");
+        self.generation += 1;
         for room in &mut self.sorted_rooms {
             if room.id == *id {
                 room.unread = true;
@@ -82,6 +97,7 @@ impl Rooms {
     /// Mark a room as read.
     pub(crate) fn mark_read(&mut self, id: &RoomId) {
         debug!("Marking room {id} read");
+        self.generation += 1;
         for room in &mut self.sorted_rooms {
             if room.id == *id {
                 room.unread = false;
@@ -90,35 +106,77 @@ impl Rooms {
         }
     }
 
-    /// Returns an iterator to rooms with the given filter.
-    #[allow(clippy::needless_lifetimes)]
-    pub(crate) fn rooms_filtered_by<'a>(
+    /// Sets the read status of a room as known by the server: the room is unread if there was
+    /// activity after the last message we have seen, from any client.
+    /// The status of a room that is not loaded yet is applied when it is.
+    pub(crate) fn set_read_status(
+        &mut self,
+        id: &RoomId,
+        last_seen_id: Option<MessageId>,
+        last_seen_date: DateTime<Utc>,
+    ) {
+        match self.room_with_id_mut(id) {
+            Some(room) => {
+                room.unread = room.last_activity > last_seen_date;
+                room.last_seen_id = last_seen_id;
+            }
+            None => {
+                self.pending_read_status
+                    .insert(id.clone(), (last_seen_id, last_seen_date));
+            }
+        }
+    }
+
+    /// Marks a room read up to the given message.
+    /// Returns false if that message was already seen, so no acknowledgement needs to be sent.
+    pub(crate) fn mark_seen(&mut self, id: &RoomId, message_id: &MessageId) -> bool {
+        match self.room_with_id_mut(id) {
+            Some(room) => {
+                room.unread = false;
+                if room.last_seen_id.as_ref() == Some(message_id) {
+                    return false;
+                }
+                room.last_seen_id = Some(message_id.clone());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns an iterator to rooms with the given filter, muted rooms last.
+    /// The state of the rooms kept elsewhere is given by `marks`.
+    pub(crate) fn rooms_filtered_by<'a, F>(
         &'a self,
         filter: &'a RoomsListFilter,
-    ) -> impl Iterator<Item = &'a Room> {
-        self.sorted_rooms.iter().filter(move |room| match filter {
-            RoomsListFilter::All => true,
-            RoomsListFilter::Direct => room.is_direct(),
-            RoomsListFilter::Recent => room.has_activity_since(Duration::hours(24)),
-            RoomsListFilter::Spaces => room.is_space(),
-            RoomsListFilter::Unread => room.unread,
-            RoomsListFilter::InactiveSpaces => {
-                !room.is_direct()
-                    && !room.has_activity_since(Duration::days(365))
-                    && !room.is_moderated()
-            }
-        })
+        thresholds: &'a ActivityThresholds,
+        marks: F,
+    ) -> impl Iterator<Item = &'a Room>
+    where
+        F: Fn(&Room) -> RoomMarks<'a> + Copy + 'a,
+    {
+        let filtered = move |is_muted: bool| {
+            self.sorted_rooms.iter().filter(move |room| {
+                let marks = marks(room);
+                marks.is_muted == is_muted && filter.matches(room, &marks, thresholds)
+            })
+        };
+        filtered(false).chain(filtered(true))
+    }
+
+    /// Returns a number that changes whenever rooms change.
+    pub(crate) fn generation(&self) -> u64 {
+        self.generation
     }
 
     /// Remove a room completely
     pub(crate) fn remove_room(&mut self, id: &RoomId) {
         debug!("Removing room {id}");
+        self.generation += 1;
         self.sorted_rooms.retain(|room| room.id != *id);
         self.requested_rooms.remove(id);
     }
 
     /// Returns a reference to the sorted rooms list
-    #[allow(dead_code)]
     pub(crate) fn sorted_rooms(&self) -> &Vec<Room> {
         &self.sorted_rooms
     }
@@ -128,7 +186,7 @@ impl Rooms {
 mod tests {
     use super::*;
     use crate::app::cache::room::RoomId;
-    use chrono::{TimeZone, Utc};
+    use chrono::{Duration, TimeZone, Utc};
 
     #[test]
     fn test_update_with_room() {
@@ -198,6 +256,63 @@ mod tests {
         assert_eq!(rooms.sorted_rooms[2].id, "1");
     }
 
+    #[test]
+    fn test_set_read_status() {
+        let mut rooms = Rooms::default();
+        for (id, day) in [("1", 1), ("2", 3)] {
+            rooms.update_with_room(&Room {
+                id: String::from(id) as RoomId,
+                last_activity: Utc.with_ymd_and_hms(2020, 1, day, 0, 0, 0).unwrap(),
+                ..Default::default()
+            });
+        }
+        let last_seen_date = Utc.with_ymd_and_hms(2020, 1, 2, 0, 0, 0).unwrap();
+        rooms.set_read_status(&"1".to_string(), Some("m1".to_string()), last_seen_date);
+        rooms.set_read_status(&"2".to_string(), Some("m2".to_string()), last_seen_date);
+        let room1 = rooms.room_with_id(&"1".to_string()).unwrap();
+        assert!(!room1.unread);
+        assert_eq!(room1.last_seen_id.as_deref(), Some("m1"));
+        assert!(rooms.room_with_id(&"2".to_string()).unwrap().unread);
+
+        // The read status is kept when the room is updated
+        let room2 = Room {
+            id: String::from("2") as RoomId,
+            last_activity: Utc.with_ymd_and_hms(2020, 1, 4, 0, 0, 0).unwrap(),
+            ..Default::default()
+        };
+        rooms.update_with_room(&room2);
+        let room2 = rooms.room_with_id(&"2".to_string()).unwrap();
+        assert!(room2.unread);
+        assert_eq!(room2.last_seen_id.as_deref(), Some("m2"));
+
+        // The read status of a room loaded later is applied when it is loaded
+        rooms.set_read_status(&"3".to_string(), Some("m3".to_string()), last_seen_date);
+        rooms.update_with_room(&Room {
+            id: String::from("3") as RoomId,
+            last_activity: Utc.with_ymd_and_hms(2020, 1, 5, 0, 0, 0).unwrap(),
+            ..Default::default()
+        });
+        let room3 = rooms.room_with_id(&"3".to_string()).unwrap();
+        assert!(room3.unread);
+        assert_eq!(room3.last_seen_id.as_deref(), Some("m3"));
+    }
+
+    #[test]
+    fn test_mark_seen() {
+        let mut rooms = Rooms::default();
+        let id = String::from("1") as RoomId;
+        rooms.update_with_room(&Room {
+            id: id.clone(),
+            unread: true,
+            ..Default::default()
+        });
+        assert!(rooms.mark_seen(&id, &"m1".to_string()));
+        assert!(!rooms.room_with_id(&id).unwrap().unread);
+        assert!(!rooms.mark_seen(&id, &"m1".to_string()));
+        assert!(rooms.mark_seen(&id, &"m2".to_string()));
+        assert!(!rooms.mark_seen(&"unknown".to_string(), &"m1".to_string()));
+    }
+
     #[test]
     fn test_room_exists_or_requested() {
         let mut rooms = Rooms::default();
@@ -231,6 +346,52 @@ mod tests {
         assert_eq!(rooms.requested_rooms.len(), 0);
     }
 
+    #[test]
+    fn test_favourites_filter() {
+        let mut rooms = Rooms::default();
+        for id in ["1", "2"] {
+            rooms.update_with_room(&Room {
+                id: String::from(id) as RoomId,
+                ..Default::default()
+            });
+        }
+        let thresholds = ActivityThresholds::default();
+        let filtered: Vec<_> = rooms
+            .rooms_filtered_by(&RoomsListFilter::Favourites, &thresholds, |room| {
+                RoomMarks {
+                    is_favourite: room.id == "2",
+                    ..Default::default()
+                }
+            })
+            .map(|room| room.id.as_str())
+            .collect();
+        assert_eq!(filtered, vec!["2"]);
+    }
+
+    #[test]
+    fn test_muted_rooms_are_listed_last() {
+        let mut rooms = Rooms::default();
+        for (id, minutes) in [("1", 1), ("2", 2), ("3", 3)] {
+            rooms.update_with_room(&Room {
+                id: String::from(id) as RoomId,
+                last_activity: Utc.with_ymd_and_hms(2020, 1, 1, 0, minutes, 0).unwrap(),
+                ..Default::default()
+            });
+        }
+        let thresholds = ActivityThresholds::default();
+        let ids = |filter| {
+            rooms
+                .rooms_filtered_by(filter, &thresholds, |room| RoomMarks {
+                    is_muted: room.id == "3",
+                    ..Default::default()
+                })
+                .map(|room| room.id.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(&RoomsListFilter::All), vec!["2", "1", "3"]);
+        assert_eq!(ids(&RoomsListFilter::Muted), vec!["3"]);
+    }
+
     #[test]
     fn test_inactive_spaces_filter_excludes_moderated() {
         let mut rooms = Rooms::default();
@@ -280,12 +441,28 @@ mod tests {
         rooms.update_with_room(&direct_message);
         rooms.update_with_room(&recent_space);
 
+        let thresholds = ActivityThresholds::default();
         let filtered: Vec<&Room> = rooms
-            .rooms_filtered_by(&RoomsListFilter::InactiveSpaces)
+            .rooms_filtered_by(&RoomsListFilter::InactiveSpaces, &thresholds, |_| {
+                RoomMarks::default()
+            })
             .collect();
 
         // Only the inactive, non-moderated space should be included
         assert_eq!(filtered.len(), 1);
         assert_eq!(filtered[0].id, "inactive");
+
+        // With a shorter threshold, the recent space is inactive too
+        let thresholds = ActivityThresholds {
+            inactive: Duration::days(5),
+            ..Default::default()
+        };
+        let filtered: Vec<_> = rooms
+            .rooms_filtered_by(&RoomsListFilter::InactiveSpaces, &thresholds, |_| {
+                RoomMarks::default()
+            })
+            .map(|room| room.id.as_str())
+            .collect();
+        assert_eq!(filtered, vec!["recent", "inactive"]);
     }
 }
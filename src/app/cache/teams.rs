@@ -9,6 +9,8 @@ pub type TeamId = String;
 pub(crate) struct Teams {
     teams_by_id: HashMap<TeamId, Team>,
     requested_teams: HashSet<TeamId>,
+    /// Incremented when a team is added, to tell when lists showing team names are outdated.
+    generation: u64,
 }
 
 impl Teams {
@@ -16,6 +18,12 @@ impl Teams {
     pub(crate) fn add(&mut self, team: Team) {
         self.requested_teams.remove(&team.id);
         self.teams_by_id.insert(team.id.clone(), team);
+        self.generation += 1;
+    }
+
+    /// Returns a number that changes whenever a team is added.
+    pub(crate) fn generation(&self) -> u64 {
+        self.generation
     }
 
     /// Returns a reference to the team for given id, if found.
@@ -23,6 +31,13 @@ impl Teams {
         self.teams_by_id.get(id)
     }
 
+    /// Returns the known teams, sorted by name.
+    pub(crate) fn teams(&self) -> Vec<&Team> {
+        let mut teams: Vec<_> = self.teams_by_id.values().collect();
+        teams.sort_by(|a, b| a.name.cmp(&b.name));
+        teams
+    }
+
     /// Adds a `TeamId` to the set of requested teams
     pub(crate) fn add_requested(&mut self, team_id: TeamId) {
         self.requested_teams.insert(team_id);
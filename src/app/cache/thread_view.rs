@@ -0,0 +1,156 @@
+// app/cache/thread_view.rs
+
+//! How the threads of a room are presented: expanded, collapsed or a single focused thread.
+
+use std::collections::HashSet;
+
+use webex::Message;
+
+use super::MessageId;
+
+/// Presentation of the threads in the messages list.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct ThreadView {
+    /// Whether threads are reduced to their first message and a count of replies
+    collapsed: bool,
+    /// Threads shown in full while the others are collapsed
+    expanded: HashSet<MessageId>,
+    /// The only thread shown, if any
+    focused: Option<MessageId>,
+}
+
+/// A message as shown in the messages list.
+#[derive(Debug)]
+pub(crate) struct DisplayedMessage<'a> {
+    pub(crate) message: &'a Message,
+    /// Number of replies hidden under the message when its thread is collapsed
+    pub(crate) hidden_replies: usize,
+}
+
+/// Returns the id of the thread of a message, which is the id of its first message.
+pub(crate) fn thread_id_of(message: &Message) -> Option<&MessageId> {
+    message.parent_id.as_ref().or(message.id.as_ref())
+}
+
+impl ThreadView {
+    pub(crate) fn is_collapsed(&self) -> bool {
+        self.collapsed
+    }
+
+    /// Switches between collapsed and expanded threads.
+    /// Threads expanded individually are collapsed again.
+    pub(crate) fn toggle_collapsed(&mut self) {
+        self.collapsed = !self.collapsed;
+        self.expanded.clear();
+    }
+
+    /// Expands a thread if it is collapsed, or collapses it if it was expanded.
+    pub(crate) fn toggle_expanded(&mut self, thread_id: &MessageId) {
+        if !self.expanded.remove(thread_id) {
+            self.expanded.insert(thread_id.clone());
+        }
+    }
+
+    /// Returns whether the messages of the thread are all shown.
+    pub(crate) fn is_expanded(&self, thread_id: Option<&MessageId>) -> bool {
+        match (&self.focused, thread_id) {
+            (Some(_), _) => true,
+            (None, _) if !self.collapsed => true,
+            (None, Some(id)) => self.expanded.contains(id),
+            (None, None) => false,
+        }
+    }
+
+    pub(crate) fn focused(&self) -> Option<&MessageId> {
+        self.focused.as_ref()
+    }
+
+    /// Shows only the given thread, or all threads with `None`.
+    pub(crate) fn set_focused(&mut self, thread_id: Option<MessageId>) {
+        self.focused = thread_id;
+    }
+
+    /// Returns whether the thread is shown, i.e. there is no focus or it is focused.
+    pub(crate) fn shows_thread(&self, thread_id: Option<&MessageId>) -> bool {
+        match &self.focused {
+            Some(focused) => thread_id == Some(focused),
+            None => true,
+        }
+    }
+
+    /// Changes the view so that the message is displayed.
+    pub(crate) fn reveal(&mut self, message: &Message) {
+        let thread_id = thread_id_of(message);
+        if !self.shows_thread(thread_id) {
+            self.focused = None;
+        }
+        if let Some(thread_id) = thread_id {
+            if !self.is_expanded(Some(thread_id)) {
+                self.expanded.insert(thread_id.clone());
+            }
+        }
+    }
+
+    /// Forgets the threads expanded or focused, when they belong to another room.
+    /// Whether threads are collapsed is kept.
+    pub(crate) fn reset_threads(&mut self) {
+        self.expanded.clear();
+        self.focused = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(id: &str, parent_id: Option<&str>) -> Message {
+        Message {
+            id: Some(id.to_string()),
+            parent_id: parent_id.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_collapse_and_expand() {
+        let mut view = ThreadView::default();
+        let thread = "t1".to_string();
+        assert!(view.is_expanded(Some(&thread)));
+        view.toggle_collapsed();
+        assert!(!view.is_expanded(Some(&thread)));
+        view.toggle_expanded(&thread);
+        assert!(view.is_expanded(Some(&thread)));
+        view.toggle_expanded(&thread);
+        assert!(!view.is_expanded(Some(&thread)));
+        view.toggle_expanded(&thread);
+        // Collapsing again forgets the expanded threads
+        view.toggle_collapsed();
+        view.toggle_collapsed();
+        assert!(!view.is_expanded(Some(&thread)));
+    }
+
+    #[test]
+    fn test_focus() {
+        let mut view = ThreadView::default();
+        view.toggle_collapsed();
+        view.set_focused(Some("t1".to_string()));
+        assert!(view.shows_thread(Some(&"t1".to_string())));
+        assert!(!view.shows_thread(Some(&"t2".to_string())));
+        // The focused thread is shown in full
+        assert!(view.is_expanded(Some(&"t1".to_string())));
+        view.reset_threads();
+        assert!(view.shows_thread(Some(&"t2".to_string())));
+        assert!(view.is_collapsed());
+    }
+
+    #[test]
+    fn test_reveal() {
+        let mut view = ThreadView::default();
+        view.toggle_collapsed();
+        view.set_focused(Some("t1".to_string()));
+        let reply = message("reply", Some("t2"));
+        view.reveal(&reply);
+        assert_eq!(view.focused(), None);
+        assert!(view.is_expanded(thread_id_of(&reply)));
+    }
+}
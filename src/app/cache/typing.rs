@@ -0,0 +1,117 @@
+// app/cache/typing.rs
+
+//! Persons currently typing in rooms.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use super::persons::PersonId;
+use super::room::RoomId;
+
+/// How long a person is considered typing after the last typing event.
+/// Webex clients repeat the event while the user keeps typing.
+const TYPING_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Typing persons by room, with the time they were last seen typing.
+#[derive(Debug, Default)]
+pub(crate) struct Typing {
+    by_room: HashMap<RoomId, HashMap<PersonId, Instant>>,
+}
+
+impl Typing {
+    /// Records that a person started (or is still) typing in a room.
+    pub(crate) fn start(&mut self, room_id: &str, person_id: &str, now: Instant) {
+        self.by_room
+            .entry(room_id.to_string())
+            .or_default()
+            .insert(person_id.to_string(), now);
+    }
+
+    /// Records that a person stopped typing, e.g. because their message arrived.
+    pub(crate) fn stop(&mut self, room_id: &str, person_id: &str) {
+        if let Some(persons) = self.by_room.get_mut(room_id) {
+            persons.remove(person_id);
+        }
+    }
+
+    /// Returns the persons typing in the room, sorted for a stable display.
+    pub(crate) fn persons_typing_in(&self, room_id: &str, now: Instant) -> Vec<&PersonId> {
+        let mut persons: Vec<_> = self
+            .by_room
+            .get(room_id)
+            .into_iter()
+            .flatten()
+            .filter(|(_, since)| now.duration_since(**since) < TYPING_TIMEOUT)
+            .map(|(person_id, _)| person_id)
+            .collect();
+        persons.sort();
+        persons
+    }
+
+    /// Forgets the typing events that expired.
+    pub(crate) fn remove_expired(&mut self, now: Instant) {
+        for persons in self.by_room.values_mut() {
+            persons.retain(|_, since| now.duration_since(*since) < TYPING_TIMEOUT);
+        }
+        self.by_room.retain(|_, persons| !persons.is_empty());
+    }
+}
+
+/// Returns a short sentence saying who is typing, if anybody.
+pub(crate) fn typing_description(names: &[String]) -> Option<String> {
+    match names {
+        [] => None,
+        [name] => Some(format!("{name} is typing…")),
+        [first, second] => Some(format!("{first} and {second} are typing…")),
+        _ => Some(String::from("Several people are typing…")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[test]
+    fn test_typing_expires() {
+        let mut typing = Typing::default();
+        let start = Instant::now();
+        typing.start("room", "bob", start);
+        typing.start("room", "alice", start + Duration::from_secs(5));
+        assert_eq!(
+            typing.persons_typing_in("room", start + Duration::from_secs(6)),
+            vec!["alice", "bob"]
+        );
+        assert_eq!(
+            typing.persons_typing_in("room", start + Duration::from_secs(11)),
+            vec!["alice"]
+        );
+        assert!(typing.persons_typing_in("other", start).is_empty());
+
+        typing.remove_expired(start + Duration::from_secs(20));
+        assert!(typing.by_room.is_empty());
+    }
+
+    #[test]
+    fn test_typing_stop() {
+        let mut typing = Typing::default();
+        let now = Instant::now();
+        typing.start("room", "bob", now);
+        typing.stop("room", "bob");
+        typing.stop("other", "bob");
+        assert!(typing.persons_typing_in("room", now).is_empty());
+    }
+
+    #[rstest(
+        names,
+        expected,
+        case(vec![], None),
+        case(vec!["Alice"], Some("Alice is typing…")),
+        case(vec!["Alice", "Bob"], Some("Alice and Bob are typing…")),
+        case(vec!["Alice", "Bob", "Carol"], Some("Several people are typing…"))
+    )]
+    fn test_typing_description(names: Vec<&str>, expected: Option<&str>) {
+        let names: Vec<String> = names.into_iter().map(String::from).collect();
+        assert_eq!(typing_description(&names).as_deref(), expected);
+    }
+}
@@ -2,10 +2,23 @@
 
 //! Callback functions called by the `Teams` thread (under locking)
 
+use chrono::{DateTime, Utc};
 use std::collections::HashSet;
+use std::time::Instant;
 
 use super::{
-    cache::{room::RoomId, MessageId},
+    cache::{
+        attachments::AttachmentInfo,
+        meeting_info::MeetingInfo,
+        members::{Member, MembershipId},
+        persons::PersonId,
+        room::{Room, RoomId},
+        MessageId,
+    },
+    outbox::OutboxId,
+    picker::PickerKind,
+    prompt::{Prompt, PromptKind},
+    state::ActivePane,
     App, Priority,
 };
 use crate::teams::app_handler::AppCmdEvent;
@@ -20,6 +33,8 @@ impl App<'_> {
         self.dispatch_to_teams(AppCmdEvent::WhoAmI(), &Priority::Low);
         // Some more heavy tasks that we put after init to ensure quick startup
         self.dispatch_to_teams(AppCmdEvent::ListAllRooms(), &Priority::Low);
+        // Queued after the rooms so that their read status can be applied
+        self.dispatch_to_teams(AppCmdEvent::UpdateReadStatus(), &Priority::Low);
     }
 
     /// Saves `me` as the user of the client
@@ -29,8 +44,15 @@ impl App<'_> {
         self.cb_person_updated(person.to_owned());
     }
 
-    /// Callback when a message was sent. Add the message to the room immediately.
-    pub(crate) fn cb_message_sent(&mut self, message: &Message) {
+    /// Callback when a message was sent, by an outbox entry if `outbox_id` is set.
+    /// The message shown while it was being sent is replaced with that from Webex, which may
+    /// already have come as an event.
+    pub(crate) fn cb_message_sent(&mut self, outbox_id: Option<OutboxId>, message: &Message) {
+        let entry =
+            outbox_id.and_then(|id| self.state.outbox.entries().iter().find(|e| e.id == id));
+        if let Some(entry) = entry.cloned() {
+            self.state.remove_local_message(entry.id, &entry.command);
+        }
         self.cb_message_received(message, false);
     }
 
@@ -67,12 +89,26 @@ impl App<'_> {
             .and_then(|msg| msg.id.clone());
 
         self.add_messages_to_cache(messages, update_unread, room_id);
+        // The authors are done typing
+        for person_id in messages.iter().filter_map(|msg| msg.person_id.as_ref()) {
+            self.state.cache.typing.stop(room_id, person_id);
+        }
         self.request_missing_parent_messages(messages, room_id);
         self.update_message_selection_in_active_room(room_id, selected_message_id);
         self.request_missing_room_info(room_id);
         self.request_missing_persons(messages);
+        if self.state.is_active_room(room_id) {
+            self.request_attachment_info_of_active_room();
+        }
+        // Messages arriving may match the search
+        self.state.update_message_search();
+        // Messages arriving in the room being viewed are read
+        if self.state.is_active_room(room_id) {
+            self.acknowledge_active_room();
+        }
         // Update cursor to follow active room in case message arrival changed room order
         self.state.update_room_selection_with_active_room();
+        self.continue_showing_message(room_id, messages.len());
     }
 
     /// Adds messages to the cache for a given room, and updates the unread status if requested and
@@ -86,7 +122,11 @@ impl App<'_> {
         // messages came in with most recent first, reverse before adding them to cache
         for msg in messages.iter().rev() {
             if update_unread && !self.state.cache.is_me(&msg.person_id) {
-                self.state.cache.rooms.mark_unread(room_id);
+                match self.marks_room_unread(room_id, msg) {
+                    true => self.state.cache.rooms.mark_unread(room_id),
+                    // Unread once the snooze ends
+                    false => self.state.mutes.record_missed(room_id, Utc::now()),
+                }
             }
             if let Err(err) = self.state.cache.add_message(msg) {
                 error!("Error adding received message to store: {err}");
@@ -94,6 +134,13 @@ impl App<'_> {
         }
     }
 
+    /// Returns true if a new message marks its room unread: the room is not muted, or it
+    /// mentions us and mentions are configured to break through mutes.
+    fn marks_room_unread(&self, room_id: &RoomId, msg: &Message) -> bool {
+        !self.state.is_muted(room_id)
+            || (self.state.config.mentions_break_mute && self.state.cache.mentions_me(msg))
+    }
+
     /// Request info on parent messages referenced in the messages that are not in cache.
     fn request_missing_parent_messages(&mut self, messages: &[Message], room_id: &String) {
         // Identify referenced parent messages that are not in cache
@@ -126,10 +173,7 @@ impl App<'_> {
         // If the room is active, maintain the message selection as we are adding messages.
         if self.state.is_active_room(room_id) {
             if let Some(selected_message_id) = selected_message_id {
-                if let Some(msg_index) = self
-                    .state
-                    .cache
-                    .index_of_message_in_room(&selected_message_id, room_id)
+                if let Some(msg_index) = self.state.index_of_displayed_message(&selected_message_id)
                 {
                     self.state.messages_list.select_index(msg_index);
                 }
@@ -165,12 +209,180 @@ impl App<'_> {
         }
     }
 
+    /// Request info on attached files that are not in cache.
+    pub(crate) fn request_missing_attachment_info(&mut self, urls: &[String]) {
+        for url in urls {
+            if !self.state.cache.attachments.exists_or_requested(url) {
+                self.state.cache.attachments.add_requested(url);
+                self.dispatch_to_teams(
+                    AppCmdEvent::UpdateAttachmentInfo(url.clone()),
+                    &Priority::Low,
+                );
+            }
+        }
+    }
+
+    /// Request info on the files attached to the messages of the active room that are not
+    /// in cache. Files in other rooms are only looked up once their room is shown.
+    pub(crate) fn request_attachment_info_of_active_room(&mut self) {
+        let Some(room_id) = self.state.rooms_list.active_room_id() else {
+            return;
+        };
+        let urls: Vec<_> = self
+            .state
+            .cache
+            .messages_in_room(room_id)
+            .filter_map(|msg| msg.files.clone())
+            .flatten()
+            .collect();
+        self.request_missing_attachment_info(&urls);
+    }
+
+    /// Callback when information about an attached file is received.
+    /// Saves it in the store and updates the picker label if the file is listed there.
+    pub(crate) fn cb_attachment_info_updated(&mut self, url: &str, info: AttachmentInfo) {
+        if let Some(picker) = self.state.picker.as_mut() {
+            picker.set_label(url, info.description());
+        }
+        self.state.cache.attachments.insert(url.to_string(), info);
+    }
+
+    /// Callback when the members of a room could not be retrieved, they can be requested again.
+    pub(crate) fn cb_room_members_failed(&mut self, room_id: &RoomId) {
+        self.state.cache.members.remove_requested(room_id);
+    }
+
+    /// Callback when the members of a room are received.
+    /// Saves them in the store and refreshes the mention picker and the members pane if they
+    /// are open for that room.
+    pub(crate) fn cb_room_members_received(&mut self, room_id: &RoomId, members: Vec<Member>) {
+        // Names missing from the memberships are resolved through the persons
+        for member in members
+            .iter()
+            .filter(|member| member.display_name.is_none())
+        {
+            if !self
+                .state
+                .cache
+                .persons
+                .exists_or_requested(&member.person_id)
+            {
+                self.state.cache.persons.add_requested(&member.person_id);
+                self.dispatch_to_teams(
+                    AppCmdEvent::UpdatePerson(member.person_id.clone()),
+                    &Priority::Low,
+                );
+            }
+        }
+        self.state.cache.members.insert(room_id.clone(), members);
+        if !self.state.is_active_room(room_id) {
+            return;
+        }
+        self.update_members_pane();
+        let items = self.state.mention_items();
+        if let Some(picker) = self
+            .state
+            .picker
+            .as_mut()
+            .filter(|picker| picker.kind() == &PickerKind::Mentions)
+        {
+            picker.set_items(items);
+        }
+    }
+
+    /// Callback when a member was added to a room.
+    pub(crate) fn cb_member_added(&mut self, room_id: &RoomId, member: Member) {
+        self.state.cache.members.add(room_id, member);
+        if self.state.is_active_room(room_id) {
+            self.update_members_pane();
+        }
+    }
+
+    /// Callback when a member was removed from a room.
+    pub(crate) fn cb_member_removed(&mut self, room_id: &RoomId, membership_id: &MembershipId) {
+        self.state.cache.members.remove(room_id, membership_id);
+        if self.state.is_active_room(room_id) {
+            self.update_members_pane();
+        }
+    }
+
+    /// Keeps the selection of the members pane within the members of the active room,
+    /// and the actions in line with our rights to manage the room.
+    fn update_members_pane(&mut self) {
+        if self.state.show_members {
+            let nb_members = self.state.active_room_members().len();
+            match self.state.members_list.selected_index() {
+                Some(_) => self.state.members_list.clamp_selection(nb_members),
+                None => self.state.members_list.select_first(nb_members),
+            }
+        }
+        self.state.update_actions(self.state.active_pane.clone());
+    }
+
+    /// Callback when our memberships are received, with what we last read in each room.
+    /// Updates the unread status of the rooms.
+    pub(crate) fn cb_read_status_received(&mut self, memberships: Vec<Member>) {
+        for membership in memberships {
+            let (Some(room_id), Some(last_seen_date)) =
+                (membership.room_id, membership.last_seen_date)
+            else {
+                continue;
+            };
+            match DateTime::parse_from_rfc3339(&last_seen_date) {
+                Ok(date) => {
+                    self.state.cache.rooms.set_read_status(
+                        &room_id,
+                        membership.last_seen_id,
+                        date.with_timezone(&Utc),
+                    );
+                    self.state.hide_unread_of_muted_room(&room_id);
+                }
+                Err(err) => error!("Invalid last seen date {last_seen_date}: {err}"),
+            }
+        }
+        // The room being viewed is read
+        self.acknowledge_active_room();
+        // Update cursor to follow active room since the unread filter may have changed
+        self.state.update_room_selection_with_active_room();
+    }
+
+    /// Callback when one of our other clients read a room.
+    pub(crate) fn cb_room_read_elsewhere(&mut self, room_id: &RoomId) {
+        self.state.cache.rooms.mark_read(room_id);
+        self.state.update_room_selection_with_active_room();
+    }
+
+    /// Callback when we submitted the card of a message, from this or another client.
+    pub(crate) fn cb_card_submitted(&mut self, message_id: &MessageId) {
+        self.state.cache.submitted_cards.insert(message_id.clone());
+    }
+
+    /// Callback when a command of the outbox was delivered.
+    pub(crate) fn cb_outbox_delivered(&mut self, id: OutboxId) {
+        self.state.outbox.delivered(id);
+    }
+
+    /// Callback when a command of the outbox could not be delivered.
+    /// It is retried later if `retry` is true, otherwise it waits for the user.
+    pub(crate) fn cb_outbox_failed(&mut self, id: OutboxId, error: String, retry: bool) {
+        self.state.outbox.failed(id, error, retry, Utc::now());
+        if !retry {
+            warn!("Press O to resend or discard what could not be delivered");
+        }
+    }
+
     /// Callback when room information is received.
     /// Saves the room info in the store and updates cursor to follow active room.
     pub(crate) fn cb_room_updated(&mut self, webex_room: webex::Room) {
         let team_id = webex_room.team_id.clone();
         let room_title = webex_room.title.clone().unwrap_or_default();
-        self.state.cache.rooms.update_with_room(&webex_room.into());
+        let room: Room = webex_room.into();
+        // A new room gets the read status received before it, if any
+        let is_new = self.state.cache.rooms.room_with_id(&room.id).is_none();
+        self.state.cache.rooms.update_with_room(&room);
+        if is_new {
+            self.state.hide_unread_of_muted_room(&room.id);
+        }
         // Update cursor to follow active room since list order may have changed
         self.state.update_room_selection_with_active_room();
 
@@ -187,6 +399,30 @@ impl App<'_> {
         }
     }
 
+    /// Callback when a page of the list of all rooms is received.
+    pub(crate) fn cb_rooms_page_received(&mut self, webex_rooms: Vec<webex::Room>) {
+        if let Some(nb_rooms) = &mut self.state.rooms_loading_progress {
+            *nb_rooms += webex_rooms.len();
+        }
+        for webex_room in webex_rooms {
+            self.cb_room_updated(webex_room);
+        }
+    }
+
+    /// Callback when a room was renamed, locked or unlocked by us.
+    /// Locking a room changes its moderators, so its members are refreshed.
+    pub(crate) fn cb_room_settings_updated(&mut self, webex_room: webex::Room) {
+        let room_id = webex_room.id.clone();
+        self.cb_room_updated(webex_room);
+        self.dispatch_to_teams(AppCmdEvent::ListRoomMembers(room_id), &Priority::Low);
+        self.state.update_actions(self.state.active_pane.clone());
+    }
+
+    /// Callback when the links to join the meeting of a room are received.
+    pub(crate) fn cb_meeting_info_updated(&mut self, room_id: &RoomId, info: MeetingInfo) {
+        self.state.cache.meeting_infos.insert(room_id.clone(), info);
+    }
+
     /// Callback when team information is received.
     /// Saves the team info in the store.
     pub(crate) fn cb_team_updated(&mut self, team: webex::Team) {
@@ -199,6 +435,78 @@ impl App<'_> {
         self.state.cache.persons.insert(person);
     }
 
+    /// Callback when persons matching a search are received.
+    /// Saves them in the store and updates the people picker if it is open.
+    /// Results for a query that is no longer in the picker are dropped.
+    pub(crate) fn cb_people_found(&mut self, query: &str, persons: Vec<Person>) {
+        if self.state.picker_query() != Some(query) {
+            debug!("Dropping the results of the search for {query}");
+            return;
+        }
+        for person in persons {
+            self.state.cache.persons.insert(person);
+        }
+        let (kind, query) = match &self.state.picker {
+            Some(picker)
+                if matches!(picker.kind(), PickerKind::People | PickerKind::NewMember(_)) =>
+            {
+                (picker.kind().clone(), picker.query().to_string())
+            }
+            _ => return,
+        };
+        let (_, items) = self.searching_picker_content(&kind, &query);
+        if let Some(picker) = self.state.picker.as_mut() {
+            picker.set_items(items);
+        }
+    }
+
+    /// Callback when a room we started or created is available.
+    /// Saves the room in the store and makes it active.
+    pub(crate) fn cb_room_ready(&mut self, webex_room: webex::Room) {
+        let room_id = webex_room.id.clone();
+        self.cb_room_updated(webex_room);
+        self.show_room(&room_id);
+        self.state.set_active_pane(Some(ActivePane::Messages));
+    }
+
+    /// Callback when we have no conversation with a person yet.
+    /// Asks for the first message, which starts the conversation.
+    pub(crate) fn cb_no_direct_room(&mut self, person_id: &PersonId) {
+        let name = self
+            .state
+            .cache
+            .persons
+            .get(person_id)
+            .map(|person| person.display_name.clone())
+            .unwrap_or(person_id.clone());
+        self.state
+            .open_prompt(Prompt::new(PromptKind::FirstMessage {
+                person_id: person_id.clone(),
+                name,
+            }));
+    }
+
+    /// Callback when a person is typing in a room.
+    /// The person info is requested if needed, to show their name.
+    pub(crate) fn cb_typing_started(&mut self, room_id: &RoomId, person_id: &PersonId) {
+        if self.state.cache.is_me(&Some(person_id.clone())) {
+            return;
+        }
+        self.state
+            .cache
+            .typing
+            .start(room_id, person_id, Instant::now());
+        if !self.state.cache.persons.exists_or_requested(person_id) {
+            self.state.cache.persons.add_requested(person_id);
+            self.dispatch_to_teams(AppCmdEvent::UpdatePerson(person_id.clone()), &Priority::Low);
+        }
+    }
+
+    /// Callback when a person stopped typing in a room without sending a message.
+    pub(crate) fn cb_typing_stopped(&mut self, room_id: &RoomId, person_id: &PersonId) {
+        self.state.cache.typing.stop(room_id, person_id);
+    }
+
     /// Callback when a message is deleted in a room.
     pub(crate) fn cb_message_deleted(&mut self, room_id: &RoomId) {
         // We don't know which message was deleted, so we wipe the room and request the messages again.
@@ -0,0 +1,475 @@
+// app/cards.rs
+
+//! Adaptive cards sent by bots: their content in reading order, and a form to fill their
+//! inputs and trigger their actions.
+
+use log::*;
+use ratatui::widgets::TableState;
+use serde_json::{Map, Value};
+use webex::Message;
+
+use super::cache::MessageId;
+
+/// Content type of the attachments holding an adaptive card.
+const ADAPTIVE_CARD_CONTENT_TYPE: &str = "application/vnd.microsoft.card.adaptive";
+
+/// An element of a card, as shown in a text form.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum CardPart {
+    /// Text blocks, facts and image descriptions
+    Text(String),
+    Input(CardInput),
+    Action(CardAction),
+}
+
+/// A field of the card that the user can fill.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct CardInput {
+    pub(crate) id: String,
+    /// Title or placeholder of the input, its id if it has none
+    pub(crate) label: String,
+    pub(crate) kind: InputKind,
+    /// The value as submitted, choices are given by their value
+    pub(crate) value: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum InputKind {
+    /// Free text, also used for numbers, dates and times
+    Text,
+    /// Values submitted when the toggle is on or off
+    Toggle { on: String, off: String },
+    /// Title and value of each choice
+    Choice(Vec<(String, String)>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum CardAction {
+    /// Sends the inputs of the card, along with the data of the action
+    Submit {
+        title: String,
+        data: Map<String, Value>,
+    },
+    OpenUrl {
+        title: String,
+        url: String,
+    },
+}
+
+impl CardInput {
+    /// Returns the input and its value on a single line.
+    pub(crate) fn description(&self) -> String {
+        match &self.kind {
+            InputKind::Text if self.value.is_empty() => format!("{}: _", self.label),
+            InputKind::Text => format!("{}: {}", self.label, self.value),
+            InputKind::Toggle { on, .. } => {
+                let mark = if &self.value == on { "x" } else { " " };
+                format!("[{mark}] {}", self.label)
+            }
+            InputKind::Choice(choices) => {
+                let choice = choices
+                    .iter()
+                    .find(|(_, value)| value == &self.value)
+                    .map(|(title, _)| title.as_str())
+                    .unwrap_or("-");
+                format!("{}: < {choice} >", self.label)
+            }
+        }
+    }
+
+    /// Switches a toggle, or selects the next choice. Text inputs are unchanged.
+    fn cycle(&mut self) {
+        match &self.kind {
+            InputKind::Text => (),
+            InputKind::Toggle { on, off } => {
+                self.value = if &self.value == on { off } else { on }.clone();
+            }
+            InputKind::Choice(choices) => {
+                let next = choices
+                    .iter()
+                    .position(|(_, value)| value == &self.value)
+                    .map_or(0, |index| (index + 1) % choices.len());
+                if let Some((_, value)) = choices.get(next) {
+                    self.value = value.clone();
+                }
+            }
+        }
+    }
+}
+
+impl CardAction {
+    /// Returns the action as a button.
+    pub(crate) fn description(&self) -> String {
+        match self {
+            CardAction::Submit { title, .. } if title.is_empty() => "[ Submit ]".to_string(),
+            CardAction::Submit { title, .. } => format!("[ {title} ]"),
+            CardAction::OpenUrl { title, url } if title.is_empty() => format!("[ {url} ]"),
+            CardAction::OpenUrl { title, url } => format!("[ {title} ] {url}"),
+        }
+    }
+}
+
+/// Returns the parts of the adaptive cards attached to the message, in reading order.
+pub(crate) fn card_parts(message: &Message) -> Vec<CardPart> {
+    let mut parts = Vec::new();
+    for attachment in message
+        .attachments
+        .iter()
+        .flatten()
+        .filter(|attachment| attachment.content_type == ADAPTIVE_CARD_CONTENT_TYPE)
+    {
+        // Some fields of the card elements are private, walk the card as json
+        match serde_json::to_value(&attachment.content) {
+            Ok(card) => push_card_parts(&card, &mut parts),
+            Err(e) => warn!("Could not read adaptive card: {e}"),
+        }
+    }
+    parts
+}
+
+/// Appends the parts of a card, first its body then its actions.
+fn push_card_parts(card: &Value, parts: &mut Vec<CardPart>) {
+    push_elements(&card["body"], parts);
+    push_actions(&card["actions"], parts);
+}
+
+/// Appends the parts of a list of card elements, containers are flattened.
+fn push_elements(elements: &Value, parts: &mut Vec<CardPart>) {
+    for element in elements.as_array().into_iter().flatten() {
+        match element["type"].as_str().unwrap_or_default() {
+            "TextBlock" => {
+                if let Some(text) = element["text"].as_str() {
+                    parts.push(CardPart::Text(text.to_string()));
+                }
+            }
+            "FactSet" => {
+                for fact in element["facts"].as_array().into_iter().flatten() {
+                    let title = fact["title"].as_str().unwrap_or_default();
+                    let value = fact["value"].as_str().unwrap_or_default();
+                    parts.push(CardPart::Text(format!("{title}: {value}")));
+                }
+            }
+            "ColumnSet" => {
+                for column in element["columns"].as_array().into_iter().flatten() {
+                    push_elements(&column["items"], parts);
+                }
+            }
+            "Container" => push_elements(&element["items"], parts),
+            "ImageSet" => push_elements(&element["images"], parts),
+            "Image" => {
+                if let Some(alt_text) = element["altText"].as_str() {
+                    parts.push(CardPart::Text(format!("[image: {alt_text}]")));
+                }
+            }
+            "ActionSet" => push_actions(&element["actions"], parts),
+            input if input.starts_with("Input.") => {
+                if let Some(input) = card_input(element) {
+                    parts.push(CardPart::Input(input));
+                }
+            }
+            other => trace!("Ignoring card element of type {other}"),
+        }
+    }
+}
+
+/// Appends the parts of a list of card actions. Cards shown by an action are inlined.
+fn push_actions(actions: &Value, parts: &mut Vec<CardPart>) {
+    for action in actions.as_array().into_iter().flatten() {
+        let title = action["title"].as_str().unwrap_or_default().to_string();
+        match action["type"].as_str().unwrap_or_default() {
+            "Action.Submit" => {
+                let data = action["data"].as_object().cloned().unwrap_or_default();
+                parts.push(CardPart::Action(CardAction::Submit { title, data }));
+            }
+            "Action.OpenUrl" => {
+                if let Some(url) = action["url"].as_str() {
+                    let url = url.to_string();
+                    parts.push(CardPart::Action(CardAction::OpenUrl { title, url }));
+                }
+            }
+            "Action.ShowCard" => {
+                if !title.is_empty() {
+                    parts.push(CardPart::Text(title));
+                }
+                push_card_parts(&action["card"], parts);
+            }
+            other => trace!("Ignoring card action of type {other}"),
+        }
+    }
+}
+
+/// Returns the input described by an `Input.*` card element, if it has an id.
+fn card_input(element: &Value) -> Option<CardInput> {
+    let text = |name: &str| element[name].as_str().map(str::to_string);
+    let id = text("id")?;
+    let label = text("title")
+        .or_else(|| text("placeholder"))
+        .unwrap_or_else(|| id.clone());
+    let (kind, value) = match element["type"].as_str()? {
+        "Input.Toggle" => {
+            let on = text("valueOn").unwrap_or("true".to_string());
+            let off = text("valueOff").unwrap_or("false".to_string());
+            let value = text("value").unwrap_or_else(|| off.clone());
+            (InputKind::Toggle { on, off }, value)
+        }
+        "Input.ChoiceSet" => {
+            let choices = element["choices"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|choice| {
+                    let title = choice["title"].as_str()?.to_string();
+                    let value = choice["value"].as_str()?.to_string();
+                    Some((title, value))
+                })
+                .collect();
+            (
+                InputKind::Choice(choices),
+                text("value").unwrap_or_default(),
+            )
+        }
+        _ => {
+            // Numbers are the only values that are not strings
+            let value = match &element["value"] {
+                Value::String(value) => value.clone(),
+                Value::Null => String::new(),
+                other => other.to_string(),
+            };
+            (InputKind::Text, value)
+        }
+    };
+    Some(CardInput {
+        id,
+        label,
+        kind,
+        value,
+    })
+}
+
+/// The card of a message being filled, with the selected input or action.
+pub(crate) struct CardForm {
+    message_id: MessageId,
+    parts: Vec<CardPart>,
+    table_state: TableState,
+}
+
+impl CardForm {
+    /// Returns a form for the card of the message, if it has inputs or actions.
+    pub(crate) fn from_message(message: &Message) -> Option<Self> {
+        let mut form = Self {
+            message_id: message.id.clone()?,
+            parts: card_parts(message),
+            table_state: TableState::default(),
+        };
+        form.select_next();
+        form.table_state.selected().map(|_| form)
+    }
+
+    pub(crate) fn message_id(&self) -> &MessageId {
+        &self.message_id
+    }
+
+    pub(crate) fn parts(&self) -> &[CardPart] {
+        &self.parts
+    }
+
+    /// Returns the selected input or action.
+    pub(crate) fn selected_part(&self) -> Option<&CardPart> {
+        self.table_state
+            .selected()
+            .and_then(|index| self.parts.get(index))
+    }
+
+    fn selected_input_mut(&mut self) -> Option<&mut CardInput> {
+        match self
+            .table_state
+            .selected()
+            .map(|index| &mut self.parts[index])
+        {
+            Some(CardPart::Input(input)) => Some(input),
+            _ => None,
+        }
+    }
+
+    /// Selects the next input or action, wrapping around at the end of the card.
+    pub(crate) fn select_next(&mut self) {
+        let start = self.table_state.selected().map_or(0, |index| index + 1);
+        let len = self.parts.len();
+        let next = (0..len)
+            .map(|offset| (start + offset) % len)
+            .find(|&index| !matches!(self.parts[index], CardPart::Text(_)));
+        self.table_state.select(next);
+    }
+
+    /// Selects the previous input or action, wrapping around at the start of the card.
+    pub(crate) fn select_previous(&mut self) {
+        let len = self.parts.len();
+        let start = self.table_state.selected().unwrap_or_default() + len;
+        let previous = (1..=len)
+            .map(|offset| (start - offset) % len)
+            .find(|&index| !matches!(self.parts[index], CardPart::Text(_)));
+        self.table_state.select(previous);
+    }
+
+    /// Types a character in the selected text input.
+    /// A space switches the selected toggle or choice instead.
+    pub(crate) fn type_char(&mut self, c: char) {
+        if let Some(input) = self.selected_input_mut() {
+            match input.kind {
+                InputKind::Text => input.value.push(c),
+                _ if c == ' ' => input.cycle(),
+                _ => (),
+            }
+        }
+    }
+
+    /// Removes the last character of the selected text input.
+    pub(crate) fn delete_char(&mut self) {
+        if let Some(input) = self.selected_input_mut() {
+            if input.kind == InputKind::Text {
+                input.value.pop();
+            }
+        }
+    }
+
+    /// Switches the selected toggle, or selects the next value of the selected choice.
+    pub(crate) fn cycle_selected(&mut self) {
+        if let Some(input) = self.selected_input_mut() {
+            input.cycle();
+        }
+    }
+
+    /// Returns the values to submit with the data of an action: the action data,
+    /// overridden by the values of all the inputs of the card.
+    pub(crate) fn inputs(&self, data: &Map<String, Value>) -> Map<String, Value> {
+        let mut inputs = data.clone();
+        for part in &self.parts {
+            if let CardPart::Input(input) = part {
+                inputs.insert(input.id.clone(), Value::String(input.value.clone()));
+            }
+        }
+        inputs
+    }
+
+    pub(crate) fn table_state_mut(&mut self) -> &mut TableState {
+        &mut self.table_state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use webex::Attachment;
+
+    fn message_with_card(card: Value) -> Message {
+        Message {
+            id: Some("message".to_string()),
+            attachments: Some(vec![Attachment {
+                content_type: ADAPTIVE_CARD_CONTENT_TYPE.to_string(),
+                content: serde_json::from_value(card).unwrap(),
+            }]),
+            ..Default::default()
+        }
+    }
+
+    fn deploy_card() -> Value {
+        json!({
+            "type": "AdaptiveCard",
+            "version": "1.2",
+            "body": [
+                { "type": "TextBlock", "text": "Deploy build 42?" },
+                {
+                    "type": "ColumnSet",
+                    "columns": [
+                        { "items": [{ "type": "FactSet", "facts": [
+                            { "title": "Branch", "value": "main" }
+                        ]}]},
+                        { "items": [{ "type": "Input.Text", "id": "reason", "placeholder": "Reason" }]}
+                    ]
+                },
+                { "type": "Input.Toggle", "id": "notify", "title": "Notify team", "valueOn": "yes", "valueOff": "no" },
+                { "type": "Input.ChoiceSet", "id": "env", "choices": [
+                    { "title": "Staging", "value": "stg" },
+                    { "title": "Production", "value": "prd" }
+                ]}
+            ],
+            "actions": [
+                { "type": "Action.Submit", "title": "Deploy", "data": { "action": "deploy" } },
+                { "type": "Action.OpenUrl", "title": "Logs", "url": "https://ci/42" }
+            ]
+        })
+    }
+
+    #[test]
+    fn test_card_parts() {
+        let message = message_with_card(deploy_card());
+        let descriptions: Vec<_> = card_parts(&message)
+            .iter()
+            .map(|part| match part {
+                CardPart::Text(text) => text.clone(),
+                CardPart::Input(input) => input.description(),
+                CardPart::Action(action) => action.description(),
+            })
+            .collect();
+        assert_eq!(
+            descriptions,
+            vec![
+                "Deploy build 42?",
+                "Branch: main",
+                "Reason: _",
+                "[ ] Notify team",
+                "env: < - >",
+                "[ Deploy ]",
+                "[ Logs ] https://ci/42",
+            ]
+        );
+        assert!(card_parts(&Message::default()).is_empty());
+    }
+
+    #[test]
+    fn test_fill_card_form() {
+        let mut form = CardForm::from_message(&message_with_card(deploy_card())).unwrap();
+        // Text parts are skipped
+        assert!(
+            matches!(form.selected_part(), Some(CardPart::Input(input)) if input.id == "reason")
+        );
+        "ok!".chars().for_each(|c| form.type_char(c));
+        form.delete_char();
+        form.select_next();
+        form.type_char(' ');
+        form.select_next();
+        form.cycle_selected();
+        form.cycle_selected();
+        form.select_next();
+        let Some(CardPart::Action(CardAction::Submit { data, .. })) = form.selected_part() else {
+            panic!("Submit action should be selected");
+        };
+        let inputs = form.inputs(data);
+        assert_eq!(
+            Value::Object(inputs),
+            json!({ "action": "deploy", "reason": "ok", "notify": "yes", "env": "prd" })
+        );
+        // Selection wraps around
+        form.select_next();
+        form.select_next();
+        assert!(
+            matches!(form.selected_part(), Some(CardPart::Input(input)) if input.id == "reason")
+        );
+        form.select_previous();
+        assert!(matches!(
+            form.selected_part(),
+            Some(CardPart::Action(CardAction::OpenUrl { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_card_without_inputs_has_no_form() {
+        let card = json!({
+            "type": "AdaptiveCard",
+            "version": "1.2",
+            "body": [{ "type": "TextBlock", "text": "Build passed" }]
+        });
+        assert!(CardForm::from_message(&message_with_card(card)).is_none());
+    }
+}
@@ -0,0 +1,157 @@
+// app/drafts.rs
+
+//! Messages being composed in each room, kept across room switches and restarts.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use webex::Message;
+
+use super::cache::room::RoomId;
+use super::message_editor::Mention;
+use super::persistence::Persisted;
+
+/// Name of the file where drafts are saved, in the cache directory.
+const DRAFTS_FILE: &str = "drafts.json";
+
+/// The content of the message editor for a room.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub(crate) struct Draft {
+    pub(crate) text: String,
+    #[serde(default)]
+    pub(crate) mentions: Vec<Mention>,
+    pub(crate) response_to: Option<Message>,
+    pub(crate) editing_of: Option<Message>,
+}
+
+impl Draft {
+    /// Whether there is nothing worth keeping, the context alone is not.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.text.trim().is_empty()
+    }
+}
+
+/// The drafts of all rooms but the one in the editor, which the editor holds.
+#[derive(Debug, Default)]
+pub(crate) struct Drafts {
+    drafts: Persisted<HashMap<RoomId, Draft>>,
+    /// Room to which the content of the editor belongs
+    editor_room_id: Option<RoomId>,
+}
+
+impl Drafts {
+    pub(crate) fn load() -> Self {
+        Self {
+            drafts: Persisted::load(DRAFTS_FILE),
+            ..Default::default()
+        }
+    }
+
+    /// Saves the drafts to disk.
+    pub(crate) fn save(&self) {
+        self.drafts.save();
+    }
+
+    /// Returns the room to which the content of the editor belongs.
+    pub(crate) fn editor_room_id(&self) -> Option<&RoomId> {
+        self.editor_room_id.as_ref()
+    }
+
+    /// Keeps the content of the editor as the draft of its room and returns the draft of
+    /// `room_id`, which now owns the editor.
+    pub(crate) fn switch(&mut self, editor_content: Draft, room_id: Option<&RoomId>) -> Draft {
+        if let Some(previous_id) = self.editor_room_id.take() {
+            self.set(previous_id, editor_content);
+        }
+        self.editor_room_id = room_id.cloned();
+        room_id
+            .and_then(|id| self.drafts.remove(id))
+            .unwrap_or_default()
+    }
+
+    /// Keeps the content of the editor as the draft of its room, e.g. before quitting.
+    pub(crate) fn keep_editor_content(&mut self, editor_content: Draft) {
+        if let Some(id) = self.editor_room_id.clone() {
+            self.set(id, editor_content);
+        }
+    }
+
+    /// Sets the draft of a room, an empty draft removes it.
+    fn set(&mut self, room_id: RoomId, draft: Draft) {
+        match draft.is_empty() {
+            true => self.drafts.remove(&room_id),
+            false => self.drafts.insert(room_id, draft),
+        };
+    }
+
+    /// Whether a room other than that in the editor has a draft.
+    pub(crate) fn has_draft(&self, room_id: &RoomId) -> bool {
+        self.drafts.contains_key(room_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draft(text: &str) -> Draft {
+        Draft {
+            text: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_switch_keeps_editor_content() {
+        let mut drafts = Drafts::default();
+        let room_a = "a".to_string();
+        let room_b = "b".to_string();
+        assert_eq!(
+            drafts.switch(Draft::default(), Some(&room_a)),
+            Draft::default()
+        );
+        // Leaving room a with some text
+        assert_eq!(
+            drafts.switch(draft("hello"), Some(&room_b)),
+            Draft::default()
+        );
+        assert!(drafts.has_draft(&room_a));
+        // Coming back to room a, leaving nothing in room b
+        assert_eq!(drafts.switch(draft(" "), Some(&room_a)), draft("hello"));
+        assert!(!drafts.has_draft(&room_a));
+        assert!(!drafts.has_draft(&room_b));
+        assert_eq!(drafts.editor_room_id(), Some(&room_a));
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(DRAFTS_FILE);
+        let mut drafts = Drafts {
+            drafts: Persisted::load_from(path.clone()),
+            ..Default::default()
+        };
+        let reply = Draft {
+            text: "sure @Ann".to_string(),
+            mentions: vec![Mention {
+                name: "Ann".to_string(),
+                person_id: Some("ann-id".to_string()),
+            }],
+            response_to: Some(Message {
+                id: Some("msg-id".to_string()),
+                ..Default::default()
+            }),
+            editing_of: None,
+        };
+        let room_id = "room".to_string();
+        drafts.switch(Draft::default(), Some(&room_id));
+        drafts.keep_editor_content(reply.clone());
+        drafts.save();
+
+        let mut loaded = Drafts {
+            drafts: Persisted::load_from(path),
+            ..Default::default()
+        };
+        assert!(loaded.has_draft(&room_id));
+        assert_eq!(loaded.switch(Draft::default(), Some(&room_id)), reply);
+    }
+}
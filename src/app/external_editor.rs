@@ -0,0 +1,118 @@
+// app/external_editor.rs
+
+//! Composing messages in the user's own editor, on a temporary file.
+
+use color_eyre::{eyre::eyre, Result};
+use log::*;
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+
+/// Editor used when neither `$VISUAL` nor `$EDITOR` is set.
+const DEFAULT_EDITOR: &str = "vi";
+
+/// Line separating the message from the context shown to the user, which is not sent.
+const SCISSORS: &str = "# ------------------------ >8 ------------------------";
+
+/// Returns the content of the file given to the editor: the message, then the scissors line
+/// and the quoted context, if any.
+pub(crate) fn file_content(message: &str, context: Option<(&str, &str)>) -> String {
+    let mut content = message.to_string();
+    if let Some((title, quoted)) = context {
+        content.push_str(&format!(
+            "\n\n{SCISSORS}\n# Everything below the line above is ignored.\n# {title}\n"
+        ));
+        for line in quoted.lines() {
+            content.push_str(&format!("> {line}\n"));
+        }
+    }
+    content
+}
+
+/// Returns the message written in the file, without the context and trailing blank lines.
+pub(crate) fn message_from_file_content(content: &str) -> String {
+    let message = match content.find(SCISSORS) {
+        Some(index) => &content[..index],
+        None => content,
+    };
+    message.trim_end().to_string()
+}
+
+/// Returns the editor command from the environment.
+fn editor_command() -> String {
+    ["VISUAL", "EDITOR"]
+        .into_iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or(DEFAULT_EDITOR.to_string())
+}
+
+/// Opens the content in the user's editor and returns it once the editor exits.
+/// The terminal must be handed over to the editor while it runs.
+///
+/// This blocks until the editor exits, so it must not run on the async runtime directly.
+pub(crate) fn edit_in_external_editor(content: &str) -> Result<String> {
+    // The file gets a random name and is only readable by the user, so that other users
+    // can neither read the draft nor replace the file.
+    // A markdown extension gives syntax highlighting in most editors.
+    let mut builder = tempfile::Builder::new();
+    builder.prefix("webex-tui-").suffix(".md");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        builder.permissions(std::fs::Permissions::from_mode(0o600));
+    }
+    let mut file = builder.tempfile()?;
+    file.write_all(content.as_bytes())?;
+    file.flush()?;
+    run_editor(&editor_command(), file.path())?;
+    let edited = std::fs::read_to_string(file.path())?;
+    if let Err(e) = file.close() {
+        warn!("Could not remove the edited file: {e}");
+    }
+    Ok(edited)
+}
+
+/// Runs the editor on the file, the command may include arguments such as `code --wait`.
+fn run_editor(command: &str, path: &Path) -> Result<()> {
+    let mut words = command.split_whitespace();
+    let program = words.next().ok_or(eyre!("The editor command is empty"))?;
+    debug!("Editing {} with {command}", path.display());
+    let status = Command::new(program)
+        .args(words)
+        .arg(path)
+        .status()
+        .map_err(|e| eyre!("Could not start {program}: {e}"))?;
+    match status.success() {
+        true => Ok(()),
+        false => Err(eyre!("{program} exited with {status}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[test]
+    fn test_file_content_round_trip() {
+        let content = file_content(
+            "Sounds good",
+            Some(("Replying to Ann", "Lunch?\n# not a title")),
+        );
+        assert!(content.starts_with("Sounds good\n\n# ----"));
+        assert!(content.ends_with("# Replying to Ann\n> Lunch?\n> # not a title\n"));
+        assert_eq!(message_from_file_content(&content), "Sounds good");
+    }
+
+    #[rstest(
+        content,
+        expected,
+        case("", ""),
+        case("# Title\n\nbody\n\n\n", "# Title\n\nbody"),
+        case(&format!("line 1\nline 2\n{SCISSORS}\n> quoted"), "line 1\nline 2")
+    )]
+    fn test_message_from_file_content(content: &str, expected: &str) {
+        assert_eq!(message_from_file_content(content), expected);
+    }
+}
@@ -0,0 +1,81 @@
+// app/favourites.rs
+
+//! Rooms pinned as favourites by the user, kept across restarts.
+
+use super::cache::room::RoomId;
+use super::persistence::Persisted;
+
+/// Name of the file where favourites are saved, in the cache directory.
+const FAVOURITES_FILE: &str = "favourites.json";
+
+/// Ids of the favourite rooms, in the order they were added.
+#[derive(Debug, Default)]
+pub(crate) struct Favourites {
+    room_ids: Persisted<Vec<RoomId>>,
+}
+
+impl Favourites {
+    pub(crate) fn load() -> Self {
+        Self {
+            room_ids: Persisted::load(FAVOURITES_FILE),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.room_ids.is_empty()
+    }
+
+    /// Returns true if the room with given id is a favourite.
+    pub(crate) fn contains(&self, room_id: &RoomId) -> bool {
+        self.room_ids.contains(room_id)
+    }
+
+    /// Adds the room to the favourites, or removes it if it is one.
+    /// Returns true if the room was added.
+    pub(crate) fn toggle(&mut self, room_id: &RoomId) -> bool {
+        let added = match self.room_ids.iter().position(|id| id == room_id) {
+            Some(index) => {
+                self.room_ids.remove(index);
+                false
+            }
+            None => {
+                self.room_ids.push(room_id.clone());
+                true
+            }
+        };
+        self.room_ids.save();
+        added
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_toggle() {
+        let mut favourites = Favourites::default();
+        let room_id = "room".to_string();
+        assert!(favourites.is_empty());
+        assert!(favourites.toggle(&room_id));
+        assert!(favourites.contains(&room_id));
+        assert!(!favourites.toggle(&room_id));
+        assert!(!favourites.contains(&room_id));
+        assert!(favourites.is_empty());
+    }
+
+    #[test]
+    fn test_favourites_are_kept_across_restarts() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(FAVOURITES_FILE);
+        let mut favourites = Favourites {
+            room_ids: Persisted::load_from(path.clone()),
+        };
+        favourites.toggle(&"room".to_string());
+        let reloaded = Favourites {
+            room_ids: Persisted::load_from(path),
+        };
+        assert!(reloaded.contains(&"room".to_string()));
+    }
+}
@@ -0,0 +1,223 @@
+// app/files.rs
+
+//! Helpers to deal with local files when transferring attachments.
+
+use std::path::{Path, PathBuf};
+
+/// Result of a path completion.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct PathCompletion {
+    /// The completed path, as typed by the user (no tilde expansion)
+    pub(crate) completed: String,
+    /// The possible entries when the completion is ambiguous
+    pub(crate) candidates: Vec<String>,
+}
+
+/// Replaces a leading `~` with the home directory.
+pub(crate) fn expand_tilde(path: &str) -> PathBuf {
+    if path == "~" {
+        if let Some(home) = dirs::home_dir() {
+            return home;
+        }
+    }
+    if let Some(rest) = path.strip_prefix("~/") {
+        if let Some(home) = dirs::home_dir() {
+            return home.join(rest);
+        }
+    }
+    PathBuf::from(path)
+}
+
+/// Completes a partially typed path like a shell would:
+/// - a single match is completed, with a trailing `/` for directories
+/// - several matches are completed to their longest common prefix and returned as candidates
+/// - hidden entries are only proposed if the prefix starts with a `.`
+pub(crate) fn complete_path(partial: &str) -> PathCompletion {
+    let (dir_part, prefix) = match partial.rfind('/') {
+        Some(pos) => partial.split_at(pos + 1),
+        None => ("", partial),
+    };
+    let dir = match dir_part {
+        "" => PathBuf::from("."),
+        dir_part => expand_tilde(dir_part),
+    };
+    let unchanged = PathCompletion {
+        completed: partial.to_string(),
+        candidates: vec![],
+    };
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return unchanged;
+    };
+
+    let mut matches: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            if !name.starts_with(prefix) || (name.starts_with('.') && !prefix.starts_with('.')) {
+                return None;
+            }
+            match entry.path().is_dir() {
+                true => Some(format!("{name}/")),
+                false => Some(name),
+            }
+        })
+        .collect();
+    matches.sort();
+
+    match matches.len() {
+        0 => unchanged,
+        1 => PathCompletion {
+            completed: format!("{dir_part}{}", matches[0]),
+            candidates: vec![],
+        },
+        _ => PathCompletion {
+            completed: format!("{dir_part}{}", longest_common_prefix(&matches)),
+            candidates: matches,
+        },
+    }
+}
+
+/// Returns the longest prefix shared by all strings.
+fn longest_common_prefix(strings: &[String]) -> String {
+    let Some(first) = strings.first() else {
+        return String::new();
+    };
+    let mut prefix_len = first.len();
+    for s in strings.iter().skip(1) {
+        prefix_len = first
+            .char_indices()
+            .zip(s.chars())
+            .take_while(|((_, a), b)| a == b)
+            .last()
+            .map(|((i, a), _)| i + a.len_utf8())
+            .unwrap_or(0)
+            .min(prefix_len);
+    }
+    first[..prefix_len].to_string()
+}
+
+/// Returns a path in `dir` for a file named `file_name` that does not exist yet,
+/// adding a counter to the name if needed: `report.pdf`, `report (1).pdf`, ...
+/// Any directory component in `file_name` is ignored.
+pub(crate) fn unique_file_path(dir: &Path, file_name: &str) -> PathBuf {
+    let file_name = Path::new(file_name)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("attachment");
+    let candidate = dir.join(file_name);
+    if !candidate.exists() {
+        return candidate;
+    }
+    let (stem, extension) = match file_name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{extension}")),
+        _ => (file_name, String::new()),
+    };
+    (1..)
+        .map(|counter| dir.join(format!("{stem} ({counter}){extension}")))
+        .find(|path| !path.exists())
+        .expect("a free file name")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn make_tree() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("report.pdf"), "").unwrap();
+        fs::write(dir.path().join("readme.md"), "").unwrap();
+        fs::write(dir.path().join(".hidden"), "").unwrap();
+        fs::create_dir(dir.path().join("logs")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_complete_path_single_match() {
+        let dir = make_tree();
+        let base = format!("{}/", dir.path().display());
+        let completion = complete_path(&format!("{base}rep"));
+        assert_eq!(completion.completed, format!("{base}report.pdf"));
+        assert!(completion.candidates.is_empty());
+    }
+
+    #[test]
+    fn test_complete_path_directory() {
+        let dir = make_tree();
+        let base = format!("{}/", dir.path().display());
+        let completion = complete_path(&format!("{base}lo"));
+        assert_eq!(completion.completed, format!("{base}logs/"));
+    }
+
+    #[test]
+    fn test_complete_path_ambiguous() {
+        let dir = make_tree();
+        let base = format!("{}/", dir.path().display());
+        let completion = complete_path(&format!("{base}r"));
+        assert_eq!(completion.completed, format!("{base}re"));
+        assert_eq!(completion.candidates, vec!["readme.md", "report.pdf"]);
+    }
+
+    #[test]
+    fn test_complete_path_hidden_files() {
+        let dir = make_tree();
+        let base = format!("{}/", dir.path().display());
+        let completion = complete_path(&base);
+        assert_eq!(
+            completion.candidates,
+            vec!["logs/", "readme.md", "report.pdf"]
+        );
+        let completion = complete_path(&format!("{base}.h"));
+        assert_eq!(completion.completed, format!("{base}.hidden"));
+    }
+
+    #[test]
+    fn test_complete_path_no_match() {
+        let dir = make_tree();
+        let partial = format!("{}/zzz", dir.path().display());
+        assert_eq!(
+            complete_path(&partial),
+            PathCompletion {
+                completed: partial,
+                candidates: vec![]
+            }
+        );
+    }
+
+    #[test]
+    fn test_longest_common_prefix() {
+        let strings = vec!["report.pdf".to_string(), "readme.md".to_string()];
+        assert_eq!(longest_common_prefix(&strings), "re");
+        assert_eq!(longest_common_prefix(&[]), "");
+    }
+
+    #[test]
+    fn test_unique_file_path() {
+        let dir = make_tree();
+        assert_eq!(
+            unique_file_path(dir.path(), "new.txt"),
+            dir.path().join("new.txt")
+        );
+        assert_eq!(
+            unique_file_path(dir.path(), "report.pdf"),
+            dir.path().join("report (1).pdf")
+        );
+        fs::write(dir.path().join("report (1).pdf"), "").unwrap();
+        assert_eq!(
+            unique_file_path(dir.path(), "report.pdf"),
+            dir.path().join("report (2).pdf")
+        );
+        assert_eq!(
+            unique_file_path(dir.path(), "../../etc/passwd"),
+            dir.path().join("passwd")
+        );
+    }
+
+    #[test]
+    fn test_expand_tilde() {
+        assert_eq!(expand_tilde("/tmp/file"), PathBuf::from("/tmp/file"));
+        if let Some(home) = dirs::home_dir() {
+            assert_eq!(expand_tilde("~/file"), home.join("file"));
+        }
+    }
+}
@@ -0,0 +1,168 @@
+// app/forward.rs
+
+//! Re-posting a message to another room.
+
+use webex::types::MessageOut;
+use webex::Message;
+
+use super::cache::room::RoomId;
+
+/// Returns the header telling where a forwarded message comes from.
+pub(crate) fn forward_header(author: &str, room_title: &str) -> String {
+    format!("Forwarded from {author} in {room_title}")
+}
+
+/// Returns the message posting the content of `message` to `room_id`, below the header if any.
+/// Returns `None` if there is nothing to post, e.g. for a message with only attachments.
+pub(crate) fn forwarded_message(
+    message: &Message,
+    header: Option<&str>,
+    room_id: &RoomId,
+) -> Option<MessageOut> {
+    let mut msg_to_send = MessageOut {
+        room_id: Some(room_id.clone()),
+        ..Default::default()
+    };
+    // The markdown keeps the formatting, and the header is emphasized with it.
+    // Mentions would notify the people mentioned again, only their names are kept.
+    let markdown = message.markdown.as_deref().map(strip_mentions);
+    match (header, markdown, &message.text) {
+        (None, None, None) => return None,
+        (None, Some(markdown), _) => msg_to_send.markdown = Some(markdown),
+        (None, None, Some(text)) => msg_to_send.text = Some(text.clone()),
+        (Some(header), markdown, text) => {
+            let header = escape_markdown(header);
+            let content = markdown.or(text.clone());
+            msg_to_send.markdown = Some(match content {
+                Some(content) => format!("_{header}_\n\n{content}"),
+                None => format!("_{header}_"),
+            });
+        }
+    }
+    Some(msg_to_send)
+}
+
+/// Returns the text with the characters having a meaning in markdown escaped.
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\`*_[]<>#~|".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Replaces the mentions in markdown, such as `<@personId:id|Ann>` or `<@all>`, by the
+/// names mentioned.
+fn strip_mentions(markdown: &str) -> String {
+    let mut stripped = String::with_capacity(markdown.len());
+    let mut rest = markdown;
+    while let Some(start) = rest.find("<@") {
+        let Some(end) = rest[start..].find('>') else {
+            break;
+        };
+        let mention = &rest[start + 2..start + end];
+        stripped.push_str(&rest[..start]);
+        match mention.split_once('|') {
+            Some((_, name)) => stripped.push_str(name),
+            None => stripped.push_str(mention.rsplit(':').next().unwrap_or(mention)),
+        }
+        rest = &rest[start + end + 1..];
+    }
+    stripped.push_str(rest);
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn make_message(text: Option<&str>, markdown: Option<&str>) -> Message {
+        Message {
+            text: text.map(String::from),
+            markdown: markdown.map(String::from),
+            ..Default::default()
+        }
+    }
+
+    #[rstest(
+        text,
+        markdown,
+        header,
+        expected_text,
+        expected_markdown,
+        case(Some("hi"), None, None, Some("hi"), None),
+        case(Some("hi"), Some("**hi**"), None, None, Some("**hi**")),
+        case(
+            Some("hi"),
+            None,
+            Some("Forwarded from Ann in Lunch"),
+            None,
+            Some("_Forwarded from Ann in Lunch_\n\nhi")
+        ),
+        case(
+            Some("hi"),
+            Some("**hi**"),
+            Some("Forwarded from Ann in Lunch"),
+            None,
+            Some("_Forwarded from Ann in Lunch_\n\n**hi**")
+        ),
+        case(
+            None,
+            None,
+            Some("Forwarded from Ann in Lunch"),
+            None,
+            Some("_Forwarded from Ann in Lunch_")
+        )
+    )]
+    fn test_forwarded_message(
+        text: Option<&str>,
+        markdown: Option<&str>,
+        header: Option<&str>,
+        expected_text: Option<&str>,
+        expected_markdown: Option<&str>,
+    ) {
+        let message = make_message(text, markdown);
+        let room_id = "room".to_string();
+        let msg_to_send = forwarded_message(&message, header, &room_id).unwrap();
+        assert_eq!(msg_to_send.room_id, Some(room_id));
+        assert_eq!(msg_to_send.text.as_deref(), expected_text);
+        assert_eq!(msg_to_send.markdown.as_deref(), expected_markdown);
+        assert_eq!(msg_to_send.parent_id, None);
+    }
+
+    #[test]
+    fn test_forwarded_message_escapes_header_and_strips_mentions() {
+        let message = make_message(
+            Some("hi Bob and all"),
+            Some("hi <@personId:p1|Bob> and <@all>"),
+        );
+        let header = forward_header("ann_smith", "*Lunch*");
+        let msg_to_send = forwarded_message(&message, Some(&header), &"room".to_string());
+        assert_eq!(
+            msg_to_send.unwrap().markdown.as_deref(),
+            Some("_Forwarded from ann\\_smith in \\*Lunch\\*_\n\nhi Bob and all")
+        );
+    }
+
+    #[rstest(
+        markdown,
+        expected,
+        case("no mention", "no mention"),
+        case("<@personId:p1|Bob Smith>, hi", "Bob Smith, hi"),
+        case("<@groupMention:all> <@all>", "all all"),
+        case("a <@ b", "a <@ b")
+    )]
+    fn test_strip_mentions(markdown: &str, expected: &str) {
+        assert_eq!(strip_mentions(markdown), expected);
+    }
+
+    #[test]
+    fn test_nothing_to_forward_without_content_or_header() {
+        let message = make_message(None, None);
+        assert!(forwarded_message(&message, None, &"room".to_string()).is_none());
+    }
+}
@@ -0,0 +1,121 @@
+// app/links.rs
+
+//! Extraction of the links found in messages.
+
+use webex::Message;
+
+/// Schemes recognised as the start of a link in plain text.
+const URL_SCHEMES: [&str; 2] = ["https://", "http://"];
+
+/// Returns true if the url is a web link, which is safe to open in a browser.
+pub(crate) fn is_web_url(url: &str) -> bool {
+    URL_SCHEMES.iter().any(|scheme| url.starts_with(scheme))
+}
+
+/// Returns the urls found in the message, in order of appearance and without duplicates.
+/// Anchors of the HTML come first, then the links written in the text and markdown.
+pub(crate) fn urls_in_message(message: &Message) -> Vec<String> {
+    let mut urls = Vec::new();
+    let html_urls = message.html.as_deref().map(anchor_urls).unwrap_or_default();
+    let text_urls = [message.text.as_deref(), message.markdown.as_deref()]
+        .into_iter()
+        .flatten()
+        .flat_map(urls_in_text);
+    for url in html_urls.into_iter().chain(text_urls) {
+        if !urls.contains(&url) {
+            urls.push(url);
+        }
+    }
+    urls
+}
+
+/// Returns the targets of the `<a href="...">` anchors of an HTML fragment.
+fn anchor_urls(html: &str) -> Vec<String> {
+    html.split("href=")
+        .skip(1)
+        .filter_map(|rest| {
+            let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+            rest[1..].split(quote).next()
+        })
+        .filter(|url| is_web_url(url))
+        .map(|url| url.replace("&amp;", "&"))
+        .collect()
+}
+
+/// Returns the urls written in plain text or markdown, e.g. `see https://x.org.` or
+/// `[x](https://x.org)`.
+fn urls_in_text(text: &str) -> Vec<String> {
+    let mut urls = Vec::new();
+    let mut rest = text;
+    while let Some(start) = URL_SCHEMES
+        .iter()
+        .filter_map(|scheme| rest.find(scheme))
+        .min()
+    {
+        let candidate = &rest[start..];
+        let end = candidate
+            .find(|c: char| c.is_whitespace() || matches!(c, '<' | '>' | '"' | '\'' | ')' | ']'))
+            .unwrap_or(candidate.len());
+        // Punctuation ending a sentence is not part of the link
+        let url = candidate[..end].trim_end_matches(['.', ',', ';', ':', '!', '?']);
+        if URL_SCHEMES.iter().all(|scheme| url != *scheme) {
+            urls.push(url.to_string());
+        }
+        rest = &candidate[end..];
+    }
+    urls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest(
+        text,
+        expected,
+        case("no link here", vec![]),
+        case("see https://example.com.", vec!["https://example.com"]),
+        case(
+            "[PR](https://github.com/org/repo/pull/1) and (http://jira/ABC-1)",
+            vec!["https://github.com/org/repo/pull/1", "http://jira/ABC-1"]
+        ),
+        case("<https://a.org/x?y=1&z=2>, https://", vec!["https://a.org/x?y=1&z=2"])
+    )]
+    fn test_urls_in_text(text: &str, expected: Vec<&str>) {
+        assert_eq!(urls_in_text(text), expected);
+    }
+
+    #[rstest(
+        url,
+        expected,
+        case("https://example.com", true),
+        case("http://example.com/a?b=c", true),
+        case("file:///etc/passwd", false),
+        case("/usr/bin/app", false),
+        case("slack://open", false)
+    )]
+    fn test_is_web_url(url: &str, expected: bool) {
+        assert_eq!(is_web_url(url), expected);
+    }
+
+    #[test]
+    fn test_urls_in_message() {
+        let message = Message {
+            text: Some("Ticket https://jira/ABC-1 and docs https://docs.org".to_string()),
+            html: Some(
+                "<p>Ticket <a href=\"https://jira/ABC-1\">ABC-1</a> and <a href='https://docs.org?a=1&amp;b=2'>docs</a> <a href=\"mailto:x@y.z\">mail</a></p>"
+                    .to_string(),
+            ),
+            ..Default::default()
+        };
+        assert_eq!(
+            urls_in_message(&message),
+            vec![
+                "https://jira/ABC-1",
+                "https://docs.org?a=1&b=2",
+                "https://docs.org"
+            ]
+        );
+    }
+}
@@ -0,0 +1,45 @@
+// app/markdown.rs
+
+//! Conversion of the markdown typed in the editor, to preview how Webex will render it.
+
+use pulldown_cmark::{html, Event, Options, Parser};
+
+/// Converts markdown to HTML.
+/// Single newlines are kept as line breaks, like Webex clients do.
+pub(crate) fn markdown_to_html(markdown: &str) -> String {
+    let options = Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TABLES;
+    let parser = Parser::new_ext(markdown, options).map(|event| match event {
+        Event::SoftBreak => Event::HardBreak,
+        event => event,
+    });
+    let mut html_output = String::new();
+    html::push_html(&mut html_output, parser);
+    html_output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest(
+        markdown,
+        expected,
+        case("hello", "<p>hello</p>\n"),
+        case(
+            "**bold** and _italic_",
+            "<p><strong>bold</strong> and <em>italic</em></p>\n"
+        ),
+        case("~~gone~~", "<p><del>gone</del></p>\n"),
+        case("`code`", "<p><code>code</code></p>\n"),
+        case("line 1\nline 2", "<p>line 1<br />\nline 2</p>\n"),
+        case("- one\n- two", "<ul>\n<li>one</li>\n<li>two</li>\n</ul>\n"),
+        case(
+            "[link](https://example.com)",
+            "<p><a href=\"https://example.com\">link</a></p>\n"
+        )
+    )]
+    fn test_markdown_to_html(markdown: &str, expected: &str) {
+        assert_eq!(markdown_to_html(markdown), expected);
+    }
+}
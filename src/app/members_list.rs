@@ -0,0 +1,77 @@
+// app/members_list.rs
+
+//! Selection in the list of members of the active room.
+
+use ratatui::widgets::TableState;
+
+#[derive(Default)]
+pub(crate) struct MembersList {
+    table_state: TableState,
+}
+
+impl MembersList {
+    /// Returns the index of the selected member, if any.
+    pub(crate) fn selected_index(&self) -> Option<usize> {
+        self.table_state.selected()
+    }
+
+    /// Selects the first of `nb_members` members, if any.
+    pub(crate) fn select_first(&mut self, nb_members: usize) {
+        self.table_state.select((nb_members > 0).then_some(0));
+    }
+
+    /// Selects the next of `nb_members` members, wrapping around at the end of the list.
+    pub(crate) fn select_next(&mut self, nb_members: usize) {
+        let next = match self.table_state.selected() {
+            _ if nb_members == 0 => None,
+            Some(selected) if selected + 1 < nb_members => Some(selected + 1),
+            _ => Some(0),
+        };
+        self.table_state.select(next);
+    }
+
+    /// Selects the previous of `nb_members` members, wrapping around at the start of the list.
+    pub(crate) fn select_previous(&mut self, nb_members: usize) {
+        let previous = match self.table_state.selected() {
+            _ if nb_members == 0 => None,
+            Some(selected) if selected > 0 => Some(selected - 1),
+            _ => Some(nb_members - 1),
+        };
+        self.table_state.select(previous);
+    }
+
+    /// Keeps the selection within the `nb_members` members after the list changed.
+    pub(crate) fn clamp_selection(&mut self, nb_members: usize) {
+        if let Some(selected) = self.table_state.selected() {
+            let last = nb_members.checked_sub(1);
+            self.table_state.select(last.map(|last| selected.min(last)));
+        }
+    }
+
+    pub(crate) fn table_state_mut(&mut self) -> &mut TableState {
+        &mut self.table_state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selection_wraps_around_and_stays_in_list() {
+        let mut list = MembersList::default();
+        list.select_first(0);
+        assert_eq!(list.selected_index(), None);
+        list.select_first(3);
+        assert_eq!(list.selected_index(), Some(0));
+        list.select_previous(3);
+        assert_eq!(list.selected_index(), Some(2));
+        list.select_next(3);
+        assert_eq!(list.selected_index(), Some(0));
+        list.select_previous(3);
+        list.clamp_selection(2);
+        assert_eq!(list.selected_index(), Some(1));
+        list.clamp_selection(0);
+        assert_eq!(list.selected_index(), None);
+    }
+}
@@ -2,15 +2,54 @@
 
 //! Editor for typing messages.
 
-use tui_textarea::{Input, TextArea};
+use serde::{Deserialize, Serialize};
+use tui_textarea::{CursorMove, Input, TextArea};
 use webex::Message;
 
+use super::cache::persons::PersonId;
+use super::drafts::Draft;
+use super::files::complete_path;
+use super::vim::{Vim, VimMode};
+
+/// Typing this command followed by a path uploads the file instead of sending a message.
+pub(crate) const UPLOAD_COMMAND: &str = "/upload ";
+
+/// A mention inserted in the editor as `@name`, converted to Webex markup when sent.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub(crate) struct Mention {
+    pub(crate) name: String,
+    /// The person to notify, or everyone in the space if `None`
+    pub(crate) person_id: Option<PersonId>,
+}
+
+impl Mention {
+    /// Returns the text shown in the editor.
+    fn text(&self) -> String {
+        format!("@{}", self.name)
+    }
+
+    /// Returns the markdown understood by Webex to notify the person.
+    fn markup(&self) -> String {
+        match &self.person_id {
+            Some(id) => format!("<@personId:{id}|{}>", self.name),
+            None => String::from("<@all>"),
+        }
+    }
+}
+
 #[derive(Default)]
 pub(crate) struct MessageEditor<'a> {
     textarea: TextArea<'a>,
     is_composing: bool,
     response_to: Option<Message>,
     editing_of: Option<Message>,
+    completion_candidates: Vec<String>,
+    is_markdown: bool,
+    /// Mode to restore once the message being edited is sent or dropped
+    markdown_before_edit: Option<bool>,
+    mentions: Vec<Mention>,
+    /// Modal editing state, when enabled in the config
+    vim: Option<Vim>,
 }
 
 impl MessageEditor<'_> {
@@ -29,9 +68,45 @@ impl MessageEditor<'_> {
         self.is_composing = is_editing;
     }
 
-    /// Sends a character to the editor.
+    /// Whether the message is sent as markdown instead of plain text.
+    pub(crate) fn is_markdown(&self) -> bool {
+        self.is_markdown
+    }
+
+    /// Sets whether the message is sent as markdown. The mode is kept when the editor is reset,
+    /// except after editing a message.
+    pub(crate) fn set_is_markdown(&mut self, is_markdown: bool) {
+        self.is_markdown = is_markdown;
+    }
+
+    /// Switches between markdown and plain text modes.
+    pub(crate) fn toggle_markdown(&mut self) {
+        self.is_markdown = !self.is_markdown;
+    }
+
+    /// Enables or disables vim-style modal editing.
+    pub(crate) fn set_vim_mode(&mut self, enabled: bool) {
+        self.vim = enabled.then(Vim::default);
+    }
+
+    /// Returns the mode of the editor if modal editing is enabled.
+    pub(crate) fn vim_mode(&self) -> Option<VimMode> {
+        self.vim.as_ref().map(Vim::mode)
+    }
+
+    /// Whether `Esc` is used by the editor, e.g. to leave insert mode, instead of
+    /// ending the composition.
+    pub(crate) fn captures_escape(&self) -> bool {
+        self.vim.as_ref().is_some_and(Vim::captures_escape)
+    }
+
+    /// Sends a character to the editor, returns whether the text was modified.
     pub(crate) fn input(&mut self, input: impl Into<Input>) -> bool {
-        self.textarea.input(input)
+        self.completion_candidates.clear();
+        match self.vim.as_mut() {
+            Some(vim) => vim.input(input.into(), &mut self.textarea),
+            None => self.textarea.input(input),
+        }
     }
 
     /// Inserts a newline in the editor.
@@ -61,6 +136,148 @@ impl MessageEditor<'_> {
         self.textarea = TextArea::new(lines);
         self.response_to = None;
         self.editing_of = None;
+        if let Some(is_markdown) = self.markdown_before_edit.take() {
+            self.is_markdown = is_markdown;
+        }
+        self.completion_candidates.clear();
+        self.mentions.clear();
+        // A new message is typed right away
+        if let Some(vim) = self.vim.as_mut() {
+            *vim = Vim::default();
+        }
+    }
+
+    /// Returns the content of the editor, to be kept as a draft.
+    pub(crate) fn draft(&self) -> Draft {
+        Draft {
+            text: self.lines().join("\n"),
+            mentions: self.mentions.clone(),
+            response_to: self.response_to.clone(),
+            editing_of: self.editing_of.clone(),
+        }
+    }
+
+    /// Replaces the content of the editor with a draft, the cursor is placed at the end.
+    pub(crate) fn load_draft(&mut self, draft: Draft) {
+        self.reset_with_text(draft.text);
+        self.textarea.move_cursor(CursorMove::Bottom);
+        self.textarea.move_cursor(CursorMove::End);
+        self.mentions = draft.mentions;
+        self.response_to = draft.response_to;
+        if let Some(message) = draft.editing_of {
+            self.start_editing(message);
+        }
+    }
+
+    /// Starts editing a message, as markdown if it was sent as markdown and as plain text
+    /// otherwise. The previous mode is restored when the editor is reset.
+    pub(crate) fn edit_message(&mut self, message: Message) {
+        let text = match &message.markdown {
+            Some(markdown) => markdown.clone(),
+            None => message.text.clone().unwrap_or_default(),
+        };
+        self.reset_with_text(text);
+        self.start_editing(message);
+    }
+
+    fn start_editing(&mut self, message: Message) {
+        self.markdown_before_edit = Some(self.is_markdown);
+        self.is_markdown = message.markdown.is_some();
+        self.editing_of = Some(message);
+    }
+
+    /// Replaces the text, keeping the message replied to or edited and the mentions.
+    /// The cursor is placed at the end of the text.
+    pub(crate) fn set_text(&mut self, text: &str) {
+        let lines = text.split('\n').map(|s| s.to_string()).collect::<Vec<_>>();
+        self.textarea = TextArea::new(lines);
+        self.textarea.move_cursor(CursorMove::Bottom);
+        self.textarea.move_cursor(CursorMove::End);
+        self.completion_candidates.clear();
+    }
+
+    /// Returns the partial name typed after an `@` before the cursor, if any.
+    /// The `@` must start a word and the name cannot contain spaces, and be typed in insert mode.
+    pub(crate) fn mention_query(&self) -> Option<String> {
+        if self.vim_mode().is_some_and(|mode| mode != VimMode::Insert) {
+            return None;
+        }
+        let (row, col) = self.textarea.cursor();
+        let before_cursor: String = self.lines().get(row)?.chars().take(col).collect();
+        let (before_at, query) = before_cursor.rsplit_once('@')?;
+        let starts_word = before_at.is_empty() || before_at.ends_with(char::is_whitespace);
+        match starts_word && !query.contains(char::is_whitespace) {
+            true => Some(query.to_string()),
+            false => None,
+        }
+    }
+
+    /// Replaces the `@` and partial name before the cursor with the mention.
+    pub(crate) fn insert_mention(&mut self, mention: Mention) {
+        if let Some(query) = self.mention_query() {
+            for _ in 0..=query.chars().count() {
+                self.textarea.delete_char();
+            }
+        }
+        self.textarea.insert_str(format!("{} ", mention.text()));
+        self.mentions.push(mention);
+    }
+
+    /// Returns whether the text contains mentions that need to be sent as markdown.
+    pub(crate) fn has_mentions(&self) -> bool {
+        let text = self.lines().join("\n");
+        self.mentions
+            .iter()
+            .any(|mention| text.contains(&mention.text()))
+    }
+
+    /// Returns the text of the message, with the mentions converted to Webex markup.
+    pub(crate) fn text_with_mentions(&self) -> String {
+        let mut text = self.lines().join("\n");
+        // Longest names first, so that a name does not replace the start of another
+        let mut mentions = self.mentions.clone();
+        mentions.sort_by_key(|mention| std::cmp::Reverse(mention.name.len()));
+        for mention in mentions {
+            text = replace_outside_code(&text, &mention.text(), &mention.markup());
+        }
+        text
+    }
+
+    /// Returns the path to upload if the editor contains an upload command.
+    pub(crate) fn upload_path(&self) -> Option<String> {
+        match self.lines() {
+            [line] => line
+                .strip_prefix(UPLOAD_COMMAND)
+                .map(|path| path.trim().to_string())
+                .filter(|path| !path.is_empty()),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the editor starts with the upload command.
+    pub(crate) fn is_upload_command(&self) -> bool {
+        self.lines()
+            .first()
+            .is_some_and(|line| line.starts_with(UPLOAD_COMMAND))
+    }
+
+    /// Completes the path of an upload command.
+    /// Returns false if the editor does not contain an upload command.
+    pub(crate) fn complete_upload_path(&mut self) -> bool {
+        if !self.is_upload_command() || self.lines().len() > 1 {
+            return false;
+        }
+        let partial = self.lines()[0][UPLOAD_COMMAND.len()..].to_string();
+        let completion = complete_path(&partial);
+        self.textarea = TextArea::new(vec![format!("{UPLOAD_COMMAND}{}", completion.completed)]);
+        self.textarea.move_cursor(CursorMove::End);
+        self.completion_candidates = completion.candidates;
+        true
+    }
+
+    /// Returns the possible completions of the last ambiguous path completion.
+    pub(crate) fn completion_candidates(&self) -> &[String] {
+        &self.completion_candidates
     }
 
     /// Returns the message to which the message is replying.
@@ -77,9 +294,251 @@ impl MessageEditor<'_> {
     pub(crate) fn editing_of(&self) -> Option<&Message> {
         self.editing_of.as_ref()
     }
+}
+
+/// Replaces the mention `from` with `to` in markdown text, except in code spans and fenced
+/// code blocks.
+fn replace_outside_code(text: &str, from: &str, to: &str) -> String {
+    let mut in_code_block = false;
+    text.split('\n')
+        .map(|line| {
+            if line.trim_start().starts_with("```") {
+                in_code_block = !in_code_block;
+                return line.to_string();
+            }
+            if in_code_block {
+                return line.to_string();
+            }
+            let parts: Vec<_> = line.split('`').collect();
+            // A backtick without its closing one does not start a code span
+            let nb_spans = (parts.len() - 1) / 2;
+            parts
+                .iter()
+                .enumerate()
+                .map(
+                    |(index, part)| match index % 2 == 1 && index <= 2 * nb_spans {
+                        true => part.to_string(),
+                        false => replace_mention(part, from, to),
+                    },
+                )
+                .collect::<Vec<_>>()
+                .join("`")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Replaces the mention `from` with `to` where it is not the start of a longer name,
+/// e.g. `@Bob` in `@Bob, hi` but not in `@Bobby`.
+fn replace_mention(text: &str, from: &str, to: &str) -> String {
+    let mut replaced = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(from) {
+        let end = start + from.len();
+        let is_longer_name = rest[end..]
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_alphanumeric() || c == '_');
+        replaced.push_str(&rest[..start]);
+        replaced.push_str(if is_longer_name { from } else { to });
+        rest = &rest[end..];
+    }
+    replaced.push_str(rest);
+    replaced
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyCode, KeyEvent};
+    use rstest::rstest;
+
+    #[test]
+    fn test_upload_path() {
+        let mut editor = MessageEditor::default();
+        editor.reset_with_text("/upload ~/logs/app.log ".to_string());
+        assert_eq!(editor.upload_path(), Some("~/logs/app.log".to_string()));
+        editor.reset_with_text("/upload ".to_string());
+        assert!(editor.is_upload_command());
+        assert_eq!(editor.upload_path(), None);
+        editor.reset_with_text("please /upload the file".to_string());
+        assert!(!editor.is_upload_command());
+        assert_eq!(editor.upload_path(), None);
+    }
+
+    #[test]
+    fn test_markdown_mode_survives_reset() {
+        let mut editor = MessageEditor::default();
+        assert!(!editor.is_markdown());
+        editor.toggle_markdown();
+        editor.reset_with_text("**bold**".to_string());
+        assert!(editor.is_markdown());
+        editor.toggle_markdown();
+        assert!(!editor.is_markdown());
+    }
+
+    #[test]
+    fn test_editing_uses_the_mode_of_the_message_then_restores_it() {
+        let mut editor = MessageEditor::default();
+        editor.set_is_markdown(true);
+        editor.edit_message(Message {
+            text: Some("plain".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(editor.lines(), ["plain"]);
+        assert!(!editor.is_markdown());
+        editor.reset();
+        assert!(editor.is_markdown());
+
+        editor.set_is_markdown(false);
+        editor.edit_message(Message {
+            text: Some("bold".to_string()),
+            markdown: Some("**bold**".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(editor.lines(), ["**bold**"]);
+        assert!(editor.is_markdown());
+        editor.reset();
+        assert!(!editor.is_markdown());
+    }
+
+    #[test]
+    fn test_mention_query() {
+        let mut editor = MessageEditor::default();
+        editor.input(Input::from(KeyEvent::from(KeyCode::Char('@'))));
+        assert_eq!(editor.mention_query(), Some(String::new()));
+        for c in "Bo".chars() {
+            editor.input(Input::from(KeyEvent::from(KeyCode::Char(c))));
+        }
+        assert_eq!(editor.mention_query(), Some("Bo".to_string()));
+        editor.input(Input::from(KeyEvent::from(KeyCode::Char(' '))));
+        assert_eq!(editor.mention_query(), None);
+
+        editor.reset_with_text("mail me at a@b".to_string());
+        editor.textarea.move_cursor(CursorMove::End);
+        assert_eq!(editor.mention_query(), None);
+    }
+
+    #[test]
+    fn test_insert_mentions() {
+        let mut editor = MessageEditor::default();
+        editor.textarea.insert_str("hi @bo");
+        editor.insert_mention(Mention {
+            name: "Bob".to_string(),
+            person_id: Some("p1".to_string()),
+        });
+        editor.textarea.insert_str("and @");
+        editor.insert_mention(Mention {
+            name: "all".to_string(),
+            person_id: None,
+        });
+        assert_eq!(editor.lines()[0], "hi @Bob and @all ");
+        assert!(editor.has_mentions());
+        assert_eq!(
+            editor.text_with_mentions(),
+            "hi <@personId:p1|Bob> and <@all> "
+        );
+        editor.reset();
+        assert!(!editor.has_mentions());
+    }
+
+    #[test]
+    fn test_mention_is_not_part_of_a_longer_name() {
+        let mut editor = MessageEditor::default();
+        editor.textarea.insert_str("@Bobby says hi @");
+        editor.insert_mention(Mention {
+            name: "Bob".to_string(),
+            person_id: Some("p1".to_string()),
+        });
+        assert_eq!(
+            editor.text_with_mentions(),
+            "@Bobby says hi <@personId:p1|Bob> "
+        );
+    }
+
+    #[test]
+    fn test_vim_mode() {
+        let mut editor = MessageEditor::default();
+        assert_eq!(editor.vim_mode(), None);
+        assert!(!editor.captures_escape());
+        editor.set_vim_mode(true);
+        assert!(editor.input(Input::from(KeyEvent::from(KeyCode::Char('@')))));
+        assert_eq!(editor.mention_query(), Some(String::new()));
+        // Esc leaves insert mode, then is left to the app
+        assert!(editor.captures_escape());
+        editor.input(Input::from(KeyEvent::from(KeyCode::Esc)));
+        assert_eq!(editor.vim_mode(), Some(VimMode::Normal));
+        assert!(!editor.captures_escape());
+        assert_eq!(editor.mention_query(), None);
+        // Commands do not insert text
+        assert!(editor.input(Input::from(KeyEvent::from(KeyCode::Char('x')))));
+        assert!(editor.is_empty());
+        editor.reset();
+        assert_eq!(editor.vim_mode(), Some(VimMode::Insert));
+    }
+
+    #[test]
+    fn test_set_text_keeps_context() {
+        let mut editor = MessageEditor::default();
+        editor.textarea.insert_str("@");
+        editor.insert_mention(Mention {
+            name: "Bob".to_string(),
+            person_id: Some("p1".to_string()),
+        });
+        editor.set_response_to(Some(Message::default()));
+        editor.set_text("Hi @Bob\nsee you");
+        assert_eq!(editor.lines(), ["Hi @Bob", "see you"]);
+        assert_eq!(editor.textarea.cursor(), (1, 7));
+        assert!(editor.response_to().is_some());
+        assert_eq!(
+            editor.text_with_mentions(),
+            "Hi <@personId:p1|Bob>\nsee you"
+        );
+    }
+
+    #[rstest(
+        text,
+        expected,
+        case("hi @Bob", "hi <@>"),
+        case("run `@Bob` now @Bob", "run `@Bob` now <@>"),
+        case("a ` @Bob", "a ` <@>"),
+        case("```\n@Bob\n```\n@Bob", "```\n@Bob\n```\n<@>"),
+        case("@Bob, @Bobby and @Bob_2 @Bob.", "<@>, @Bobby and @Bob_2 <@>.")
+    )]
+    fn test_replace_outside_code(text: &str, expected: &str) {
+        assert_eq!(replace_outside_code(text, "@Bob", "<@>"), expected);
+    }
+
+    #[test]
+    fn test_draft_round_trip() {
+        let mut editor = MessageEditor::default();
+        editor.edit_message(Message {
+            text: Some("line 1\nline 2".to_string()),
+            ..Default::default()
+        });
+        let draft = editor.draft();
+        editor.reset();
+        editor.load_draft(draft);
+        assert_eq!(editor.lines(), ["line 1", "line 2"]);
+        assert_eq!(editor.textarea.cursor(), (1, 6));
+        assert!(editor.editing_of().is_some());
+    }
+
+    #[test]
+    fn test_complete_upload_path_keeps_context() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("report.pdf"), "").unwrap();
+        let mut editor = MessageEditor::default();
+        editor.reset_with_text(format!("{UPLOAD_COMMAND}{}/rep", dir.path().display()));
+        editor.set_response_to(Some(Message::default()));
+        assert!(editor.complete_upload_path());
+        assert_eq!(
+            editor.lines()[0],
+            format!("{UPLOAD_COMMAND}{}/report.pdf", dir.path().display())
+        );
+        assert!(editor.response_to().is_some());
 
-    /// Sets the message being edited.
-    pub(crate) fn set_editing_of(&mut self, editing_of: Option<Message>) {
-        self.editing_of = editing_of;
+        editor.reset_with_text("not a command".to_string());
+        assert!(!editor.complete_upload_path());
     }
 }
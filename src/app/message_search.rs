@@ -0,0 +1,170 @@
+// app/message_search.rs
+
+//! Full-text search in the messages of all the rooms in cache.
+
+use ratatui::widgets::TableState;
+
+use super::cache::room::RoomId;
+use super::cache::MessageId;
+
+/// Number of characters shown before the first match in an excerpt.
+const EXCERPT_CONTEXT: usize = 20;
+
+/// A message matching the search, with what is needed to display it.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SearchResult {
+    pub(crate) room_id: RoomId,
+    pub(crate) message_id: MessageId,
+    pub(crate) room_title: String,
+    pub(crate) author: String,
+    /// Creation date in RFC 3339 format
+    pub(crate) created: String,
+    /// The content on a single line, starting near the first match
+    pub(crate) excerpt: String,
+}
+
+/// Query typed by the user and the matching messages, most recent first.
+#[derive(Default)]
+pub(crate) struct MessageSearch {
+    query: String,
+    results: Vec<SearchResult>,
+    table_state: TableState,
+}
+
+impl MessageSearch {
+    pub(crate) fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Adds a character at the end of the query.
+    pub(crate) fn push(&mut self, c: char) {
+        self.query.push(c);
+    }
+
+    /// Removes the last character of the query.
+    pub(crate) fn pop(&mut self) {
+        self.query.pop();
+    }
+
+    pub(crate) fn results(&self) -> &[SearchResult] {
+        &self.results
+    }
+
+    /// Replaces the results, keeping the selected message selected if it is still found.
+    pub(crate) fn set_results(&mut self, results: Vec<SearchResult>) {
+        let selected_id = self
+            .selected_result()
+            .map(|result| result.message_id.clone());
+        self.results = results;
+        let selected = match selected_id.and_then(|id| {
+            self.results
+                .iter()
+                .position(|result| result.message_id == id)
+        }) {
+            Some(index) => Some(index),
+            None if self.results.is_empty() => None,
+            None => Some(0),
+        };
+        self.table_state.select(selected);
+    }
+
+    /// Returns the selected result, if any.
+    pub(crate) fn selected_result(&self) -> Option<&SearchResult> {
+        self.table_state
+            .selected()
+            .and_then(|index| self.results.get(index))
+    }
+
+    /// Selects the next result, wrapping around at the end of the list.
+    pub(crate) fn select_next(&mut self) {
+        let next = match self.table_state.selected() {
+            _ if self.results.is_empty() => None,
+            Some(selected) if selected + 1 < self.results.len() => Some(selected + 1),
+            _ => Some(0),
+        };
+        self.table_state.select(next);
+    }
+
+    /// Selects the previous result, wrapping around at the start of the list.
+    pub(crate) fn select_previous(&mut self) {
+        let previous = match self.table_state.selected() {
+            _ if self.results.is_empty() => None,
+            Some(selected) if selected > 0 => Some(selected - 1),
+            _ => Some(self.results.len() - 1),
+        };
+        self.table_state.select(previous);
+    }
+
+    pub(crate) fn table_state_mut(&mut self) -> &mut TableState {
+        &mut self.table_state
+    }
+}
+
+/// Returns the text on a single line, starting shortly before the first word of the query
+/// found in it, so that the match is visible in a narrow column.
+pub(crate) fn excerpt(text: &str, query: &str) -> String {
+    let line = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let lowercase = line.to_lowercase();
+    let first_match = query
+        .split_whitespace()
+        .filter_map(|term| lowercase.find(&term.to_lowercase()))
+        .min()
+        // Lowercasing may change byte offsets, only use them when it did not
+        .filter(|_| lowercase.len() == line.len())
+        .unwrap_or_default();
+    let match_char = line[..first_match].chars().count();
+    match match_char.checked_sub(EXCERPT_CONTEXT) {
+        Some(start) if start > 0 => format!("…{}", line.chars().skip(start).collect::<String>()),
+        _ => line,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn result(id: &str) -> SearchResult {
+        SearchResult {
+            room_id: "room".to_string(),
+            message_id: id.to_string(),
+            room_title: "Room".to_string(),
+            author: "Ann".to_string(),
+            created: "2024-01-01T00:00:00.000Z".to_string(),
+            excerpt: "hello".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_selection_follows_results() {
+        let mut search = MessageSearch::default();
+        assert_eq!(search.selected_result(), None);
+        search.set_results(vec![result("1"), result("2")]);
+        assert_eq!(search.selected_result().unwrap().message_id, "1");
+        search.select_previous();
+        assert_eq!(search.selected_result().unwrap().message_id, "2");
+        // A new message arrives first, the selection stays on the same message
+        search.set_results(vec![result("0"), result("1"), result("2")]);
+        assert_eq!(search.selected_result().unwrap().message_id, "2");
+        search.select_next();
+        assert_eq!(search.selected_result().unwrap().message_id, "0");
+        search.set_results(vec![]);
+        assert_eq!(search.selected_result(), None);
+    }
+
+    #[rstest(
+        text,
+        query,
+        expected,
+        case("short\nmessage", "message", "short message"),
+        case("no match here", "zzz", "no match here"),
+        case(
+            "a long message before the link https://example.com/doc",
+            "EXAMPLE",
+            "…re the link https://example.com/doc"
+        )
+    )]
+    fn test_excerpt(text: &str, query: &str, expected: &str) {
+        assert_eq!(excerpt(text, query), expected);
+    }
+}
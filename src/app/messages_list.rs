@@ -4,6 +4,8 @@
 
 use ratatui::widgets::{ScrollbarState, TableState};
 
+use super::cache::thread_view::ThreadView;
+
 #[derive(Default)]
 pub(crate) struct MessagesList {
     table_state: TableState,
@@ -13,6 +15,7 @@ pub(crate) struct MessagesList {
     scroll_state: ScrollbarState,
     nb_messages: usize,
     nb_lines: usize,
+    thread_view: ThreadView,
 }
 
 impl MessagesList {
@@ -157,6 +160,14 @@ impl MessagesList {
         self.nb_lines = nb_lines;
     }
 
+    pub(crate) fn thread_view(&self) -> &ThreadView {
+        &self.thread_view
+    }
+
+    pub(crate) fn thread_view_mut(&mut self) -> &mut ThreadView {
+        &mut self.thread_view
+    }
+
     pub(crate) fn scroll_state_mut(&mut self) -> &mut ScrollbarState {
         &mut self.scroll_state
     }
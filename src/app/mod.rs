@@ -3,34 +3,86 @@
 //! Controller used to handle user input and interaction with the `Teams` thread.
 
 pub(crate) mod actions;
+pub(crate) mod bookmarks;
 pub(crate) mod cache;
 pub(crate) mod callbacks;
+pub(crate) mod cards;
+pub(crate) mod drafts;
+pub(crate) mod external_editor;
+pub(crate) mod favourites;
+pub(crate) mod files;
+pub(crate) mod forward;
+pub(crate) mod links;
+pub(crate) mod markdown;
+pub(crate) mod members_list;
 pub(crate) mod message_editor;
+pub(crate) mod message_search;
 pub(crate) mod messages_list;
+pub(crate) mod mutes;
+pub(crate) mod outbox;
+pub(crate) mod persistence;
+pub(crate) mod picker;
+pub(crate) mod prompt;
 pub(crate) mod rooms_list;
+pub(crate) mod scheduled;
+pub(crate) mod sections;
 pub(crate) mod state;
+pub(crate) mod vim;
 
 use self::state::AppState;
 use crate::app::actions::Action;
+use crate::app::bookmarks::{Bookmark, Bookmarks};
+use crate::app::cards::{CardAction, CardForm, CardPart};
+use crate::app::drafts::Drafts;
+use crate::app::external_editor::{file_content, message_from_file_content};
+use crate::app::favourites::Favourites;
+use crate::app::files::expand_tilde;
+use crate::app::forward::{forward_header, forwarded_message};
+use crate::app::links::{is_web_url, urls_in_message};
+use crate::app::message_editor::Mention;
+use crate::app::mutes::{Mute, Mutes};
+use crate::app::outbox::{Outbox, OutboxCommand};
+use crate::app::picker::{Picker, PickerItem, PickerKind};
+use crate::app::prompt::{parse_emails, Prompt, PromptKind};
 use crate::app::rooms_list::SearchState;
-use crate::app::state::ActivePane;
+use crate::app::scheduled::{parse_send_time, Schedule};
+use crate::app::state::{ActivePane, MENTION_ALL};
+use crate::config::UserConfig;
 use crate::inputs::key::Key;
 use crate::teams::app_handler::AppCmdEvent;
 use crate::theme::Theme;
 use cache::room::RoomId;
+use cache::room_list_filter::{ActivityThresholds, RoomsListFilter};
+use cache::thread_view::thread_id_of;
+use cache::{is_local_message, MessageId};
 
+use arboard::Clipboard;
+use chrono::{Local, Utc};
 use color_eyre::{eyre::eyre, Result};
 use crossterm::event::KeyEvent;
 use log::*;
+use std::time::{Duration, Instant};
 use tui_logger::TuiWidgetEvent;
 use tui_textarea::Input;
+use webex::types::MessageOut;
 use webex::Message;
 
+/// Minimum time between two notifications that we are typing.
+const TYPING_NOTIFICATION_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Number of characters typed before persons are searched on the server.
+const PEOPLE_SEARCH_MIN_LENGTH: usize = 3;
+
+/// Time without typing after which the directory is searched for the query.
+const PEOPLE_SEARCH_DELAY: Duration = Duration::from_millis(300);
+
 /// Return status indicating whether the app should exit or not.
 #[derive(Debug, PartialEq, Eq)]
 pub(crate) enum AppReturn {
     Exit,
     Continue,
+    /// Suspend the interface to edit the given content in an external editor
+    EditExternally(String),
 }
 
 /// Priority level for events from the app to the webex thread
@@ -52,39 +104,94 @@ impl App<'_> {
     /// # Arguments
     ///
     /// * `app_to_teams_tx` - An unbounded channel used to send commands to the `Teams` thread
+    /// * `config` - The user configuration, with command line overrides applied
     pub(crate) fn new(
         app_to_teams_tx_low: tokio::sync::mpsc::UnboundedSender<AppCmdEvent>,
         app_to_teams_tx_high: tokio::sync::mpsc::UnboundedSender<AppCmdEvent>,
-        debug: bool,
-        messages_to_load: u32,
+        config: UserConfig,
         theme: Theme,
     ) -> Self {
+        let mut state = AppState {
+            debug: config.debug,
+            messages_to_load: config.messages_to_load,
+            config,
+            theme,
+            ..Default::default()
+        };
+        state.message_editor.set_is_markdown(state.config.markdown);
+        state.message_editor.set_vim_mode(state.config.vim_mode);
+        state.drafts = Drafts::load();
+        state.schedule = Schedule::load();
+        state.outbox = Outbox::load();
+        state.bookmarks = Bookmarks::load();
+        state.set_favourites(Favourites::load());
+        state.mutes = Mutes::load();
+        state.activity_thresholds = state.config.activity_thresholds().unwrap_or_else(|e| {
+            warn!("Using the default activity thresholds: {e}");
+            ActivityThresholds::default()
+        });
+        // Messages still to be sent from the last session
+        for entry in state.outbox.entries().to_vec() {
+            if let OutboxCommand::Send(msg_to_send) = &entry.command {
+                state.add_local_message(entry.id, msg_to_send);
+            }
+        }
         Self {
             app_to_teams_tx_low,
             app_to_teams_tx_high,
-            state: AppState {
-                debug,
-                messages_to_load,
-                theme,
-                ..Default::default()
-            },
+            state,
         }
     }
 
     /// Process a key event to the text editor if active, or to execute
     /// the corresponding action otherwise
     pub(crate) async fn process_key_event(&mut self, key_event: KeyEvent) -> AppReturn {
-        if self.state.message_editor.is_composing() {
+        if self.state.send_time_prompt.is_some() {
+            trace!("Send time keyevent: {key_event:?}");
+            self.process_send_time_key(key_event)
+        } else if self.state.prompt.is_some() {
+            trace!("Prompt keyevent: {key_event:?}");
+            self.process_prompt_key(key_event)
+        } else if self.state.message_editor.is_composing() {
             trace!("Keyevent: {key_event:?}");
             self.process_editing_key(key_event)
+        } else if self.state.card_form.is_some() {
+            trace!("Card form keyevent: {key_event:?}");
+            self.process_card_form_key(key_event)
+        } else if self.state.message_search.is_some() {
+            trace!("Message search keyevent: {key_event:?}");
+            self.process_message_search_key(key_event)
         } else if self.state.rooms_list.search_state() == &SearchState::Entering {
             trace!("Search entering keyevent: {key_event:?}");
             self.process_search_entering_key(key_event)
+        } else if self.is_searching_picker_open() {
+            trace!("Searching picker keyevent: {key_event:?}");
+            self.process_searching_picker_key(key_event)
+        } else if let Some(index) = self.link_number(key_event) {
+            // Links are numbered in the picker to open them with a single key
+            if let Some(picker) = self.state.picker.as_mut() {
+                if picker.select(index) {
+                    return self.do_action(Key::Enter);
+                }
+            }
+            AppReturn::Continue
         } else {
             self.do_action(Key::from(key_event))
         }
     }
 
+    /// Returns the index of the link chosen with a digit key, if the links picker is open.
+    fn link_number(&self, key_event: KeyEvent) -> Option<usize> {
+        self.state
+            .picker
+            .as_ref()
+            .filter(|picker| picker.kind() == &PickerKind::Links)?;
+        match Key::from(key_event) {
+            Key::Char(c @ '1'..='9') => c.to_digit(10).map(|n| n as usize - 1),
+            _ => None,
+        }
+    }
+
     /// Handle a user action (non-editing mode)
     fn do_action(&mut self, key: crate::inputs::key::Key) -> AppReturn {
         if let Some(action) = self.state.actions.find(key) {
@@ -101,7 +208,11 @@ impl App<'_> {
                     };
                 }
                 Action::ComposeNewMessage => {
-                    self.state.message_editor.reset();
+                    // A draft of the room is resumed
+                    if self.state.message_editor.draft().is_empty() {
+                        self.state.message_editor.reset();
+                        self.reply_in_focused_thread();
+                    }
                     self.state.message_editor.set_is_composing(true);
                     self.state.set_active_pane(Some(ActivePane::Compose));
                 }
@@ -116,6 +227,9 @@ impl App<'_> {
                     self.state.message_editor.set_is_composing(false);
                     self.state.set_active_pane(Some(ActivePane::Messages));
                 }
+                Action::ToggleMarkdown => {
+                    self.state.message_editor.toggle_markdown();
+                }
                 Action::LogExitPageMode => {
                     self.state.log_state.transition(TuiWidgetEvent::EscapeKey);
                 }
@@ -153,7 +267,7 @@ impl App<'_> {
                     self.state.log_state.transition(TuiWidgetEvent::HideKey);
                 }
                 Action::MarkRead => {
-                    self.state.mark_active_read();
+                    self.acknowledge_active_room();
                     self.next_room();
                 }
                 Action::NextPane => {
@@ -168,6 +282,173 @@ impl App<'_> {
                 Action::PreviousRoomFilter => {
                     self.previous_filtering_mode();
                 }
+                Action::SendLater => {
+                    if let Err(e) = self.open_send_time_prompt() {
+                        error!("Could not schedule message: {e}");
+                    }
+                }
+                Action::ScheduleMessage => {
+                    if let Err(e) = self.schedule_message_buffer() {
+                        error!("Could not schedule message: {e}");
+                    }
+                }
+                Action::CloseSendTimePrompt => {
+                    self.state.send_time_prompt = None;
+                }
+                Action::NewDirectChat => self.open_people_picker(),
+                Action::NewSpace => self.state.open_prompt(Prompt::new(PromptKind::SpaceTitle)),
+                Action::ConfirmPrompt => {
+                    let prompt = self.state.prompt.clone();
+                    if let Err(e) = self.confirm_prompt() {
+                        error!("{e}");
+                        // The prompt stays open for the user to correct the input
+                        if let Some(mut prompt) = prompt {
+                            prompt.set_error(e.to_string());
+                            self.state.open_prompt(prompt);
+                        }
+                    }
+                }
+                Action::ClosePrompt => {
+                    self.state.close_prompt();
+                }
+                Action::ShowScheduledMessages => {
+                    self.state.show_scheduled = true;
+                    self.state.schedule.select_first();
+                }
+                Action::NextScheduledMessage => self.state.schedule.select_next(),
+                Action::PreviousScheduledMessage => self.state.schedule.select_previous(),
+                Action::EditScheduledMessage => {
+                    if let Err(e) = self.edit_selected_scheduled_message() {
+                        error!("Could not edit scheduled message: {e}");
+                    }
+                }
+                Action::CancelScheduledMessage => {
+                    if let Err(e) = self.cancel_selected_scheduled_message() {
+                        error!("Could not cancel scheduled message: {e}");
+                    }
+                }
+                Action::CloseScheduledMessages => {
+                    self.state.show_scheduled = false;
+                }
+                Action::ShowOutbox => {
+                    self.state.show_outbox = true;
+                    self.state.outbox.select_first();
+                }
+                Action::NextOutboxEntry => self.state.outbox.select_next(),
+                Action::PreviousOutboxEntry => self.state.outbox.select_previous(),
+                Action::ResendOutboxEntry => {
+                    if let Err(e) = self.resend_selected_outbox_entry() {
+                        error!("Could not resend: {e}");
+                    }
+                }
+                Action::DiscardOutboxEntry => {
+                    if let Err(e) = self.discard_selected_outbox_entry() {
+                        error!("Could not discard: {e}");
+                    }
+                }
+                Action::CloseOutbox => {
+                    self.state.show_outbox = false;
+                }
+                Action::ToggleBookmark => {
+                    if let Err(e) = self.toggle_bookmark_of_selected_message() {
+                        error!("Could not bookmark message: {e}");
+                    }
+                }
+                Action::ShowBookmarks => {
+                    self.state.show_bookmarks = true;
+                    self.state.bookmarks.select_first();
+                }
+                Action::NextBookmark => self.state.bookmarks.select_next(),
+                Action::PreviousBookmark => self.state.bookmarks.select_previous(),
+                Action::OpenBookmark => {
+                    if let Err(e) = self.open_selected_bookmark() {
+                        error!("Could not go to the message: {e}");
+                    }
+                }
+                Action::DeleteBookmark => {
+                    if let Some(message_id) = self
+                        .state
+                        .bookmarks
+                        .selected()
+                        .map(|bookmark| bookmark.message_id.clone())
+                    {
+                        self.state.bookmarks.remove(&message_id);
+                    }
+                    if self.state.bookmarks.is_empty() {
+                        self.state.show_bookmarks = false;
+                    }
+                }
+                Action::CloseBookmarks => {
+                    self.state.show_bookmarks = false;
+                }
+                Action::ShowMembers => self.show_members(),
+                Action::NextMember => {
+                    let nb_members = self.state.active_room_members().len();
+                    self.state.members_list.select_next(nb_members);
+                }
+                Action::PreviousMember => {
+                    let nb_members = self.state.active_room_members().len();
+                    self.state.members_list.select_previous(nb_members);
+                }
+                Action::AddMember => self.open_new_member_picker(),
+                Action::RemoveMember => {
+                    if let Err(e) = self.remove_selected_member() {
+                        error!("Could not remove member: {e}");
+                    }
+                }
+                Action::CloseMembers => {
+                    self.state.show_members = false;
+                }
+                Action::ShowRoomDetails => self.show_room_details(),
+                Action::RenameRoom => {
+                    if let Some(room) = self.state.active_room() {
+                        let kind = PromptKind::RoomTitle {
+                            room_id: room.id.clone(),
+                        };
+                        let title = room.title.clone().unwrap_or_default();
+                        self.state.open_prompt(Prompt::with_input(kind, title));
+                    }
+                }
+                Action::ToggleRoomLock => {
+                    if let Err(e) = self.toggle_active_room_lock() {
+                        error!("Could not lock or unlock the room: {e}");
+                    }
+                }
+                Action::CloseRoomDetails => {
+                    self.state.show_room_details = false;
+                }
+                Action::ToggleFavourite => {
+                    if let Some(room_id) = self.state.id_of_selected_room() {
+                        match self.state.toggle_favourite(&room_id) {
+                            true => info!("Room added to favourites"),
+                            false => info!("Room removed from favourites"),
+                        }
+                        // The room moves to or from the favourites section
+                        self.state.update_room_selection_with_active_room();
+                    }
+                }
+                Action::ToggleMute => {
+                    if let Some(room_id) = self.state.id_of_selected_room() {
+                        match self.state.unmute(&room_id) {
+                            true => info!("Room unmuted"),
+                            false => {
+                                self.state.mute(&room_id, Mute::Always);
+                                info!("Room muted");
+                            }
+                        }
+                        // The room moves to or from the bottom of the list
+                        self.state.update_room_selection_with_active_room();
+                    }
+                }
+                Action::SnoozeRoom => {
+                    if let Some(room_id) = self.state.id_of_selected_room() {
+                        let kind = PromptKind::SnoozeUntil { room_id };
+                        self.state.open_prompt(Prompt::new(kind));
+                    }
+                }
+                Action::OpenExternalEditor => {
+                    return AppReturn::EditExternally(self.external_editor_content());
+                }
                 Action::Quit => return AppReturn::Exit,
                 Action::RespondMessage => {
                     if let Err(e) = self.respond_to_selected_message() {
@@ -315,6 +596,125 @@ impl App<'_> {
                         error!("Could not delete selected rooms: {e}");
                     }
                 }
+                Action::ShowAttachments => {
+                    if let Err(e) = self.show_attachments_of_selected_message() {
+                        error!("Could not show attachments: {e}");
+                    }
+                }
+                Action::DownloadAllAttachments => {
+                    if let Some(picker) = self.state.close_picker() {
+                        for item in picker.items() {
+                            self.download_attachment(&item.value);
+                        }
+                    }
+                }
+                Action::ShowLinks => {
+                    if let Err(e) = self.show_links() {
+                        error!("Could not show links: {e}");
+                    }
+                }
+                Action::CopyLink => {
+                    if let Some(item) = self
+                        .state
+                        .close_picker()
+                        .and_then(|picker| picker.selected_item().cloned())
+                    {
+                        if let Err(e) = copy_to_clipboard(&item.value) {
+                            error!("Could not copy link: {e}");
+                        }
+                    }
+                }
+                Action::PickerNext => {
+                    if let Some(picker) = self.state.picker.as_mut() {
+                        picker.select_next();
+                    }
+                }
+                Action::PickerPrevious => {
+                    if let Some(picker) = self.state.picker.as_mut() {
+                        picker.select_previous();
+                    }
+                }
+                Action::PickerConfirm => {
+                    if let Err(e) = self.confirm_picker_choice() {
+                        error!("Could not use the chosen item: {e}");
+                    }
+                }
+                Action::PickerClose => {
+                    self.state.close_picker();
+                }
+                Action::ToggleThreadsCollapsed => {
+                    self.state
+                        .messages_list
+                        .thread_view_mut()
+                        .toggle_collapsed();
+                    self.update_messages_after_view_change();
+                }
+                Action::ToggleThreadExpanded => {
+                    if let Err(e) = self.toggle_selected_thread_expanded() {
+                        error!("Could not expand thread: {e}");
+                    }
+                }
+                Action::FocusThread | Action::LeaveThread => {
+                    if let Err(e) = self.toggle_focus_on_selected_thread() {
+                        error!("Could not focus thread: {e}");
+                    }
+                }
+                Action::StartMessageSearch => {
+                    self.state.open_message_search();
+                }
+                Action::NextSearchResult => {
+                    if let Some(search) = self.state.message_search.as_mut() {
+                        search.select_next();
+                    }
+                }
+                Action::PreviousSearchResult => {
+                    if let Some(search) = self.state.message_search.as_mut() {
+                        search.select_previous();
+                    }
+                }
+                Action::OpenSearchResult => {
+                    if let Err(e) = self.open_selected_search_result() {
+                        error!("Could not go to the message: {e}");
+                    }
+                }
+                Action::SearchOlderMessages => {
+                    // Results are updated when the messages arrive
+                    match self.state.rooms_list.active_room_id().cloned() {
+                        Some(room_id) => self.get_messages_before_first(&room_id),
+                        None => warn!("Select a room to load its older messages."),
+                    }
+                }
+                Action::EndMessageSearch => {
+                    self.state.close_message_search();
+                }
+                Action::FillCard => {
+                    if let Err(e) = self.open_card_form() {
+                        error!("Could not fill card: {e}");
+                    }
+                }
+                Action::NextCardField => {
+                    if let Some(form) = self.state.card_form.as_mut() {
+                        form.select_next();
+                    }
+                }
+                Action::PreviousCardField => {
+                    if let Some(form) = self.state.card_form.as_mut() {
+                        form.select_previous();
+                    }
+                }
+                Action::ActivateCardField => {
+                    if let Err(e) = self.activate_card_field() {
+                        error!("Could not use the card: {e}");
+                    }
+                }
+                Action::CloseCardForm => {
+                    self.state.card_form = None;
+                }
+                Action::ForwardMessage => {
+                    if let Err(e) = self.open_forward_picker() {
+                        error!("Could not forward message: {e}");
+                    }
+                }
             }
         } else {
             warn!("No action associated with {key} in this mode");
@@ -326,19 +726,48 @@ impl App<'_> {
     // Handle a key while in text editing mode
     fn process_editing_key(&mut self, key_event: KeyEvent) -> AppReturn {
         let key: Key = key_event.into();
+        if let Some(picker) = &self.state.picker {
+            // The mention picker is open, navigate it or keep typing the name
+            match key {
+                Key::Down | Key::Up | Key::Esc => return self.do_action(key),
+                Key::Enter | Key::Tab if picker.selected_item().is_some() => {
+                    if let Err(e) = self.confirm_picker_choice() {
+                        error!("Could not insert mention: {e}");
+                    }
+                    return AppReturn::Continue;
+                }
+                _ => (),
+            }
+        }
         match key {
             Key::Ctrl('c') => return AppReturn::Exit,
-            Key::Esc => {
+            Key::Esc if !self.state.message_editor.captures_escape() => {
                 self.state.message_editor.set_is_composing(false);
-                self.state.set_active_pane(Some(ActivePane::Messages))
+                self.state.set_active_pane(Some(ActivePane::Messages));
+                self.notify_typing_stopped();
             }
             Key::AltEnter => self.state.message_editor.insert_newline(),
+            Key::Alt('m') => self.state.message_editor.toggle_markdown(),
+            Key::Alt('e') | Key::Alt('s') => {
+                self.notify_typing_stopped();
+                return self.do_action(key);
+            }
+            Key::Tab if self.state.message_editor.is_upload_command() => {
+                self.state.message_editor.complete_upload_path();
+            }
             Key::Enter => {
                 if let Err(e) = self.send_message_buffer() {
                     error!("Could not send message: {e}");
                 };
+                self.update_mention_picker();
+                self.notify_typing_stopped();
+            }
+            _ => {
+                if self.state.message_editor.input(Input::from(key_event)) {
+                    self.notify_typing();
+                }
+                self.update_mention_picker();
             }
-            _ => _ = self.state.message_editor.input(Input::from(key_event)),
         }
         AppReturn::Continue
     }
@@ -405,58 +834,1035 @@ impl App<'_> {
         AppReturn::Continue
     }
 
+    // Handle a key while the message search is open
+    fn process_message_search_key(&mut self, key_event: KeyEvent) -> AppReturn {
+        let key: Key = key_event.into();
+        let Some(search) = self.state.message_search.as_mut() else {
+            return AppReturn::Continue;
+        };
+        match key {
+            Key::Char(c) => search.push(c),
+            Key::Backspace => search.pop(),
+            _ => return self.do_action(key),
+        }
+        self.state.update_message_search();
+        AppReturn::Continue
+    }
+
+    // Handle a key while choosing the room to forward a message to, or the person to chat with
+    fn process_searching_picker_key(&mut self, key_event: KeyEvent) -> AppReturn {
+        let key: Key = key_event.into();
+        let Some(picker) = self.state.picker.as_ref() else {
+            return AppReturn::Continue;
+        };
+        let kind = picker.kind().clone();
+        let mut query = picker.query().to_string();
+        match key {
+            Key::Char(c) => query.push(c),
+            Key::Backspace => {
+                query.pop();
+            }
+            _ => return self.do_action(key),
+        }
+        // Persons we never met are only known to the server, they are searched once the
+        // user stops typing
+        let searches_people = matches!(kind, PickerKind::People | PickerKind::NewMember(_));
+        self.state.people_search = (searches_people
+            && query.chars().count() >= PEOPLE_SEARCH_MIN_LENGTH)
+            .then(|| (query.clone(), Instant::now()));
+        let (title, items) = self.searching_picker_content(&kind, &query);
+        if let Some(picker) = self.state.picker.as_mut() {
+            picker.set_title(title);
+            picker.set_query(&query);
+            picker.set_items(items);
+        }
+        AppReturn::Continue
+    }
+
+    /// Returns the title and the items matching the query of a picker searched as the user
+    /// types.
+    fn searching_picker_content(
+        &self,
+        kind: &PickerKind,
+        query: &str,
+    ) -> (String, Vec<PickerItem>) {
+        match kind {
+            PickerKind::People => (
+                format!("Chat with: {query}"),
+                self.state.people_items(query),
+            ),
+            PickerKind::NewMember(_) => (
+                format!("Add member: {query}"),
+                self.state.new_member_items(query),
+            ),
+            _ => (
+                format!("Forward to: {query}"),
+                self.state.forward_items(query),
+            ),
+        }
+    }
+
+    /// Returns true if a picker whose items are searched as the user types is open:
+    /// the rooms to forward a message to, the persons to chat with or to add to a room.
+    fn is_searching_picker_open(&self) -> bool {
+        self.state.picker.as_ref().is_some_and(|picker| {
+            matches!(
+                picker.kind(),
+                PickerKind::Forward(..) | PickerKind::People | PickerKind::NewMember(_)
+            )
+        })
+    }
+
+    // Handle a key while the prompt is open
+    fn process_prompt_key(&mut self, key_event: KeyEvent) -> AppReturn {
+        let key: Key = key_event.into();
+        let Some(prompt) = self.state.prompt.as_mut() else {
+            return AppReturn::Continue;
+        };
+        match key {
+            Key::Char(c) => prompt.push(c),
+            Key::Backspace => prompt.pop(),
+            _ => return self.do_action(key),
+        }
+        AppReturn::Continue
+    }
+
+    // Handle a key while the card form is open
+    fn process_card_form_key(&mut self, key_event: KeyEvent) -> AppReturn {
+        let key: Key = key_event.into();
+        let Some(form) = self.state.card_form.as_mut() else {
+            return AppReturn::Continue;
+        };
+        match key {
+            Key::Char(c) => form.type_char(c),
+            Key::Backspace => form.delete_char(),
+            _ => return self.do_action(key),
+        }
+        AppReturn::Continue
+    }
+
+    /// Opens a form to fill the card of the selected message.
+    fn open_card_form(&mut self) -> Result<()> {
+        let message = self.state.selected_message()?;
+        let form = CardForm::from_message(message)
+            .ok_or(eyre!("The message has no card with inputs or actions"))?;
+        self.state.card_form = Some(form);
+        Ok(())
+    }
+
+    /// Switches the selected input of the card form, or runs the selected action.
+    /// Submitting the card closes the form.
+    fn activate_card_field(&mut self) -> Result<()> {
+        let form = self
+            .state
+            .card_form
+            .as_mut()
+            .ok_or(eyre!("No card form is open"))?;
+        match form.selected_part().cloned() {
+            Some(CardPart::Input(_)) => form.cycle_selected(),
+            Some(CardPart::Action(CardAction::Submit { data, .. })) => {
+                let inputs = form.inputs(&data);
+                let message_id = form.message_id().clone();
+                self.state.card_form = None;
+                self.dispatch_to_teams(
+                    AppCmdEvent::SubmitCard(message_id, inputs),
+                    &Priority::High,
+                );
+            }
+            Some(CardPart::Action(CardAction::OpenUrl { url, .. })) => {
+                // The url comes from the sender of the card, only web links are opened
+                if !is_web_url(&url) {
+                    return Err(eyre!("Refusing to open {url}, it is not a web link"));
+                }
+                info!("Opening {url}");
+                open::that_detached(&url).map_err(|e| eyre!("Failed to open {}: {}", url, e))?;
+            }
+            Some(CardPart::Text(_)) | None => (),
+        }
+        Ok(())
+    }
+
+    /// Closes the message search and shows the selected message in its room.
+    fn open_selected_search_result(&mut self) -> Result<()> {
+        let result = self
+            .state
+            .message_search
+            .as_ref()
+            .and_then(|search| search.selected_result())
+            .cloned()
+            .ok_or(eyre!("No search result selected"))?;
+        self.state.close_message_search();
+        self.show_message(&result.room_id, &result.message_id)
+    }
+
+    /// Adds a bookmark to the selected message, or removes it if it has one.
+    fn toggle_bookmark_of_selected_message(&mut self) -> Result<()> {
+        let message = self.state.selected_message()?;
+        let (Some(room_id), Some(message_id)) = (message.room_id.clone(), message.id.clone())
+        else {
+            return Err(eyre!("The selected message has no id"));
+        };
+        let bookmark = Bookmark::new(
+            room_id,
+            message_id,
+            self.state.author_name(message),
+            &Self::extract_message_content(message).unwrap_or_default(),
+            message.created.clone(),
+        );
+        match self.state.bookmarks.toggle(bookmark) {
+            true => info!("Message bookmarked"),
+            false => info!("Bookmark removed"),
+        }
+        Ok(())
+    }
+
+    /// Closes the bookmarks and shows the message of the selected one.
+    fn open_selected_bookmark(&mut self) -> Result<()> {
+        let bookmark = self
+            .state
+            .bookmarks
+            .selected()
+            .cloned()
+            .ok_or(eyre!("No bookmark selected"))?;
+        self.state.show_bookmarks = false;
+        self.show_message(&bookmark.room_id, &bookmark.message_id)
+    }
+
+    /// Makes the room of a message active and selects the message.
+    /// If the message is not loaded yet, older messages are requested until it is.
+    fn show_message(&mut self, room_id: &RoomId, message_id: &MessageId) -> Result<()> {
+        self.show_room(room_id);
+        self.state.set_active_pane(Some(ActivePane::Messages));
+        if !self.state.cache.message_exists_in_room(message_id, room_id) {
+            info!("Loading older messages to find the message");
+            self.state.message_to_show = Some((room_id.clone(), message_id.clone()));
+            self.get_messages_before_first(room_id);
+            return Ok(());
+        }
+        self.select_message(room_id, message_id)
+    }
+
+    /// Selects a message of the active room, revealing it if it is in a hidden thread.
+    fn select_message(&mut self, room_id: &RoomId, message_id: &MessageId) -> Result<()> {
+        // The message may be hidden in a collapsed or unfocused thread
+        let message = self
+            .state
+            .cache
+            .messages_in_room(room_id)
+            .find(|msg| msg.id.as_ref() == Some(message_id))
+            .cloned()
+            .ok_or(eyre!("Message {} is not in cache", message_id))?;
+        self.state.messages_list.thread_view_mut().reveal(&message);
+        let index = self
+            .state
+            .index_of_displayed_message(message_id)
+            .ok_or(eyre!("Message {} is not displayed", message_id))?;
+        self.state
+            .messages_list
+            .set_nb_messages(self.state.num_messages_active_room());
+        self.state.messages_list.select_index(index);
+        Ok(())
+    }
+
+    /// Selects the message waiting to be shown once messages arrived in its room, or requests
+    /// older messages if it is still missing. Gives up at the beginning of the room, or if
+    /// another room was opened in the meantime.
+    fn continue_showing_message(&mut self, room_id: &RoomId, nb_received: usize) {
+        let Some((target_room_id, message_id)) = self.state.message_to_show.clone() else {
+            return;
+        };
+        if target_room_id != *room_id {
+            return;
+        }
+        if !self.state.is_active_room(room_id) {
+            self.state.message_to_show = None;
+        } else if self
+            .state
+            .cache
+            .message_exists_in_room(&message_id, room_id)
+        {
+            self.state.message_to_show = None;
+            if let Err(e) = self.select_message(room_id, &message_id) {
+                error!("Could not go to the message: {e}");
+            }
+        } else if nb_received == 0 {
+            self.state.message_to_show = None;
+            warn!("Message {message_id} was not found in the room");
+        } else {
+            self.get_messages_before_first(room_id);
+        }
+    }
+
+    /// Makes a room active, listing it if the filter or the search hides it.
+    fn show_room(&mut self, room_id: &RoomId) {
+        // Make sure the room is listed so that it can be selected
+        if !self.state.visible_rooms().any(|room| room.id == *room_id) {
+            self.state.rooms_list.clear_search();
+            self.state.rooms_list.set_filter(RoomsListFilter::All);
+        }
+        if !self.state.is_active_room(room_id) {
+            self.state.messages_list.thread_view_mut().reset_threads();
+        }
+        self.switch_draft(Some(room_id));
+        self.state
+            .rooms_list
+            .set_active_room_id(Some(room_id.clone()));
+        self.state.update_room_selection_with_active_room();
+        self.get_messages_if_room_empty(room_id);
+        self.acknowledge_active_room();
+        self.request_attachment_info_of_active_room();
+    }
+
     /// We could update the app or dispatch event on tick
     pub(crate) async fn update_on_tick(&mut self) -> AppReturn {
         self.state.update_on_tick();
+        self.send_due_scheduled_messages();
+        self.retry_outbox();
+        self.search_people_when_typing_stops();
         AppReturn::Continue
     }
 
+    /// Searches the directory for the query of the people picker, once it has not changed
+    /// for a while, so that a request is not sent for each key typed.
+    fn search_people_when_typing_stops(&mut self) {
+        let is_due = self
+            .state
+            .people_search
+            .as_ref()
+            .is_some_and(|(_, typed_at)| typed_at.elapsed() >= PEOPLE_SEARCH_DELAY);
+        if !is_due {
+            return;
+        }
+        if let Some((query, _)) = self.state.people_search.take() {
+            if self.state.picker_query() == Some(query.as_str()) {
+                self.dispatch_to_teams(AppCmdEvent::SearchPeople(query), &Priority::High);
+            }
+        }
+    }
+
     /// Send a message with the text contained in the editor
     /// to the active person or room.
     fn send_message_buffer(&mut self) -> Result<()> {
         if self.state.message_editor.is_empty() {
             return Err(eyre!("An empty message cannot be sent."));
         };
+        if let Some(path) = self.state.message_editor.upload_path() {
+            return self.upload_file(&path);
+        }
         let room = self
             .state
             .active_room()
             .ok_or(eyre!("Cannot send message, no room selected."))?;
-        let lines = self.state.message_editor.lines().to_vec();
-        if let Some(msg_to_edit) = self.state.message_editor.editing_of() {
-            // Editing a message
-            let msg_id = msg_to_edit
-                .id
-                .clone()
-                .ok_or(eyre!("Cannot edit message without id"))?;
-            let new_text = lines.join("\n");
+        let (room_id, room_title) = (room.id.clone(), room.title.clone());
+        let command = match self.state.message_editor.editing_of() {
+            Some(msg_to_edit) => {
+                // Editing a message, mentions are only understood by Webex in markdown
+                let message_id = msg_to_edit
+                    .id
+                    .clone()
+                    .ok_or(eyre!("Cannot edit message without id"))?;
+                OutboxCommand::Edit {
+                    message_id,
+                    room_id: room_id.clone(),
+                    content: self.state.message_editor.text_with_mentions(),
+                    is_markdown: self.state.message_editor.is_markdown()
+                        || self.state.message_editor.has_mentions(),
+                }
+            }
+            None => OutboxCommand::Send(self.message_out_from_editor(&room_id)),
+        };
+        self.deliver(command);
+        debug!("Sending message to room {room_title:?}");
+        self.state.cache.rooms.mark_read(&room_id);
+        self.state.message_editor.reset();
+        self.reply_in_focused_thread();
+        self.state.messages_list.deselect();
+        Ok(())
+    }
+
+    /// Returns the new message or reply in the editor, ready to be sent to the room.
+    fn message_out_from_editor(&self, room_id: &RoomId) -> MessageOut {
+        // Mentions are only understood by Webex in markdown
+        let content = self.state.message_editor.text_with_mentions();
+        let is_markdown =
+            self.state.message_editor.is_markdown() || self.state.message_editor.has_mentions();
+        let mut msg_to_send = match self.state.message_editor.response_to() {
+            Some(orig_msg) => {
+                // Replying to a message
+                orig_msg.reply()
+            }
+            None => MessageOut {
+                // Sending a new message
+                room_id: Some(room_id.clone()),
+                ..Default::default()
+            },
+        };
+        match is_markdown {
+            true => msg_to_send.markdown = Some(content),
+            false => msg_to_send.text = Some(content),
+        }
+        msg_to_send
+    }
+
+    /// Opens the prompt asking when to send the message in the editor.
+    fn open_send_time_prompt(&mut self) -> Result<()> {
+        let editor = &self.state.message_editor;
+        if editor.is_empty() {
+            return Err(eyre!("An empty message cannot be scheduled."));
+        }
+        if editor.editing_of().is_some() || editor.is_upload_command() {
+            return Err(eyre!("Only new messages and replies can be scheduled."));
+        }
+        self.state.picker = None;
+        self.state.send_time_prompt = Some(String::new());
+        Ok(())
+    }
+
+    // Handle a key while the send time prompt is open
+    fn process_send_time_key(&mut self, key_event: KeyEvent) -> AppReturn {
+        let key: Key = key_event.into();
+        let Some(input) = self.state.send_time_prompt.as_mut() else {
+            return AppReturn::Continue;
+        };
+        match key {
+            Key::Char(c) => input.push(c),
+            Key::Backspace => {
+                input.pop();
+            }
+            _ => return self.do_action(key),
+        }
+        AppReturn::Continue
+    }
+
+    /// Schedules the message in the editor at the time typed in the prompt.
+    fn schedule_message_buffer(&mut self) -> Result<()> {
+        let input = self.state.send_time_prompt.clone().unwrap_or_default();
+        let due = parse_send_time(&input, &Local::now())?;
+        let room_id = self
+            .state
+            .active_room()
+            .ok_or(eyre!("Cannot schedule message, no room selected."))?
+            .id
+            .clone();
+        let message = self.message_out_from_editor(&room_id);
+        let draft = self.state.message_editor.draft();
+        self.state
+            .schedule
+            .add(room_id, due.with_timezone(&Utc), message, draft);
+        info!("Message scheduled for {}", due.format("%a %d %b, %H:%M"));
+        self.state.send_time_prompt = None;
+        self.state.message_editor.reset();
+        self.reply_in_focused_thread();
+        self.state.message_editor.set_is_composing(false);
+        self.state.set_active_pane(Some(ActivePane::Messages));
+        Ok(())
+    }
+
+    /// Records a command in the outbox, then hands it to the `Teams` thread.
+    /// A message being sent is shown right away.
+    fn deliver(&mut self, command: OutboxCommand) {
+        let id = self.state.outbox.add(command.clone());
+        if let OutboxCommand::Send(msg_to_send) = &command {
+            self.state.add_local_message(id, msg_to_send);
+        }
+        self.dispatch_to_teams(AppCmdEvent::Deliver(id, command), &Priority::High);
+    }
+
+    /// Hands the commands of the outbox due for a retry to the `Teams` thread.
+    fn retry_outbox(&mut self) {
+        for (id, command) in self.state.outbox.take_due_retries(Utc::now()) {
+            debug!("Retrying: {}", command.description());
+            self.dispatch_to_teams(AppCmdEvent::Deliver(id, command), &Priority::High);
+        }
+    }
+
+    /// Resends the selected command of the outbox now.
+    fn resend_selected_outbox_entry(&mut self) -> Result<()> {
+        let id = self
+            .state
+            .outbox
+            .selected()
+            .map(|entry| entry.id)
+            .ok_or(eyre!("Nothing selected in the outbox"))?;
+        let command = self
+            .state
+            .outbox
+            .resend(id)
+            .ok_or(eyre!("It is already being sent"))?;
+        self.dispatch_to_teams(AppCmdEvent::Deliver(id, command), &Priority::High);
+        Ok(())
+    }
+
+    /// Removes the selected command from the outbox, it will not be delivered.
+    fn discard_selected_outbox_entry(&mut self) -> Result<()> {
+        let id = self
+            .state
+            .outbox
+            .selected()
+            .map(|entry| entry.id)
+            .ok_or(eyre!("Nothing selected in the outbox"))?;
+        let entry = self
+            .state
+            .outbox
+            .discard(id)
+            .ok_or(eyre!("It is being sent, it can be discarded if it fails"))?;
+        info!("Discarded: {}", entry.command.description());
+        self.state.remove_local_message(id, &entry.command);
+        Ok(())
+    }
+
+    /// Sends the scheduled messages that are due.
+    fn send_due_scheduled_messages(&mut self) {
+        for scheduled in self.state.schedule.take_due(Utc::now()) {
+            debug!("Sending message scheduled for {}", scheduled.due);
+            self.deliver(OutboxCommand::Send(scheduled.message));
+        }
+    }
+
+    /// Unschedules the selected scheduled message and opens it in the editor, in its room.
+    fn edit_selected_scheduled_message(&mut self) -> Result<()> {
+        let (id, room_id) = self
+            .state
+            .schedule
+            .selected()
+            .map(|scheduled| (scheduled.id, scheduled.room_id.clone()))
+            .ok_or(eyre!("No scheduled message selected"))?;
+        self.show_room(&room_id);
+        if !self.state.message_editor.draft().is_empty() {
+            return Err(eyre!(
+                "The room has a draft, send or clear it before editing the scheduled message"
+            ));
+        }
+        let scheduled = self
+            .state
+            .schedule
+            .remove(id)
+            .ok_or(eyre!("The message was sent in the meantime"))?;
+        self.state.message_editor.load_draft(scheduled.draft);
+        self.state.show_scheduled = false;
+        self.state.message_editor.set_is_composing(true);
+        self.state.set_active_pane(Some(ActivePane::Compose));
+        info!("The message is no longer scheduled, send it or schedule it again with Alt-s");
+        Ok(())
+    }
+
+    /// Cancels the selected scheduled message.
+    fn cancel_selected_scheduled_message(&mut self) -> Result<()> {
+        let id = self
+            .state
+            .schedule
+            .selected()
+            .map(|scheduled| scheduled.id)
+            .ok_or(eyre!("No scheduled message selected"))?;
+        self.state.schedule.remove(id);
+        info!("Scheduled message cancelled");
+        Ok(())
+    }
+
+    /// Uploads the file at `path` to the active room, as a reply if the editor is responding
+    /// to a message.
+    fn upload_file(&mut self, path: &str) -> Result<()> {
+        if self.state.message_editor.editing_of().is_some() {
+            return Err(eyre!("A file cannot be attached to an edited message."));
+        }
+        let room_id = self
+            .state
+            .active_room()
+            .ok_or(eyre!("Cannot upload file, no room selected."))?
+            .id
+            .clone();
+        let path = expand_tilde(path);
+        if !path.is_file() {
+            return Err(eyre!("Cannot upload {}, it is not a file.", path.display()));
+        }
+        let parent_id = self
+            .state
+            .message_editor
+            .response_to()
+            .and_then(|msg| msg.reply().parent_id);
+        debug!("Uploading {} to room {room_id}", path.display());
+        self.dispatch_to_teams(
+            AppCmdEvent::UploadFile(room_id.clone(), parent_id, path),
+            &Priority::High,
+        );
+        self.state.cache.rooms.mark_read(&room_id);
+        self.state.message_editor.reset();
+        self.reply_in_focused_thread();
+        self.state.messages_list.deselect();
+        Ok(())
+    }
+
+    /// Opens a picker with the attachments of the selected message.
+    fn show_attachments_of_selected_message(&mut self) -> Result<()> {
+        let files = self
+            .state
+            .selected_message()?
+            .files
+            .clone()
+            .unwrap_or_default();
+        if files.is_empty() {
+            return Err(eyre!("The selected message has no attachments"));
+        }
+        self.request_missing_attachment_info(&files);
+        let items = files
+            .iter()
+            .enumerate()
+            .map(|(index, url)| PickerItem::new(self.state.attachment_label(index, url), url))
+            .collect();
+        self.state.open_picker(Picker::new(
+            PickerKind::Attachments,
+            "Download attachment",
+            items,
+        ));
+        Ok(())
+    }
+
+    /// Opens a picker to choose the room to forward the selected message to.
+    fn open_forward_picker(&mut self) -> Result<()> {
+        let message = self.state.selected_message()?;
+        let (Some(room_id), Some(message_id)) = (message.room_id.clone(), message.id.clone())
+        else {
+            return Err(eyre!("The selected message has no id"));
+        };
+        let kind = PickerKind::Forward(room_id, message_id);
+        let items = self.state.forward_items("");
+        self.state
+            .open_picker(Picker::prefiltered(kind, "Forward to: ", items));
+        Ok(())
+    }
+
+    /// Posts the content and the attachments of a message to another room.
+    /// Each attachment is posted in a message of its own, as Webex allows only one per message.
+    fn forward_message(
+        &mut self,
+        room_id: &RoomId,
+        message_id: &MessageId,
+        to_room_id: &RoomId,
+    ) -> Result<()> {
+        let index = self
+            .state
+            .cache
+            .index_of_message_in_room(message_id, room_id)
+            .ok_or(eyre!("Message {message_id} not found"))?;
+        let message = self.state.cache.nth_message_in_room(index, room_id)?;
+        let header = match self.state.config.forward_header {
+            true => {
+                let room_title = self.state.cache.room_and_team_title(room_id)?.room_title;
+                Some(forward_header(
+                    &self.state.author_name(message),
+                    &room_title,
+                ))
+            }
+            false => None,
+        };
+        let msg_to_send = forwarded_message(message, header.as_deref(), to_room_id);
+        let files = message.files.clone().unwrap_or_default();
+        if msg_to_send.is_none() && files.is_empty() {
+            return Err(eyre!("The message has nothing to forward"));
+        }
+        if let Some(msg_to_send) = msg_to_send {
+            self.deliver(OutboxCommand::Send(msg_to_send));
+        }
+        for url in files {
+            self.deliver(OutboxCommand::ForwardAttachment {
+                url,
+                room_id: to_room_id.clone(),
+            });
+        }
+        info!("Forwarding message to {to_room_id}");
+        Ok(())
+    }
+
+    /// Opens a picker with the links of the selected message,
+    /// or of all the messages shown in the room if none is selected, most recent first.
+    fn show_links(&mut self) -> Result<()> {
+        let urls = match self.state.selected_message() {
+            Ok(message) => urls_in_message(message),
+            Err(_) => {
+                let messages: Vec<_> = self
+                    .state
+                    .displayed_messages_active_room()
+                    .map(|displayed| displayed.message)
+                    .collect();
+                messages.into_iter().rev().flat_map(urls_in_message).fold(
+                    Vec::new(),
+                    |mut urls, url| {
+                        if !urls.contains(&url) {
+                            urls.push(url);
+                        }
+                        urls
+                    },
+                )
+            }
+        };
+        if urls.is_empty() {
+            return Err(eyre!("No links found"));
+        }
+        let items = urls
+            .iter()
+            .enumerate()
+            .map(|(index, url)| PickerItem::new(format!("{}. {url}", index + 1), url))
+            .collect();
+        self.state
+            .open_picker(Picker::new(PickerKind::Links, "Open link (1-9)", items));
+        Ok(())
+    }
+
+    /// Acts on the item chosen in the picker, according to the picker kind.
+    fn confirm_picker_choice(&mut self) -> Result<()> {
+        let picker = self
+            .state
+            .close_picker()
+            .ok_or(eyre!("No picker is open"))?;
+        let item = picker.selected_item().ok_or(eyre!("No item selected"))?;
+        match picker.kind() {
+            PickerKind::Attachments => self.download_attachment(&item.value),
+            PickerKind::Mentions => self.insert_mention(&item.value.clone())?,
+            PickerKind::Forward(room_id, message_id) => {
+                self.forward_message(room_id, message_id, &item.value)?
+            }
+            PickerKind::Links => {
+                info!("Opening {}", item.value);
+                open::that_detached(&item.value)
+                    .map_err(|e| eyre!("Failed to open {}: {}", item.value, e))?;
+            }
+            PickerKind::People => {
+                info!("Opening the conversation with {}", item.label);
+                self.dispatch_to_teams(
+                    AppCmdEvent::OpenDirectRoom(item.value.clone()),
+                    &Priority::High,
+                );
+            }
+            PickerKind::NewMember(room_id) => {
+                info!("Adding {} to the room", item.value);
+                self.dispatch_to_teams(
+                    AppCmdEvent::AddMember(room_id.clone(), item.value.clone()),
+                    &Priority::High,
+                );
+            }
+            PickerKind::Teams(title) => {
+                let team_id = (!item.value.is_empty()).then(|| item.value.clone());
+                self.state
+                    .open_prompt(Prompt::new(PromptKind::SpaceMembers {
+                        title: title.clone(),
+                        team_id,
+                    }));
+            }
+        }
+        Ok(())
+    }
+
+    /// Shows the members of the active room, refreshed from the server.
+    fn show_members(&mut self) {
+        let Some(room_id) = self.state.rooms_list.active_room_id().cloned() else {
+            return;
+        };
+        self.state.show_members = true;
+        let nb_members = self.state.active_room_members().len();
+        self.state.members_list.select_first(nb_members);
+        self.state.cache.members.add_requested(&room_id);
+        self.dispatch_to_teams(AppCmdEvent::ListRoomMembers(room_id), &Priority::High);
+    }
+
+    /// Shows the details of the active room, requesting those not known yet.
+    fn show_room_details(&mut self) {
+        let Some(room) = self.state.active_room() else {
+            return;
+        };
+        let room_id = room.id.clone();
+        let creator_id = room.creator_id.clone();
+        self.state.show_room_details = true;
+        if !self.state.cache.meeting_infos.contains_key(&room_id) {
             self.dispatch_to_teams(
-                AppCmdEvent::EditMessage(msg_id, room.id.clone(), new_text),
+                AppCmdEvent::UpdateMeetingInfo(room_id.clone()),
                 &Priority::High,
             );
-        } else {
-            let msg_to_send = match self.state.message_editor.response_to() {
-                Some(orig_msg) => {
-                    // Replying to a message
-                    let mut reply = orig_msg.reply();
-                    reply.text = Some(lines.join("\n"));
-                    reply
-                }
-                None => webex::types::MessageOut {
-                    // Sending a new message
-                    room_id: Some(room.id.clone()),
-                    text: Some(lines.join("\n")),
+        }
+        if !self.state.cache.members.exists_or_requested(&room_id) {
+            self.state.cache.members.add_requested(&room_id);
+            self.dispatch_to_teams(AppCmdEvent::ListRoomMembers(room_id), &Priority::High);
+        }
+        if !creator_id.is_empty() && !self.state.cache.persons.exists_or_requested(&creator_id) {
+            self.state.cache.persons.add_requested(&creator_id);
+            self.dispatch_to_teams(AppCmdEvent::UpdatePerson(creator_id), &Priority::High);
+        }
+    }
+
+    /// Locks the active room if it is unlocked, unlocks it otherwise.
+    fn toggle_active_room_lock(&mut self) -> Result<()> {
+        let room = self.state.active_room().ok_or(eyre!("No room selected"))?;
+        let title = room.title.clone().ok_or(eyre!("The room has no title"))?;
+        match room.is_locked {
+            true => info!("Unlocking {title}"),
+            false => info!("Locking {title}"),
+        }
+        self.dispatch_to_teams(
+            AppCmdEvent::UpdateRoomSettings(room.id.clone(), title, !room.is_locked),
+            &Priority::High,
+        );
+        Ok(())
+    }
+
+    /// Opens a picker to choose a person to add to the active room, among the known persons
+    /// and those found on the server as the user types.
+    fn open_new_member_picker(&mut self) {
+        let Some(room_id) = self.state.rooms_list.active_room_id().cloned() else {
+            return;
+        };
+        let items = self.state.new_member_items("");
+        self.state.open_picker(Picker::prefiltered(
+            PickerKind::NewMember(room_id),
+            "Add member: ",
+            items,
+        ));
+    }
+
+    /// Removes the member selected in the members pane from the active room.
+    fn remove_selected_member(&mut self) -> Result<()> {
+        let room_id = self
+            .state
+            .rooms_list
+            .active_room_id()
+            .cloned()
+            .ok_or(eyre!("No room selected"))?;
+        let member = self
+            .state
+            .selected_member()
+            .ok_or(eyre!("No member selected"))?;
+        if self.state.cache.is_me(&Some(member.person_id.clone())) {
+            return Err(eyre!("Leave the room to remove yourself"));
+        }
+        info!("Removing {} from the room", self.state.member_name(member));
+        let membership_id = member.id.clone();
+        self.dispatch_to_teams(
+            AppCmdEvent::RemoveMember(room_id, membership_id),
+            &Priority::High,
+        );
+        Ok(())
+    }
+
+    /// Opens a picker to choose a person to chat with, among the known persons and those
+    /// found on the server as the user types.
+    fn open_people_picker(&mut self) {
+        let items = self.state.people_items("");
+        self.state.open_picker(Picker::prefiltered(
+            PickerKind::People,
+            "Chat with: ",
+            items,
+        ));
+    }
+
+    /// Acts on the text typed in the prompt, according to the prompt kind.
+    fn confirm_prompt(&mut self) -> Result<()> {
+        let prompt = self
+            .state
+            .close_prompt()
+            .ok_or(eyre!("No prompt is open"))?;
+        let input = prompt.input().trim().to_string();
+        match prompt.kind() {
+            PromptKind::FirstMessage { person_id, name } => {
+                if input.is_empty() {
+                    return Err(eyre!("An empty message cannot be sent."));
+                }
+                info!("Starting a conversation with {name}");
+                self.deliver(OutboxCommand::Send(MessageOut {
+                    to_person_id: Some(person_id.clone()),
+                    text: Some(input),
                     ..Default::default()
-                },
-            };
-            self.dispatch_to_teams(AppCmdEvent::SendMessage(msg_to_send), &Priority::High);
+                }));
+            }
+            PromptKind::SpaceTitle => {
+                if input.is_empty() {
+                    return Err(eyre!("A space needs a title."));
+                }
+                // The space can only be created in a team we know of
+                match self.state.cache.teams.teams().is_empty() {
+                    true => self
+                        .state
+                        .open_prompt(Prompt::new(PromptKind::SpaceMembers {
+                            title: input,
+                            team_id: None,
+                        })),
+                    false => {
+                        let items = self.state.team_items();
+                        let title = format!("Team of {input}");
+                        self.state
+                            .open_picker(Picker::new(PickerKind::Teams(input), title, items));
+                    }
+                }
+            }
+            PromptKind::SpaceMembers { title, team_id } => {
+                let emails = parse_emails(&input)?;
+                info!("Creating space {title}");
+                self.dispatch_to_teams(
+                    AppCmdEvent::CreateSpace(title.clone(), team_id.clone(), emails),
+                    &Priority::High,
+                );
+            }
+            PromptKind::RoomTitle { room_id } => {
+                if input.is_empty() {
+                    return Err(eyre!("A room needs a title."));
+                }
+                let is_locked = self
+                    .state
+                    .cache
+                    .rooms
+                    .room_with_id(room_id)
+                    .ok_or(eyre!("Room {} not found", room_id))?
+                    .is_locked;
+                info!("Renaming the room to {input}");
+                self.dispatch_to_teams(
+                    AppCmdEvent::UpdateRoomSettings(room_id.clone(), input, is_locked),
+                    &Priority::High,
+                );
+            }
+            PromptKind::SnoozeUntil { room_id } => {
+                let until = parse_send_time(&input, &Local::now())?;
+                info!("Room snoozed until {}", until.format("%a %H:%M"));
+                self.state
+                    .mute(room_id, Mute::Until(until.with_timezone(&Utc)));
+                self.state.update_room_selection_with_active_room();
+            }
         }
-        debug!("Sending message to room {:?}", room.title);
-        self.state.cache.rooms.mark_read(&room.id.clone());
-        self.state.message_editor.reset();
-        self.state.messages_list.deselect();
         Ok(())
     }
 
+    /// Returns the content to edit in an external editor: the message being composed,
+    /// and the message replied to or edited as context.
+    fn external_editor_content(&self) -> String {
+        let editor = &self.state.message_editor;
+        let context = match (editor.editing_of(), editor.response_to()) {
+            (Some(message), _) => Some(("Editing".to_string(), message)),
+            (None, Some(message)) => Some((
+                format!("Replying to {}", self.state.author_name(message)),
+                message,
+            )),
+            (None, None) => None,
+        }
+        .map(|(title, message)| {
+            let quoted = Self::extract_message_content(message).unwrap_or_default();
+            (title, quoted)
+        });
+        file_content(
+            &editor.lines().join("\n"),
+            context
+                .as_ref()
+                .map(|(title, quoted)| (title.as_str(), quoted.as_str())),
+        )
+    }
+
+    /// Loads the message written in the external editor back into the message editor.
+    pub(crate) fn load_externally_edited(&mut self, edited: Result<String>) {
+        match edited {
+            Ok(content) => {
+                let message = message_from_file_content(&content);
+                self.state.message_editor.set_text(&message);
+            }
+            Err(e) => error!("Could not edit the message externally: {e}"),
+        }
+    }
+
+    /// Tells the members of the active room that we are typing, if enabled in the config.
+    /// The notification is repeated while typing, but not for every key.
+    fn notify_typing(&mut self) {
+        if !self.state.config.send_typing || self.state.message_editor.is_upload_command() {
+            return;
+        }
+        let Some(room_id) = self.state.active_room().map(|room| room.id.clone()) else {
+            return;
+        };
+        let is_recent =
+            self.state.typing_notified.as_ref().is_some_and(|(id, at)| {
+                *id == room_id && at.elapsed() < TYPING_NOTIFICATION_INTERVAL
+            });
+        if !is_recent {
+            self.dispatch_to_teams(
+                AppCmdEvent::SetTyping(room_id.clone(), true),
+                &Priority::Low,
+            );
+            self.state.typing_notified = Some((room_id, Instant::now()));
+        }
+    }
+
+    /// Tells the members of the room that we stopped typing, if we told them we were.
+    fn notify_typing_stopped(&mut self) {
+        if let Some((room_id, _)) = self.state.typing_notified.take() {
+            self.dispatch_to_teams(AppCmdEvent::SetTyping(room_id, false), &Priority::Low);
+        }
+    }
+
+    /// Opens, updates or closes the mention picker according to the text before the cursor.
+    fn update_mention_picker(&mut self) {
+        let is_mention_picker_open = self
+            .state
+            .picker
+            .as_ref()
+            .is_some_and(|picker| picker.kind() == &PickerKind::Mentions);
+        let query = self.state.message_editor.mention_query();
+        let room_id = self.state.active_room().map(|room| room.id.clone());
+        let (Some(query), Some(room_id)) = (query, room_id) else {
+            if is_mention_picker_open {
+                self.state.close_picker();
+            }
+            return;
+        };
+        if !self.state.cache.members.exists_or_requested(&room_id) {
+            self.state.cache.members.add_requested(&room_id);
+            self.dispatch_to_teams(AppCmdEvent::ListRoomMembers(room_id), &Priority::High);
+        }
+        let title = format!("Mention @{query}");
+        match self.state.picker.as_mut() {
+            Some(picker) if is_mention_picker_open => {
+                picker.set_title(title);
+                picker.set_query(&query);
+            }
+            _ => {
+                let mut picker =
+                    Picker::new(PickerKind::Mentions, title, self.state.mention_items());
+                picker.set_query(&query);
+                self.state.open_picker(picker);
+            }
+        }
+    }
+
+    /// Inserts a mention of the member with the given id in the editor.
+    fn insert_mention(&mut self, person_id: &str) -> Result<()> {
+        let mention = match person_id {
+            MENTION_ALL => Mention {
+                name: String::from("all"),
+                person_id: None,
+            },
+            person_id => {
+                let room = self
+                    .state
+                    .active_room()
+                    .ok_or(eyre!("Cannot mention, no room selected."))?;
+                let member = self
+                    .state
+                    .cache
+                    .members
+                    .get(&room.id)
+                    .and_then(|members| members.iter().find(|m| m.person_id == person_id))
+                    .ok_or(eyre!("Cannot mention unknown member {person_id}"))?;
+                Mention {
+                    name: member.name(),
+                    person_id: Some(member.person_id.clone()),
+                }
+            }
+        };
+        self.state.message_editor.insert_mention(mention);
+        Ok(())
+    }
+
+    /// Downloads the attachment with given url to the configured download directory.
+    fn download_attachment(&self, url: &str) {
+        let dir = expand_tilde(&self.state.config.download_dir().to_string_lossy());
+        self.dispatch_to_teams(
+            AppCmdEvent::DownloadAttachment(url.to_string(), dir),
+            &Priority::Low,
+        );
+    }
+
     /// Deletes the selected message, if there is one and it was authored by self.
     /// Otherwise does nothing.
     fn delete_selected_message(&mut self) -> Result<()> {
@@ -475,7 +1881,10 @@ impl App<'_> {
 
         // Dispatch a delete event and remove the message from the store
         self.state.messages_list.select_previous_message();
-        self.dispatch_to_teams(AppCmdEvent::DeleteMessage(msg_id.clone()), &Priority::High);
+        self.deliver(OutboxCommand::Delete {
+            message_id: msg_id.clone(),
+            room_id: room_id.clone(),
+        });
         self.state.cache.delete_message(&msg_id, &room_id)?;
         Ok(())
     }
@@ -503,17 +1912,10 @@ impl App<'_> {
 
     /// Copies the selected message content to clipboard
     fn copy_selected_message(&mut self) -> Result<()> {
-        use arboard::Clipboard;
-
         let message = self.state.selected_message()?;
         let content = Self::extract_message_content(message)?;
 
-        let mut clipboard =
-            Clipboard::new().map_err(|e| eyre!("Failed to access clipboard: {}", e))?;
-        clipboard
-            .set_text(content)
-            .map_err(|e| eyre!("Failed to set clipboard content: {}", e))?;
-
+        copy_to_clipboard(&content)?;
         info!("Message copied to clipboard");
         Ok(())
     }
@@ -527,6 +1929,78 @@ impl App<'_> {
         Ok(())
     }
 
+    /// Makes the editor reply to the focused thread, if any, so that new messages stay in it.
+    fn reply_in_focused_thread(&mut self) {
+        let Some(thread_id) = self.state.messages_list.thread_view().focused().cloned() else {
+            return;
+        };
+        if self.state.message_editor.editing_of().is_some() {
+            return;
+        }
+        let first_message = self
+            .state
+            .displayed_messages_active_room()
+            .next()
+            .map(|displayed| displayed.message.clone());
+        match first_message {
+            Some(message) => self.state.message_editor.set_response_to(Some(message)),
+            None => warn!("Focused thread {thread_id} has no message to reply to"),
+        }
+    }
+
+    /// Shows only the thread of the selected message, or all threads if one is focused.
+    fn toggle_focus_on_selected_thread(&mut self) -> Result<()> {
+        let thread_id = match self.state.messages_list.thread_view().focused() {
+            Some(_) => None,
+            None => {
+                let message = self.state.selected_message()?;
+                Some(
+                    thread_id_of(message)
+                        .cloned()
+                        .ok_or(eyre!("The selected message has no id"))?,
+                )
+            }
+        };
+        self.state
+            .messages_list
+            .thread_view_mut()
+            .set_focused(thread_id);
+        self.update_messages_after_view_change();
+        Ok(())
+    }
+
+    /// Expands the thread of the selected message if it is collapsed, or collapses it.
+    fn toggle_selected_thread_expanded(&mut self) -> Result<()> {
+        let thread_id = thread_id_of(self.state.selected_message()?)
+            .cloned()
+            .ok_or(eyre!("The selected message has no id"))?;
+        self.state
+            .messages_list
+            .thread_view_mut()
+            .toggle_expanded(&thread_id);
+        self.update_messages_after_view_change();
+        Ok(())
+    }
+
+    /// Keeps the selected message selected when the thread view changed,
+    /// or the first message of its thread if it is now hidden.
+    fn update_messages_after_view_change(&mut self) {
+        let selected = self.state.selected_message().ok().cloned();
+        self.state
+            .messages_list
+            .set_nb_messages(self.state.num_messages_active_room());
+        let index = selected.and_then(|msg| {
+            let msg_id = msg.id.as_ref()?;
+            self.state.index_of_displayed_message(msg_id).or_else(|| {
+                thread_id_of(&msg).and_then(|id| self.state.index_of_displayed_message(id))
+            })
+        });
+        match index {
+            Some(index) => self.state.messages_list.select_index(index),
+            None => self.state.messages_list.deselect(),
+        }
+    }
+
     /// Prepeares the message editor with the contents of the selected message
     fn edit_selected_message(&mut self) -> Result<()> {
         let message = self.state.selected_message()?.clone();
@@ -535,13 +2009,8 @@ impl App<'_> {
             return Err(eyre!("Cannot edit message, it was not authored by self"));
         }
 
-        // set the message editor text to the message text
-        let text = message.text.clone().unwrap_or_default();
-        self.state.message_editor.reset_with_text(text);
-        // keep a copy of the message we are editing
-        self.state
-            .message_editor
-            .set_editing_of(Some(message.clone()));
+        // the markdown source is edited if there is one, so that the formatting is preserved
+        self.state.message_editor.edit_message(message);
         Ok(())
     }
 
@@ -555,9 +2024,38 @@ impl App<'_> {
         }
     }
 
+    /// Marks the active room read, and acknowledges its latest message to the server so that
+    /// the room is also read in our other clients.
+    pub(crate) fn acknowledge_active_room(&mut self) {
+        let Some(room_id) = self.state.rooms_list.active_room_id().cloned() else {
+            return;
+        };
+        self.state.cache.rooms.mark_read(&room_id);
+        let latest_message_id = self
+            .state
+            .cache
+            .messages_in_room(&room_id)
+            .filter(|msg| !is_local_message(msg))
+            .max_by(|a, b| a.created.cmp(&b.created))
+            .and_then(|msg| msg.id.clone());
+        if let Some(message_id) = latest_message_id {
+            if self.state.cache.rooms.mark_seen(&room_id, &message_id) {
+                self.dispatch_to_teams(
+                    AppCmdEvent::AcknowledgeMessage(room_id, message_id),
+                    &Priority::Low,
+                );
+            }
+        }
+    }
+
     /// Retrieves messages before the first message in the room
     fn get_messages_before_first(&mut self, id: &RoomId) {
-        if let Some(first_message) = self.state.cache.messages_in_room(id).next() {
+        if let Some(first_message) = self
+            .state
+            .cache
+            .messages_in_room(id)
+            .find(|msg| !is_local_message(msg))
+        {
             self.dispatch_to_teams(
                 AppCmdEvent::ListMessagesInRoom(
                     id.clone(),
@@ -584,12 +2082,19 @@ impl App<'_> {
     /// Sets the active room to that highlighted by the list selection
     fn set_active_room_to_selection(&mut self) {
         let id_option = self.state.id_of_selected_room();
+        if self.state.rooms_list.active_room_id() != id_option.as_ref() {
+            // Expanded and focused threads are those of the previous room
+            self.state.messages_list.thread_view_mut().reset_threads();
+        }
+        self.switch_draft(id_option.as_ref());
         self.state.rooms_list.set_active_room_id(id_option.clone());
         // Changing active room may have affected the selection
         // e.g. with Unread filter which includes active room
         self.state.update_room_selection_with_active_room();
         if let Some(id) = id_option {
             self.get_messages_if_room_empty(&id);
+            self.acknowledge_active_room();
+            self.request_attachment_info_of_active_room();
         }
         // Update the number of messages in the active room
         self.state
@@ -599,17 +2104,46 @@ impl App<'_> {
         self.state.messages_list.deselect();
     }
 
+    /// Keeps the message being written as a draft of its room and restores the draft of
+    /// the room becoming active, if the room changes.
+    fn switch_draft(&mut self, room_id: Option<&RoomId>) {
+        if self.state.drafts.editor_room_id() == room_id {
+            return;
+        }
+        let editor_content = self.state.message_editor.draft();
+        let draft = self.state.drafts.switch(editor_content, room_id);
+        self.state.message_editor.load_draft(draft);
+        self.state.drafts.save();
+    }
+
+    /// Saves the drafts of all rooms, including the message being written.
+    pub(crate) fn save_drafts(&mut self) {
+        let editor_content = self.state.message_editor.draft();
+        self.state.drafts.keep_editor_content(editor_content);
+        self.state.drafts.save();
+    }
+
     /// Change the rooms list filter to the previous one
     fn previous_filtering_mode(&mut self) {
         self.state.rooms_list.set_active_room_id(None);
-        self.state.rooms_list.previous_filter(&self.state.cache);
+        self.state
+            .rooms_list
+            .previous_filter(&self.state.config.room_filters);
+        // Reset selection when we change filter
+        let num_rooms = self.state.num_of_visible_rooms();
+        self.state.rooms_list.select_first_room(num_rooms);
         self.set_active_room_to_selection();
     }
 
     /// Change the rooms list filter to the next one
     fn next_filtering_mode(&mut self) {
         self.state.rooms_list.set_active_room_id(None);
-        self.state.rooms_list.next_filter(&self.state.cache);
+        self.state
+            .rooms_list
+            .next_filter(&self.state.config.room_filters);
+        // Reset selection when we change filter
+        let num_rooms = self.state.num_of_visible_rooms();
+        self.state.rooms_list.select_first_room(num_rooms);
         self.set_active_room_to_selection();
     }
 
@@ -686,6 +2220,14 @@ impl App<'_> {
     }
 }
 
+/// Copies text to the system clipboard.
+fn copy_to_clipboard(text: &str) -> Result<()> {
+    let mut clipboard = Clipboard::new().map_err(|e| eyre!("Failed to access clipboard: {}", e))?;
+    clipboard
+        .set_text(text)
+        .map_err(|e| eyre!("Failed to set clipboard content: {}", e))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,192 @@
+// app/mutes.rs
+
+//! Rooms muted or snoozed by the user, kept across restarts.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use super::cache::room::RoomId;
+use super::persistence::Persisted;
+
+/// Name of the file where mutes are saved, in the cache directory.
+const MUTES_FILE: &str = "mutes.json";
+
+/// How long a room is muted: new messages do not mark it unread in the meantime.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub(crate) enum Mute {
+    /// Until the user unmutes the room
+    Always,
+    /// Snoozed until the given time
+    Until(DateTime<Utc>),
+}
+
+impl Mute {
+    /// Returns true if the mute is still in effect at `now`.
+    fn is_active(&self, now: DateTime<Utc>) -> bool {
+        match self {
+            Mute::Always => true,
+            Mute::Until(until) => *until > now,
+        }
+    }
+}
+
+/// Muted and snoozed rooms.
+#[derive(Debug, Default)]
+pub(crate) struct Mutes {
+    mutes: Persisted<HashMap<RoomId, Mute>>,
+    /// Snoozed rooms with activity since they were snoozed, unread when the snooze ends.
+    /// Not saved, the read status of the rooms is given by the server after a restart.
+    missed: HashSet<RoomId>,
+}
+
+impl Mutes {
+    pub(crate) fn load() -> Self {
+        Self::new(Persisted::load(MUTES_FILE))
+    }
+
+    /// Restores the saved mutes, without the snoozes that ended while the app was closed.
+    fn new(mut mutes: Persisted<HashMap<RoomId, Mute>>) -> Self {
+        let now = Utc::now();
+        mutes.retain(|_, mute| mute.is_active(now));
+        Self {
+            mutes,
+            missed: HashSet::new(),
+        }
+    }
+
+    /// Returns the mute of the room, if it is in effect at `now`.
+    pub(crate) fn get(&self, room_id: &RoomId, now: DateTime<Utc>) -> Option<Mute> {
+        self.mutes
+            .get(room_id)
+            .filter(|mute| mute.is_active(now))
+            .copied()
+    }
+
+    /// Mutes the room, replacing any previous mute.
+    pub(crate) fn mute(&mut self, room_id: &RoomId, mute: Mute) {
+        self.mutes.insert(room_id.clone(), mute);
+        self.missed.remove(room_id);
+        self.mutes.save();
+    }
+
+    /// Unmutes the room. Returns true if it was muted.
+    pub(crate) fn unmute(&mut self, room_id: &RoomId) -> bool {
+        let was_muted = self.mutes.remove(room_id).is_some();
+        self.missed.remove(room_id);
+        self.mutes.save();
+        was_muted
+    }
+
+    /// Records activity in the room if it is snoozed at `now`, so that it is unread when the
+    /// snooze ends. Activity in rooms muted for good is not recorded.
+    pub(crate) fn record_missed(&mut self, room_id: &RoomId, now: DateTime<Utc>) {
+        if let Some(Mute::Until(_)) = self.get(room_id, now) {
+            self.missed.insert(room_id.clone());
+        }
+    }
+
+    /// Returns true if there was activity in the room while it was snoozed.
+    pub(crate) fn has_missed(&self, room_id: &RoomId) -> bool {
+        self.missed.contains(room_id)
+    }
+
+    /// Removes the snoozes that ended at `now`. Returns the rooms that were snoozed, and
+    /// whether there was activity in each while it was.
+    pub(crate) fn take_ended_snoozes(&mut self, now: DateTime<Utc>) -> Vec<(RoomId, bool)> {
+        let ended: Vec<RoomId> = self
+            .mutes
+            .iter()
+            .filter(|(_, mute)| !mute.is_active(now))
+            .map(|(room_id, _)| room_id.clone())
+            .collect();
+        if ended.is_empty() {
+            return vec![];
+        }
+        for room_id in &ended {
+            self.mutes.remove(room_id);
+        }
+        self.mutes.save();
+        ended
+            .into_iter()
+            .map(|room_id| {
+                let missed = self.missed.remove(&room_id);
+                (room_id, missed)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_mute_and_unmute() {
+        let mut mutes = Mutes::default();
+        let room_id = "room".to_string();
+        let now = Utc::now();
+        assert_eq!(mutes.get(&room_id, now), None);
+        mutes.mute(&room_id, Mute::Always);
+        assert_eq!(mutes.get(&room_id, now), Some(Mute::Always));
+        assert!(mutes.unmute(&room_id));
+        assert!(!mutes.unmute(&room_id));
+        assert_eq!(mutes.get(&room_id, now), None);
+    }
+
+    #[test]
+    fn test_snooze_ends() {
+        let mut mutes = Mutes::default();
+        let room_id = "room".to_string();
+        let now = Utc::now();
+        let until = now + Duration::hours(1);
+        mutes.mute(&room_id, Mute::Until(until));
+        assert_eq!(mutes.get(&room_id, now), Some(Mute::Until(until)));
+        assert_eq!(mutes.get(&room_id, until), None);
+    }
+
+    #[test]
+    fn test_activity_missed_during_snooze() {
+        let mut mutes = Mutes::default();
+        let (muted, snoozed, quiet) = (
+            "muted".to_string(),
+            "snoozed".to_string(),
+            "quiet".to_string(),
+        );
+        let now = Utc::now();
+        let until = now + Duration::hours(1);
+        mutes.mute(&muted, Mute::Always);
+        mutes.mute(&snoozed, Mute::Until(until));
+        mutes.mute(&quiet, Mute::Until(until));
+        mutes.record_missed(&muted, now);
+        mutes.record_missed(&snoozed, now);
+        assert!(!mutes.has_missed(&muted));
+        assert!(mutes.has_missed(&snoozed));
+        assert!(mutes.take_ended_snoozes(now).is_empty());
+        let mut ended = mutes.take_ended_snoozes(until);
+        ended.sort();
+        assert_eq!(ended, vec![(quiet, false), (snoozed.clone(), true)]);
+        assert!(!mutes.has_missed(&snoozed));
+        assert_eq!(mutes.get(&muted, until), Some(Mute::Always));
+    }
+
+    #[test]
+    fn test_mutes_are_kept_across_restarts_until_snoozes_end() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(MUTES_FILE);
+        let mut mutes = Mutes::new(Persisted::load_from(path.clone()));
+        let now = Utc::now();
+        mutes.mute(&"muted".to_string(), Mute::Always);
+        mutes.mute(
+            &"snoozed".to_string(),
+            Mute::Until(now + Duration::hours(1)),
+        );
+        mutes.mute(&"woken".to_string(), Mute::Until(now - Duration::hours(1)));
+        let reloaded = Mutes::new(Persisted::load_from(path));
+        assert_eq!(reloaded.mutes.len(), 2);
+        assert_eq!(reloaded.get(&"muted".to_string(), now), Some(Mute::Always));
+        assert!(reloaded.get(&"snoozed".to_string(), now).is_some());
+    }
+}
@@ -0,0 +1,402 @@
+// app/outbox.rs
+
+//! Messages, edits and deletions on their way to Webex, kept across restarts and retried
+//! when the network or the server fails.
+
+use chrono::{DateTime, Duration, Utc};
+use ratatui::widgets::TableState;
+use serde::{Deserialize, Serialize};
+use webex::types::MessageOut;
+
+use super::cache::attachments::AttachmentUrl;
+use super::cache::room::RoomId;
+use super::cache::MessageId;
+use super::persistence::Persisted;
+
+/// Name of the file where the outbox is saved, in the cache directory.
+const OUTBOX_FILE: &str = "outbox.json";
+
+/// Number of attempts after which a command is left to the user to resend or discard.
+const MAX_ATTEMPTS: u32 = 8;
+
+/// Delay before the first retry, doubled after each failure.
+const FIRST_RETRY_DELAY_SECS: i64 = 5;
+
+/// Longest delay between two retries.
+const MAX_RETRY_DELAY_SECS: i64 = 300;
+
+pub(crate) type OutboxId = u64;
+
+/// A change to make on Webex.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub(crate) enum OutboxCommand {
+    Send(MessageOut),
+    Edit {
+        message_id: MessageId,
+        room_id: RoomId,
+        content: String,
+        is_markdown: bool,
+    },
+    Delete {
+        message_id: MessageId,
+        room_id: RoomId,
+    },
+    /// Post an attached file of another message to a room
+    ForwardAttachment {
+        url: AttachmentUrl,
+        room_id: RoomId,
+    },
+}
+
+impl OutboxCommand {
+    /// Returns the room the command applies to.
+    pub(crate) fn room_id(&self) -> Option<&RoomId> {
+        match self {
+            OutboxCommand::Send(message) => message.room_id.as_ref(),
+            OutboxCommand::Edit { room_id, .. }
+            | OutboxCommand::Delete { room_id, .. }
+            | OutboxCommand::ForwardAttachment { room_id, .. } => Some(room_id),
+        }
+    }
+
+    /// Returns true if the command posts something new. Once the request is sent, it may be
+    /// posted even if no answer comes back, so sending it again could post it twice.
+    pub(crate) fn posts_new_content(&self) -> bool {
+        matches!(
+            self,
+            OutboxCommand::Send(_) | OutboxCommand::ForwardAttachment { .. }
+        )
+    }
+
+    /// Returns a short description of the command for the user.
+    pub(crate) fn description(&self) -> String {
+        match self {
+            OutboxCommand::Send(message) => {
+                let content = message.markdown.as_ref().or(message.text.as_ref());
+                format!("Send: {}", content.cloned().unwrap_or_default())
+            }
+            OutboxCommand::Edit { content, .. } => format!("Edit: {content}"),
+            OutboxCommand::Delete { .. } => String::from("Delete message"),
+            OutboxCommand::ForwardAttachment { .. } => String::from("Forward attachment"),
+        }
+    }
+}
+
+/// Where a command is in its delivery.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub(crate) enum DeliveryStatus {
+    /// Handed to the `Teams` thread, waiting for the result
+    InFlight,
+    /// Failed with a transient error, retried at the given time
+    Waiting { retry_at: DateTime<Utc> },
+    /// Given up on, the user can resend or discard it
+    Failed,
+}
+
+/// A command in the outbox.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub(crate) struct OutboxEntry {
+    pub(crate) id: OutboxId,
+    pub(crate) command: OutboxCommand,
+    pub(crate) status: DeliveryStatus,
+    pub(crate) attempts: u32,
+    pub(crate) last_error: Option<String>,
+}
+
+/// The outbox as saved on disk.
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct SavedOutbox {
+    /// Id of the next command. Ids are never reused, so that the result of a delivery
+    /// cannot be taken for that of a newer command.
+    next_id: OutboxId,
+    entries: Vec<OutboxEntry>,
+}
+
+/// Commands not yet delivered, oldest first, and the selection in the list shown to the user.
+#[derive(Debug, Default)]
+pub(crate) struct Outbox {
+    saved: Persisted<SavedOutbox>,
+    table_state: TableState,
+}
+
+impl Outbox {
+    pub(crate) fn load() -> Self {
+        Self::new(Persisted::load(OUTBOX_FILE))
+    }
+
+    /// Restores the saved outbox. Commands in flight when the app was closed are retried
+    /// right away, unless they may have been posted already: the user decides.
+    fn new(mut saved: Persisted<SavedOutbox>) -> Self {
+        saved.next_id = saved
+            .entries
+            .iter()
+            .map(|e| e.id + 1)
+            .fold(saved.next_id, OutboxId::max);
+        for entry in saved.entries.iter_mut() {
+            if entry.status != DeliveryStatus::InFlight {
+                continue;
+            }
+            match entry.command.posts_new_content() {
+                true => {
+                    entry.status = DeliveryStatus::Failed;
+                    entry.last_error = Some(String::from(
+                        "Interrupted by the app closing, it may have been sent",
+                    ));
+                }
+                false => {
+                    entry.status = DeliveryStatus::Waiting {
+                        retry_at: Utc::now(),
+                    }
+                }
+            }
+        }
+        Self {
+            saved,
+            table_state: TableState::default(),
+        }
+    }
+
+    /// Returns the entries, oldest first.
+    pub(crate) fn entries(&self) -> &[OutboxEntry] {
+        &self.saved.entries
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.saved.entries.is_empty()
+    }
+
+    /// Returns the entries applying to a room, oldest first.
+    pub(crate) fn entries_in_room<'a>(
+        &'a self,
+        room_id: &'a RoomId,
+    ) -> impl Iterator<Item = &'a OutboxEntry> {
+        self.saved
+            .entries
+            .iter()
+            .filter(move |entry| entry.command.room_id() == Some(room_id))
+    }
+
+    /// Records a command about to be dispatched, returns its id.
+    pub(crate) fn add(&mut self, command: OutboxCommand) -> OutboxId {
+        let id = self.saved.next_id;
+        self.saved.next_id += 1;
+        self.saved.entries.push(OutboxEntry {
+            id,
+            command,
+            status: DeliveryStatus::InFlight,
+            attempts: 1,
+            last_error: None,
+        });
+        self.saved.save();
+        id
+    }
+
+    /// Removes a delivered command.
+    pub(crate) fn delivered(&mut self, id: OutboxId) {
+        self.saved.entries.retain(|entry| entry.id != id);
+        self.clamp_selection();
+        self.saved.save();
+    }
+
+    /// Records a failed delivery. Transient errors are retried later, with a delay doubling
+    /// after each attempt, until the command is given up on.
+    pub(crate) fn failed(&mut self, id: OutboxId, error: String, retry: bool, now: DateTime<Utc>) {
+        let Some(entry) = self.saved.entries.iter_mut().find(|entry| entry.id == id) else {
+            return;
+        };
+        entry.status = match retry && entry.attempts < MAX_ATTEMPTS {
+            true => DeliveryStatus::Waiting {
+                retry_at: now + retry_delay(entry.attempts),
+            },
+            false => DeliveryStatus::Failed,
+        };
+        entry.last_error = Some(error);
+        self.saved.save();
+    }
+
+    /// Returns the commands due for a retry, which are now in flight.
+    pub(crate) fn take_due_retries(
+        &mut self,
+        now: DateTime<Utc>,
+    ) -> Vec<(OutboxId, OutboxCommand)> {
+        let mut due = vec![];
+        for entry in self.saved.entries.iter_mut() {
+            if let DeliveryStatus::Waiting { retry_at } = entry.status {
+                if retry_at <= now {
+                    entry.status = DeliveryStatus::InFlight;
+                    entry.attempts += 1;
+                    due.push((entry.id, entry.command.clone()));
+                }
+            }
+        }
+        if !due.is_empty() {
+            self.saved.save();
+        }
+        due
+    }
+
+    /// Returns the command to resend now, unless it is already in flight.
+    /// The attempts are counted again from the start.
+    pub(crate) fn resend(&mut self, id: OutboxId) -> Option<OutboxCommand> {
+        let entry = self
+            .saved
+            .entries
+            .iter_mut()
+            .find(|entry| entry.id == id && entry.status != DeliveryStatus::InFlight)?;
+        entry.status = DeliveryStatus::InFlight;
+        entry.attempts = 1;
+        let command = entry.command.clone();
+        self.saved.save();
+        Some(command)
+    }
+
+    /// Removes a command that will not be delivered, unless it is in flight: the request
+    /// cannot be stopped once sent.
+    pub(crate) fn discard(&mut self, id: OutboxId) -> Option<OutboxEntry> {
+        let index = self
+            .saved
+            .entries
+            .iter()
+            .position(|entry| entry.id == id && entry.status != DeliveryStatus::InFlight)?;
+        let entry = self.saved.entries.remove(index);
+        self.clamp_selection();
+        self.saved.save();
+        Some(entry)
+    }
+
+    /// Returns the entry selected in the list.
+    pub(crate) fn selected(&self) -> Option<&OutboxEntry> {
+        self.table_state
+            .selected()
+            .and_then(|index| self.saved.entries.get(index))
+    }
+
+    /// Selects the first entry, if any.
+    pub(crate) fn select_first(&mut self) {
+        let first = (!self.saved.entries.is_empty()).then_some(0);
+        self.table_state.select(first);
+    }
+
+    /// Selects the next entry, wrapping around at the end of the list.
+    pub(crate) fn select_next(&mut self) {
+        let next = match self.table_state.selected() {
+            _ if self.saved.entries.is_empty() => None,
+            Some(selected) if selected + 1 < self.saved.entries.len() => Some(selected + 1),
+            _ => Some(0),
+        };
+        self.table_state.select(next);
+    }
+
+    /// Selects the previous entry, wrapping around at the start of the list.
+    pub(crate) fn select_previous(&mut self) {
+        let previous = match self.table_state.selected() {
+            _ if self.saved.entries.is_empty() => None,
+            Some(selected) if selected > 0 => Some(selected - 1),
+            _ => Some(self.saved.entries.len() - 1),
+        };
+        self.table_state.select(previous);
+    }
+
+    /// Keeps the selection within the list after entries are removed.
+    fn clamp_selection(&mut self) {
+        if let Some(selected) = self.table_state.selected() {
+            let last = self.saved.entries.len().checked_sub(1);
+            self.table_state.select(last.map(|last| selected.min(last)));
+        }
+    }
+
+    pub(crate) fn table_state_mut(&mut self) -> &mut TableState {
+        &mut self.table_state
+    }
+}
+
+/// Returns the delay before retrying a command that failed `attempts` times.
+fn retry_delay(attempts: u32) -> Duration {
+    let secs = FIRST_RETRY_DELAY_SECS.saturating_mul(1 << attempts.saturating_sub(1).min(16));
+    Duration::seconds(secs.min(MAX_RETRY_DELAY_SECS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn send(text: &str) -> OutboxCommand {
+        OutboxCommand::Send(MessageOut {
+            room_id: Some("room".to_string()),
+            text: Some(text.to_string()),
+            ..Default::default()
+        })
+    }
+
+    #[rstest(attempts, secs, case(1, 5), case(2, 10), case(4, 40), case(7, 300))]
+    fn test_retry_delay(attempts: u32, secs: i64) {
+        assert_eq!(retry_delay(attempts), Duration::seconds(secs));
+    }
+
+    #[test]
+    fn test_retry_until_failed() {
+        let now = Utc::now();
+        let mut outbox = Outbox::default();
+        let id = outbox.add(send("hello"));
+        outbox.failed(id, "503".to_string(), true, now);
+        assert!(outbox.take_due_retries(now).is_empty());
+        let later = now + Duration::seconds(FIRST_RETRY_DELAY_SECS);
+        assert_eq!(outbox.take_due_retries(later), vec![(id, send("hello"))]);
+        assert_eq!(outbox.entries()[0].attempts, 2);
+        // Errors that will not go away are not retried
+        outbox.failed(id, "400".to_string(), false, later);
+        assert_eq!(outbox.entries()[0].status, DeliveryStatus::Failed);
+        assert!(outbox
+            .take_due_retries(later + Duration::days(1))
+            .is_empty());
+        // Unless the user asks for it
+        assert_eq!(outbox.resend(id), Some(send("hello")));
+        assert_eq!(outbox.resend(id), None);
+        outbox.delivered(id);
+        assert!(outbox.is_empty());
+    }
+
+    #[test]
+    fn test_in_flight_after_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(OUTBOX_FILE);
+        let mut outbox = Outbox::new(Persisted::load_from(path.clone()));
+        let delete = OutboxCommand::Delete {
+            message_id: "msg".to_string(),
+            room_id: "room".to_string(),
+        };
+        let sent = outbox.add(send("in flight"));
+        let deleted = outbox.add(delete.clone());
+        let failed = outbox.add(delete.clone());
+        outbox.failed(failed, "404".to_string(), false, Utc::now());
+
+        let mut loaded = Outbox::new(Persisted::load_from(path));
+        assert_eq!(loaded.entries_in_room(&"room".to_string()).count(), 3);
+        // Only the commands that cannot post twice are retried
+        assert_eq!(loaded.take_due_retries(Utc::now()), vec![(deleted, delete)]);
+        assert_eq!(loaded.entries()[0].status, DeliveryStatus::Failed);
+        assert!(loaded.entries()[0].last_error.is_some());
+        assert!(loaded.discard(deleted).is_none());
+        assert!(loaded.discard(sent).is_some());
+        assert!(loaded.discard(failed).is_some());
+        assert_eq!(loaded.entries().len(), 1);
+    }
+
+    #[test]
+    fn test_ids_are_not_reused() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(OUTBOX_FILE);
+        let mut outbox = Outbox::new(Persisted::load_from(path.clone()));
+        let first = outbox.add(send("first"));
+        let second = outbox.add(send("second"));
+        outbox.delivered(second);
+        let third = outbox.add(send("third"));
+        assert!(third > second);
+        outbox.delivered(first);
+        outbox.delivered(third);
+
+        let mut loaded = Outbox::new(Persisted::load_from(path));
+        assert!(loaded.add(send("fourth")) > third);
+    }
+}
@@ -0,0 +1,160 @@
+// app/persistence.rs
+
+//! State kept between sessions as JSON files in the cache directory.
+
+use color_eyre::eyre::{eyre, Result};
+use log::*;
+use serde::{de::DeserializeOwned, Serialize};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Write};
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+
+/// A value kept in a file of the cache directory, such as the drafts.
+///
+/// It dereferences to the value. Changes are written to the file by `save`.
+#[derive(Debug, Default)]
+pub(crate) struct Persisted<T> {
+    value: T,
+    /// Where the value is saved, `None` without a cache directory
+    path: Option<PathBuf>,
+}
+
+impl<T: Default + DeserializeOwned + Serialize> Persisted<T> {
+    /// Loads the value saved in the cache directory as `file_name`, it is saved back to it.
+    /// The value is the default one if nothing could be loaded.
+    pub(crate) fn load(file_name: &str) -> Self {
+        match cache_file_path(file_name) {
+            Ok(path) => Self::load_from(path),
+            Err(e) => {
+                warn!("{file_name} will not be saved: {e}");
+                Self::default()
+            }
+        }
+    }
+
+    /// Loads the value saved at `path`, it is saved back to it.
+    pub(crate) fn load_from(path: PathBuf) -> Self {
+        let value = load(&path)
+            .unwrap_or_else(|e| {
+                warn!("Could not load {}: {e}", path.display());
+                None
+            })
+            .unwrap_or_default();
+        Self {
+            value,
+            path: Some(path),
+        }
+    }
+
+    /// Saves the value to its file, if it has one.
+    pub(crate) fn save(&self) {
+        if let Some(path) = &self.path {
+            if let Err(e) = save(path, &self.value) {
+                warn!("Could not save {}: {e}", path.display());
+            }
+        }
+    }
+}
+
+impl<T> Deref for Persisted<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for Persisted<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+/// Returns the path of a file in the cache directory of the application.
+/// The directory is created if needed.
+pub(crate) fn cache_file_path(file_name: &str) -> Result<PathBuf> {
+    let cache_dir = dirs::cache_dir()
+        .or_else(|| dirs::home_dir().map(|home| home.join(".cache")))
+        .ok_or_else(|| eyre!("Could not determine cache directory"))?;
+    let app_cache_dir = cache_dir.join("webex-tui");
+    fs::create_dir_all(&app_cache_dir)?;
+    Ok(app_cache_dir.join(file_name))
+}
+
+/// Loads a value from a JSON file, or returns `None` if the file does not exist.
+pub(crate) fn load<T: DeserializeOwned>(path: &Path) -> Result<Option<T>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    debug!("Loading {path:?}");
+    let reader = BufReader::new(File::open(path)?);
+    let value = serde_json::from_reader(reader)
+        .map_err(|e| eyre!("Failed to parse {}: {e}", path.display()))?;
+    Ok(Some(value))
+}
+
+/// Saves a value to a JSON file, readable only by the user as it may contain messages.
+///
+/// The value is written to a temporary file in the same directory, which then replaces the
+/// file, so that an interrupted save leaves the previous content intact.
+pub(crate) fn save<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    debug!("Saving {path:?}");
+    let dir = path
+        .parent()
+        .ok_or_else(|| eyre!("{} has no parent directory", path.display()))?;
+    let mut builder = tempfile::Builder::new();
+    builder.prefix(".webex-tui-").suffix(".tmp");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        builder.permissions(fs::Permissions::from_mode(0o600));
+    }
+    let mut file = builder.tempfile_in(dir)?;
+    {
+        let mut writer = BufWriter::new(file.as_file_mut());
+        serde_json::to_writer_pretty(&mut writer, value)?;
+        writer.flush()?;
+    }
+    file.as_file().sync_all()?;
+    file.persist(path)
+        .map_err(|e| eyre!("Failed to replace {}: {e}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_save_replaces_the_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("values.json");
+        save(&path, &vec![1, 2, 3]).unwrap();
+        save(&path, &vec![4]).unwrap();
+        assert_eq!(load::<Vec<u32>>(&path).unwrap(), Some(vec![4]));
+        // No temporary file is left behind
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_persisted() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("values.json");
+        let mut values = Persisted::<Vec<u32>>::load_from(path.clone());
+        assert!(values.is_empty());
+        values.push(1);
+        values.save();
+        assert_eq!(*Persisted::<Vec<u32>>::load_from(path.clone()), vec![1]);
+        // A file that cannot be parsed gives the default value
+        fs::write(&path, "not json").unwrap();
+        assert!(Persisted::<Vec<u32>>::load_from(path).is_empty());
+    }
+}
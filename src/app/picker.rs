@@ -0,0 +1,306 @@
+// app/picker.rs
+
+//! A popup list the user picks an item from.
+
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+use ratatui::widgets::TableState;
+
+use super::cache::room::RoomId;
+use super::cache::MessageId;
+
+/// What the picker is used for, which determines what happens with the chosen item.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum PickerKind {
+    /// Attachments of the selected message, item values are content urls.
+    Attachments,
+    /// Members of the active room to mention, item values are person ids.
+    Mentions,
+    /// Links found in messages, item values are urls.
+    Links,
+    /// Rooms to forward the message with given id and room to, item values are room ids.
+    Forward(RoomId, MessageId),
+    /// Persons to chat with, item values are person ids.
+    People,
+    /// Persons to add to the room with given id, item values are emails.
+    NewMember(RoomId),
+    /// Teams to create the space with given title in, item values are team ids, empty for
+    /// no team.
+    Teams(String),
+}
+
+/// An entry in the picker.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct PickerItem {
+    /// Text shown to the user
+    pub(crate) label: String,
+    /// Value used by the app when the item is chosen
+    pub(crate) value: String,
+}
+
+impl PickerItem {
+    pub(crate) fn new(label: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            value: value.into(),
+        }
+    }
+}
+
+pub(crate) struct Picker {
+    kind: PickerKind,
+    title: String,
+    items: Vec<PickerItem>,
+    /// Indices of the items matching the query, best match first
+    visible: Vec<usize>,
+    query: String,
+    /// Whether the items are filtered by the caller, the query is then not applied to them
+    prefiltered: bool,
+    table_state: TableState,
+}
+
+impl Picker {
+    /// Returns a picker with the first item selected.
+    pub(crate) fn new(kind: PickerKind, title: impl Into<String>, items: Vec<PickerItem>) -> Self {
+        let mut picker = Self {
+            kind,
+            title: title.into(),
+            items,
+            visible: vec![],
+            query: String::new(),
+            prefiltered: false,
+            table_state: TableState::default(),
+        };
+        picker.apply_query();
+        picker
+    }
+
+    /// Returns a picker showing all its items, which the caller updates as the query changes.
+    pub(crate) fn prefiltered(
+        kind: PickerKind,
+        title: impl Into<String>,
+        items: Vec<PickerItem>,
+    ) -> Self {
+        let mut picker = Self::new(kind, title, items);
+        picker.prefiltered = true;
+        picker
+    }
+
+    pub(crate) fn kind(&self) -> &PickerKind {
+        &self.kind
+    }
+
+    pub(crate) fn title(&self) -> &str {
+        &self.title
+    }
+
+    pub(crate) fn set_title(&mut self, title: impl Into<String>) {
+        self.title = title.into();
+    }
+
+    /// Returns all the items, including those not matching the query.
+    pub(crate) fn items(&self) -> &[PickerItem] {
+        &self.items
+    }
+
+    /// Replaces the items, keeping the query.
+    pub(crate) fn set_items(&mut self, items: Vec<PickerItem>) {
+        self.items = items;
+        self.apply_query();
+    }
+
+    /// Returns the items matching the query, best match first.
+    pub(crate) fn visible_items(&self) -> Vec<&PickerItem> {
+        self.visible
+            .iter()
+            .map(|&index| &self.items[index])
+            .collect()
+    }
+
+    pub(crate) fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Filters the items with a fuzzy match of their label, and selects the best match.
+    /// An empty query shows all the items.
+    pub(crate) fn set_query(&mut self, query: &str) {
+        self.query = query.to_string();
+        self.apply_query();
+    }
+
+    fn apply_query(&mut self) {
+        self.visible = match self.prefiltered || self.query.is_empty() {
+            true => (0..self.items.len()).collect(),
+            false => {
+                let matcher = SkimMatcherV2::default();
+                let mut scored: Vec<_> = self
+                    .items
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, item)| {
+                        matcher
+                            .fuzzy_match(&item.label, &self.query)
+                            .map(|score| (index, score))
+                    })
+                    .collect();
+                // Sort by score (highest first), stable to keep the order of equal matches
+                scored.sort_by(|a, b| b.1.cmp(&a.1));
+                scored.into_iter().map(|(index, _)| index).collect()
+            }
+        };
+        let selected = match self.visible.is_empty() {
+            true => None,
+            false => Some(0),
+        };
+        self.table_state.select(selected);
+    }
+
+    /// Returns the selected item, if any.
+    pub(crate) fn selected_item(&self) -> Option<&PickerItem> {
+        self.table_state
+            .selected()
+            .and_then(|index| self.visible.get(index))
+            .map(|&index| &self.items[index])
+    }
+
+    /// Selects the visible item at `index`, if there is one.
+    pub(crate) fn select(&mut self, index: usize) -> bool {
+        if index < self.visible.len() {
+            self.table_state.select(Some(index));
+            return true;
+        }
+        false
+    }
+
+    /// Selects the next item, wrapping around at the end of the list.
+    pub(crate) fn select_next(&mut self) {
+        let next = match self.table_state.selected() {
+            _ if self.visible.is_empty() => None,
+            Some(selected) if selected + 1 < self.visible.len() => Some(selected + 1),
+            _ => Some(0),
+        };
+        self.table_state.select(next);
+    }
+
+    /// Selects the previous item, wrapping around at the start of the list.
+    pub(crate) fn select_previous(&mut self) {
+        let previous = match self.table_state.selected() {
+            _ if self.visible.is_empty() => None,
+            Some(selected) if selected > 0 => Some(selected - 1),
+            _ => Some(self.visible.len() - 1),
+        };
+        self.table_state.select(previous);
+    }
+
+    /// Updates the label of the items with the given value.
+    pub(crate) fn set_label(&mut self, value: &str, label: impl Into<String>) {
+        let label = label.into();
+        for item in self.items.iter_mut().filter(|item| item.value == value) {
+            item.label.clone_from(&label);
+        }
+    }
+
+    pub(crate) fn table_state_mut(&mut self) -> &mut TableState {
+        &mut self.table_state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_picker(nb_items: usize) -> Picker {
+        let items = (0..nb_items)
+            .map(|i| PickerItem::new(format!("item {i}"), format!("value {i}")))
+            .collect();
+        Picker::new(PickerKind::Attachments, "Attachments", items)
+    }
+
+    #[test]
+    fn test_select_next_and_previous_wrap_around() {
+        let mut picker = make_picker(3);
+        assert_eq!(picker.selected_item().unwrap().value, "value 0");
+        picker.select_previous();
+        assert_eq!(picker.selected_item().unwrap().value, "value 2");
+        picker.select_next();
+        assert_eq!(picker.selected_item().unwrap().value, "value 0");
+        picker.select_next();
+        assert_eq!(picker.selected_item().unwrap().value, "value 1");
+    }
+
+    #[test]
+    fn test_select() {
+        let mut picker = make_picker(2);
+        assert!(picker.select(1));
+        assert_eq!(picker.selected_item().unwrap().value, "value 1");
+        assert!(!picker.select(2));
+        assert_eq!(picker.selected_item().unwrap().value, "value 1");
+    }
+
+    #[test]
+    fn test_empty_picker_has_no_selection() {
+        let mut picker = make_picker(0);
+        picker.select_next();
+        assert_eq!(picker.selected_item(), None);
+        picker.select_previous();
+        assert_eq!(picker.selected_item(), None);
+    }
+
+    #[test]
+    fn test_query_filters_and_selects_best_match() {
+        let items = vec![
+            PickerItem::new("Alice Smith", "1"),
+            PickerItem::new("Bob Jones", "2"),
+            PickerItem::new("Bobby Tables", "3"),
+        ];
+        let mut picker = Picker::new(PickerKind::Mentions, "Mention", items);
+        picker.set_query("bob");
+        let labels: Vec<_> = picker.visible_items().iter().map(|i| &i.label).collect();
+        assert_eq!(labels, vec!["Bob Jones", "Bobby Tables"]);
+        assert_eq!(picker.selected_item().unwrap().value, "2");
+        picker.select_next();
+        assert_eq!(picker.selected_item().unwrap().value, "3");
+        picker.select_next();
+        assert_eq!(picker.selected_item().unwrap().value, "2");
+
+        picker.set_query("zzz");
+        assert!(picker.visible_items().is_empty());
+        assert_eq!(picker.selected_item(), None);
+
+        picker.set_query("");
+        assert_eq!(picker.visible_items().len(), 3);
+        assert_eq!(picker.selected_item().unwrap().value, "1");
+    }
+
+    #[test]
+    fn test_set_items_keeps_query() {
+        let mut picker = make_picker(0);
+        picker.set_query("3");
+        picker.set_items(
+            (0..5)
+                .map(|i| PickerItem::new(format!("item {i}"), format!("value {i}")))
+                .collect(),
+        );
+        assert_eq!(picker.items().len(), 5);
+        assert_eq!(picker.selected_item().unwrap().value, "value 3");
+    }
+
+    #[test]
+    fn test_prefiltered_picker_shows_all_items() {
+        let items = vec![PickerItem::new("Lunch", "1"), PickerItem::new("Work", "2")];
+        let kind = PickerKind::Forward("room".to_string(), "message".to_string());
+        let mut picker = Picker::prefiltered(kind, "Forward to", items);
+        picker.set_query("zzz");
+        assert_eq!(picker.query(), "zzz");
+        assert_eq!(picker.visible_items().len(), 2);
+        picker.set_items(vec![PickerItem::new("Work", "2")]);
+        assert_eq!(picker.selected_item().unwrap().value, "2");
+    }
+
+    #[test]
+    fn test_set_label() {
+        let mut picker = make_picker(2);
+        picker.set_label("value 1", "report.pdf");
+        assert_eq!(picker.items()[1].label, "report.pdf");
+        assert_eq!(picker.items()[0].label, "item 0");
+    }
+}
@@ -0,0 +1,146 @@
+// app/prompt.rs
+
+//! A line of text asked to the user in a popup, to start a conversation, create a space
+//! or snooze a room.
+
+use color_eyre::{eyre::eyre, Result};
+
+use super::cache::persons::PersonId;
+use super::cache::room::RoomId;
+use super::cache::teams::TeamId;
+
+/// What the text is asked for, which determines what happens when it is confirmed.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum PromptKind {
+    /// The first message to a person we have no conversation with, which starts it.
+    FirstMessage { person_id: PersonId, name: String },
+    /// The title of a new space.
+    SpaceTitle,
+    /// The emails of the first members of a new space.
+    SpaceMembers {
+        title: String,
+        team_id: Option<TeamId>,
+    },
+    /// The new title of the room with given id.
+    RoomTitle { room_id: RoomId },
+    /// When the room with given id stops being snoozed.
+    SnoozeUntil { room_id: RoomId },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Prompt {
+    kind: PromptKind,
+    input: String,
+    /// Why the input was rejected, until it is changed
+    error: Option<String>,
+}
+
+impl Prompt {
+    pub(crate) fn new(kind: PromptKind) -> Self {
+        Self {
+            kind,
+            input: String::new(),
+            error: None,
+        }
+    }
+
+    /// Returns a prompt with some text already typed, which the user can change.
+    pub(crate) fn with_input(kind: PromptKind, input: impl Into<String>) -> Self {
+        Self {
+            kind,
+            input: input.into(),
+            error: None,
+        }
+    }
+
+    pub(crate) fn kind(&self) -> &PromptKind {
+        &self.kind
+    }
+
+    pub(crate) fn input(&self) -> &str {
+        &self.input
+    }
+
+    pub(crate) fn push(&mut self, c: char) {
+        self.error = None;
+        self.input.push(c);
+    }
+
+    pub(crate) fn pop(&mut self) {
+        self.error = None;
+        self.input.pop();
+    }
+
+    /// Returns why the input was rejected, if it was.
+    pub(crate) fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Shows why the input was rejected, so that the user can correct it.
+    pub(crate) fn set_error(&mut self, error: String) {
+        self.error = Some(error);
+    }
+
+    /// Returns the question asked to the user.
+    pub(crate) fn title(&self) -> String {
+        match &self.kind {
+            PromptKind::FirstMessage { name, .. } => format!("First message to {name}"),
+            PromptKind::SpaceTitle => String::from("Title of the new space"),
+            PromptKind::SpaceMembers { title, .. } => {
+                format!("Emails of the members of {title}, optional")
+            }
+            PromptKind::RoomTitle { .. } => String::from("New title of the room"),
+            PromptKind::SnoozeUntil { .. } => {
+                String::from("Snooze until, e.g. 'in 2h', '17:30' or 'tomorrow 9am'")
+            }
+        }
+    }
+}
+
+/// Returns the email addresses in the text, separated by commas, semicolons or spaces.
+/// Fails on anything that is not an email address.
+pub(crate) fn parse_emails(text: &str) -> Result<Vec<String>> {
+    text.split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+        .filter(|word| !word.is_empty())
+        .map(|word| match word.contains('@') {
+            true => Ok(word.to_string()),
+            false => Err(eyre!("'{word}' is not an email address")),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest(
+        text,
+        expected,
+        case("", vec![]),
+        case("ann@example.com", vec!["ann@example.com"]),
+        case(
+            "ann@example.com, bob@example.com;carl@example.com  ",
+            vec!["ann@example.com", "bob@example.com", "carl@example.com"]
+        )
+    )]
+    fn test_parse_emails(text: &str, expected: Vec<&str>) {
+        assert_eq!(parse_emails(text).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_parse_emails_rejects_other_words() {
+        assert!(parse_emails("ann@example.com, dan").is_err());
+    }
+
+    #[test]
+    fn test_input() {
+        let mut prompt = Prompt::new(PromptKind::SpaceTitle);
+        prompt.push('a');
+        prompt.push('b');
+        prompt.set_error("Not valid".to_string());
+        prompt.pop();
+        assert_eq!(prompt.input(), "a");
+        assert_eq!(prompt.error(), None);
+    }
+}
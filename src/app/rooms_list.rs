@@ -3,9 +3,7 @@
 //! List of rooms, with UI scrolling and selection state and display filters.
 
 use super::cache::room::{Room, RoomId};
-use super::cache::room_list_filter::RoomsListFilter;
-use super::cache::Cache;
-use enum_iterator::{next_cycle, previous_cycle};
+use super::cache::room_list_filter::{CustomFilter, RoomsListFilter};
 use log::*;
 use ratatui::widgets::TableState;
 use std::collections::HashSet;
@@ -33,34 +31,24 @@ pub(crate) struct RoomsList {
 }
 
 impl RoomsList {
-    /// Switches the rooms list table to the next filtering mode.
-    /// Does not update the active room.
-    pub(crate) fn next_filter(&mut self, store: &Cache) {
-        let new_mode = next_cycle(&self.filter);
+    /// Switches the rooms list table to the next filtering mode, the custom filters
+    /// following the built-in ones. Does not update the active room.
+    pub(crate) fn next_filter(&mut self, custom_filters: &[CustomFilter]) {
+        let new_mode = self.filter.next(custom_filters);
         debug!("Rooms list filter set to {new_mode:?}");
         self.filter = new_mode;
-        // Reset selection when we change filter
-        let num_rooms = store
-            .rooms
-            .rooms_filtered_by(self.filter())
-            .collect::<Vec<_>>()
-            .len();
-        let selected = if num_rooms == 0 { None } else { Some(0) };
-        self.table_state.select(selected);
     }
 
-    /// Switches the rooms list table to the previous filtering mode.
-    /// Does not update the active room.
-    pub(crate) fn previous_filter(&mut self, store: &Cache) {
-        let new_mode = previous_cycle(&self.filter);
+    /// Switches the rooms list table to the previous filtering mode, the custom filters
+    /// following the built-in ones. Does not update the active room.
+    pub(crate) fn previous_filter(&mut self, custom_filters: &[CustomFilter]) {
+        let new_mode = self.filter.previous(custom_filters);
         debug!("Rooms list mode set to {new_mode:?}");
         self.filter = new_mode;
-        // Reset selection when we change filter
-        let num_rooms = store
-            .rooms
-            .rooms_filtered_by(self.filter())
-            .collect::<Vec<_>>()
-            .len();
+    }
+
+    /// Selects the first room, after the filter changed.
+    pub(crate) fn select_first_room(&mut self, num_rooms: usize) {
         let selected = if num_rooms == 0 { None } else { Some(0) };
         self.table_state.select(selected);
     }
@@ -136,6 +124,12 @@ impl RoomsList {
         &self.filter
     }
 
+    /// Sets the filtering mode. Does not update the selection nor the active room.
+    pub(crate) fn set_filter(&mut self, filter: RoomsListFilter) {
+        debug!("Rooms list filter set to {filter:?}");
+        self.filter = filter;
+    }
+
     pub(crate) fn active_room_id(&self) -> Option<&String> {
         self.active_room_id.as_ref()
     }
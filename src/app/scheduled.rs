@@ -0,0 +1,343 @@
+// app/scheduled.rs
+
+//! Messages scheduled to be sent later, kept across restarts.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use color_eyre::{eyre::eyre, Result};
+use ratatui::widgets::TableState;
+use serde::{Deserialize, Serialize};
+use webex::types::MessageOut;
+
+use super::cache::room::RoomId;
+use super::drafts::Draft;
+use super::persistence::Persisted;
+
+/// Name of the file where scheduled messages are saved, in the cache directory.
+const SCHEDULED_FILE: &str = "scheduled.json";
+
+/// Examples of send times shown to the user.
+pub(crate) const SEND_TIME_EXAMPLES: &str = "in 2h, in 1h30m, 17:30, tomorrow 9:00, mon 8:30";
+
+/// A message waiting to be sent.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub(crate) struct ScheduledMessage {
+    pub(crate) id: u64,
+    pub(crate) room_id: RoomId,
+    pub(crate) due: DateTime<Utc>,
+    /// The message sent when due
+    pub(crate) message: MessageOut,
+    /// The content of the editor, to edit the message before it is sent
+    pub(crate) draft: Draft,
+}
+
+/// Scheduled messages, by due date, and the selection in the list shown to the user.
+#[derive(Debug, Default)]
+pub(crate) struct Schedule {
+    messages: Persisted<Vec<ScheduledMessage>>,
+    table_state: TableState,
+}
+
+impl Schedule {
+    pub(crate) fn load() -> Self {
+        Self {
+            messages: Persisted::load(SCHEDULED_FILE),
+            ..Default::default()
+        }
+    }
+
+    /// Returns the messages, the next one due first.
+    pub(crate) fn messages(&self) -> &[ScheduledMessage] {
+        &self.messages
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// Schedules a message, returns its id.
+    pub(crate) fn add(
+        &mut self,
+        room_id: RoomId,
+        due: DateTime<Utc>,
+        message: MessageOut,
+        draft: Draft,
+    ) -> u64 {
+        let id = self.messages.iter().map(|m| m.id + 1).max().unwrap_or(1);
+        self.messages.push(ScheduledMessage {
+            id,
+            room_id,
+            due,
+            message,
+            draft,
+        });
+        self.messages.sort_by_key(|m| m.due);
+        self.messages.save();
+        id
+    }
+
+    /// Removes a message from the schedule and returns it.
+    pub(crate) fn remove(&mut self, id: u64) -> Option<ScheduledMessage> {
+        let index = self.messages.iter().position(|m| m.id == id)?;
+        let message = self.messages.remove(index);
+        self.messages.save();
+        self.clamp_selection();
+        Some(message)
+    }
+
+    /// Removes the messages due at `now` from the schedule and returns them.
+    pub(crate) fn take_due(&mut self, now: DateTime<Utc>) -> Vec<ScheduledMessage> {
+        let (due, later) = self.messages.drain(..).partition(|m| m.due <= now);
+        *self.messages = later;
+        if !due.is_empty() {
+            self.messages.save();
+            self.clamp_selection();
+        }
+        due
+    }
+
+    /// Returns the message selected in the list.
+    pub(crate) fn selected(&self) -> Option<&ScheduledMessage> {
+        self.table_state
+            .selected()
+            .and_then(|index| self.messages.get(index))
+    }
+
+    /// Selects the first message, if any.
+    pub(crate) fn select_first(&mut self) {
+        let first = (!self.messages.is_empty()).then_some(0);
+        self.table_state.select(first);
+    }
+
+    /// Selects the next message, wrapping around at the end of the list.
+    pub(crate) fn select_next(&mut self) {
+        let next = match self.table_state.selected() {
+            _ if self.messages.is_empty() => None,
+            Some(selected) if selected + 1 < self.messages.len() => Some(selected + 1),
+            _ => Some(0),
+        };
+        self.table_state.select(next);
+    }
+
+    /// Selects the previous message, wrapping around at the start of the list.
+    pub(crate) fn select_previous(&mut self) {
+        let previous = match self.table_state.selected() {
+            _ if self.messages.is_empty() => None,
+            Some(selected) if selected > 0 => Some(selected - 1),
+            _ => Some(self.messages.len() - 1),
+        };
+        self.table_state.select(previous);
+    }
+
+    /// Keeps the selection within the list after messages are removed.
+    fn clamp_selection(&mut self) {
+        if let Some(selected) = self.table_state.selected() {
+            let last = self.messages.len().checked_sub(1);
+            self.table_state.select(last.map(|last| selected.min(last)));
+        }
+    }
+
+    pub(crate) fn table_state_mut(&mut self) -> &mut TableState {
+        &mut self.table_state
+    }
+}
+
+/// Returns the time described by the user, relative to `now` in its time zone.
+/// Accepts a delay such as `in 2h` or `in 1h30m`, or a time such as `17:30` or `9am`
+/// optionally preceded by `today`, `tomorrow`, a weekday or a date `2025-12-24`.
+/// The time must be in the future.
+pub(crate) fn parse_send_time<Tz: TimeZone>(
+    input: &str,
+    now: &DateTime<Tz>,
+) -> Result<DateTime<Tz>> {
+    let input = input.trim().to_lowercase();
+    let time = match input.strip_prefix("in ") {
+        Some(delay) => now
+            .clone()
+            .checked_add_signed(parse_delay(delay)?)
+            .ok_or(eyre!("{input} is too far in the future"))?,
+        None => {
+            let (day, time) = match input.split_once(char::is_whitespace) {
+                Some((day, time)) => (Some(day), time.trim()),
+                None => (None, input.as_str()),
+            };
+            let time = parse_time_of_day(time)?;
+            let today = now.date_naive();
+            let date = match day {
+                None if today.and_time(time) > now.naive_local() => today,
+                None => today + Duration::days(1),
+                Some(day) => parse_day(day, today, today.and_time(time) <= now.naive_local())?,
+            };
+            now.timezone()
+                .from_local_datetime(&date.and_time(time))
+                .earliest()
+                .ok_or(eyre!("{input} does not exist in the local time zone"))?
+        }
+    };
+    match time > *now {
+        true => Ok(time),
+        false => Err(eyre!("{input} is in the past")),
+    }
+}
+
+/// Parses a delay made of numbers followed by a unit, e.g. `2h`, `1h 30m` or `3 days`.
+fn parse_delay(delay: &str) -> Result<Duration> {
+    let delay: String = delay.split_whitespace().collect();
+    let mut total = Duration::zero();
+    let mut rest = delay.as_str();
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let number: i64 = rest[..digits]
+            .parse()
+            .map_err(|_| eyre!("Expected a number in {delay}"))?;
+        let too_long = || eyre!("{delay} is too long");
+        let number = i32::try_from(number).map_err(|_| too_long())?;
+        rest = &rest[digits..];
+        let letters = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let unit = match &rest[..letters] {
+            "m" | "min" | "mins" | "minute" | "minutes" => Duration::minutes(1),
+            "h" | "hr" | "hrs" | "hour" | "hours" => Duration::hours(1),
+            "d" | "day" | "days" => Duration::days(1),
+            "w" | "week" | "weeks" => Duration::weeks(1),
+            unit => return Err(eyre!("Unknown unit '{unit}' in {delay}")),
+        };
+        total = unit
+            .checked_mul(number)
+            .and_then(|duration| total.checked_add(&duration))
+            .ok_or_else(too_long)?;
+        rest = &rest[letters..];
+    }
+    match total > Duration::zero() {
+        true => Ok(total),
+        false => Err(eyre!("Expected a delay such as 2h")),
+    }
+}
+
+/// Parses a time of the day, e.g. `9`, `17:30`, `9am` or `5:30pm`.
+fn parse_time_of_day(time: &str) -> Result<NaiveTime> {
+    let invalid = || eyre!("Expected a time such as 9:00 or 5pm, got '{time}'");
+    let (time_part, offset) = match (time.strip_suffix("am"), time.strip_suffix("pm")) {
+        (Some(time), _) => (time, Some(0)),
+        (_, Some(time)) => (time, Some(12)),
+        _ => (time, None),
+    };
+    let (hours, minutes) = time_part.trim().split_once(':').unwrap_or((time_part, "0"));
+    let hours: u32 = hours.trim().parse().map_err(|_| invalid())?;
+    let minutes: u32 = minutes.trim().parse().map_err(|_| invalid())?;
+    let hours = match offset {
+        Some(_) if !(1..=12).contains(&hours) => return Err(invalid()),
+        Some(offset) => hours % 12 + offset,
+        None => hours,
+    };
+    NaiveTime::from_hms_opt(hours, minutes, 0).ok_or_else(invalid)
+}
+
+/// Parses a day relative to `today`, the next occurrence for a weekday.
+/// Today's weekday is next week's if the time has passed.
+fn parse_day(day: &str, today: NaiveDate, time_passed: bool) -> Result<NaiveDate> {
+    match day {
+        "today" => Ok(today),
+        "tomorrow" => Ok(today + Duration::days(1)),
+        _ => {
+            if let Ok(date) = NaiveDate::parse_from_str(day, "%Y-%m-%d") {
+                return Ok(date);
+            }
+            let weekday: Weekday = day
+                .parse()
+                .map_err(|_| eyre!("Expected today, tomorrow, a weekday or a date, got '{day}'"))?;
+            let days_ahead =
+                (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+            let days_ahead = match days_ahead == 0 && time_passed {
+                true => 7,
+                false => days_ahead,
+            };
+            Ok(today + Duration::days(days_ahead.into()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    /// Wednesday 2025-10-15 10:00 UTC
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 10, 15, 10, 0, 0).unwrap()
+    }
+
+    #[rstest(
+        input,
+        expected,
+        case("in 2h", "2025-10-15T12:00:00Z"),
+        case("in 1h 30m", "2025-10-15T11:30:00Z"),
+        case("In 3 days", "2025-10-18T10:00:00Z"),
+        case("17:30", "2025-10-15T17:30:00Z"),
+        case("9:00", "2025-10-16T09:00:00Z"),
+        case("tomorrow 9am", "2025-10-16T09:00:00Z"),
+        case("today 5:15pm", "2025-10-15T17:15:00Z"),
+        case("mon 8:30", "2025-10-20T08:30:00Z"),
+        case("Wednesday 12pm", "2025-10-15T12:00:00Z"),
+        case("wed 9:00", "2025-10-22T09:00:00Z"),
+        case("2025-12-24 18:00", "2025-12-24T18:00:00Z")
+    )]
+    fn test_parse_send_time(input: &str, expected: &str) {
+        let expected: DateTime<Utc> = expected.parse().unwrap();
+        assert_eq!(parse_send_time(input, &now()).unwrap(), expected);
+    }
+
+    #[rstest(
+        input,
+        case(""),
+        case("in"),
+        case("in 0m"),
+        case("in 2 parsecs"),
+        case("today 9:00"),
+        case("25:00"),
+        case("13pm"),
+        case("someday 9:00"),
+        case("in 99999999w"),
+        case("in 9999999999999999999d"),
+        case("in 2000000000w 2000000000w")
+    )]
+    fn test_parse_invalid_send_time(input: &str) {
+        assert!(parse_send_time(input, &now()).is_err());
+    }
+
+    #[test]
+    fn test_schedule() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(SCHEDULED_FILE);
+        let mut schedule = Schedule {
+            messages: Persisted::load_from(path.clone()),
+            ..Default::default()
+        };
+        let later = schedule.add(
+            "room".to_string(),
+            now() + Duration::hours(2),
+            MessageOut::default(),
+            Draft::default(),
+        );
+        let sooner = schedule.add(
+            "room".to_string(),
+            now() + Duration::hours(1),
+            MessageOut::default(),
+            Draft::default(),
+        );
+        assert_eq!(schedule.messages()[0].id, sooner);
+
+        let mut loaded = Schedule {
+            messages: Persisted::load_from(path),
+            ..Default::default()
+        };
+        assert_eq!(loaded.messages(), schedule.messages());
+        let due = loaded.take_due(now() + Duration::minutes(90));
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].id, sooner);
+        assert!(loaded.remove(later).is_some());
+        assert!(loaded.is_empty());
+    }
+}
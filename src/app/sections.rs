@@ -0,0 +1,122 @@
+// app/sections.rs
+
+//! Named sections grouping rooms in the rooms list, defined in the config.
+
+use serde::{Deserialize, Serialize};
+
+use super::cache::room::{Room, RoomId};
+
+/// A section of the rooms list. A room belongs to the first section it matches by id,
+/// team or title.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RoomSection {
+    /// Name shown above the rooms of the section
+    pub(crate) name: String,
+    /// Ids of the rooms in the section
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) rooms: Vec<RoomId>,
+    /// Names or ids of the teams whose rooms are in the section, ignoring case
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) teams: Vec<String>,
+    /// Pattern matching the titles of the rooms in the section, ignoring case,
+    /// where `*` matches any text, e.g. `*standup*`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) title_pattern: Option<String>,
+}
+
+impl RoomSection {
+    /// Returns true if the room, in the team with the given name if any, is in the section.
+    pub(crate) fn matches(&self, room: &Room, team_name: Option<&str>) -> bool {
+        self.rooms.contains(&room.id)
+            || self
+                .teams
+                .iter()
+                .any(|team| room.is_in_team(team, team_name))
+            || self
+                .title_pattern
+                .as_ref()
+                .zip(room.title.as_ref())
+                .is_some_and(|(pattern, title)| matches_pattern(pattern, title))
+    }
+}
+
+/// Returns true if the text matches the pattern, ignoring case, where `*` matches any text.
+fn matches_pattern(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    let text = text.to_lowercase();
+    let mut parts = pattern.split('*');
+    // The text starts with the part before the first star, if any
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts = parts.peekable();
+    while let Some(part) = parts.next() {
+        if parts.peek().is_none() {
+            // The last part must end the text
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest(
+        pattern,
+        text,
+        expected,
+        case("Standup", "standup", true),
+        case("Standup", "Daily standup", false),
+        case("*standup*", "Daily Standup notes", true),
+        case("*standup", "Daily standup", true),
+        case("*standup", "Standup notes", false),
+        case("ops-*", "ops-alerts", true),
+        case("ops-*-prod", "ops-db-prod", true),
+        case("ops-*-prod", "ops-db-dev", false),
+        case("a*a", "a", false),
+        case("*", "anything", true)
+    )]
+    fn test_matches_pattern(pattern: &str, text: &str, expected: bool) {
+        assert_eq!(matches_pattern(pattern, text), expected);
+    }
+
+    #[test]
+    fn test_section_matches_by_id_team_or_title() {
+        let room = Room {
+            id: "room".to_string(),
+            title: Some("Release planning".to_string()),
+            team_id: Some("team".to_string()),
+            ..Default::default()
+        };
+        let by_id = RoomSection {
+            rooms: vec!["room".to_string()],
+            ..Default::default()
+        };
+        let by_team_id = RoomSection {
+            teams: vec!["team".to_string()],
+            ..Default::default()
+        };
+        let by_team_name = RoomSection {
+            teams: vec!["engineering".to_string()],
+            ..Default::default()
+        };
+        let by_title = RoomSection {
+            title_pattern: Some("release*".to_string()),
+            ..Default::default()
+        };
+        assert!(by_id.matches(&room, None));
+        assert!(by_team_id.matches(&room, None));
+        assert!(by_team_name.matches(&room, Some("Engineering")));
+        assert!(!by_team_name.matches(&room, Some("Sales")));
+        assert!(by_title.matches(&room, None));
+        assert!(!RoomSection::default().matches(&room, Some("Engineering")));
+    }
+}
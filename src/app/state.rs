@@ -1,21 +1,54 @@
 //! State of the application
 
+use chrono::Utc;
 use color_eyre::{eyre::eyre, Result};
 use enum_iterator::{next_cycle, previous_cycle, Sequence};
 use itertools::concat;
 use log::*;
 use ratatui::layout::Rect;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::time::Instant;
 use tui_logger::TuiWidgetState;
+use webex::types::MessageOut;
 use webex::Message;
 
 use super::actions::{Action, Actions};
+use super::bookmarks::Bookmarks;
+use super::cache::members::Member;
 use super::cache::room::{Room, RoomId};
-use super::cache::Cache;
+use super::cache::room_list_filter::{ActivityThresholds, RoomMarks, RoomsListFilter};
+use super::cache::thread_view::DisplayedMessage;
+use super::cache::typing::typing_description;
+use super::cache::{is_local_message, local_message_id, searchable_text, Cache, MessageId};
+use super::cards::CardForm;
+use super::drafts::Drafts;
+use super::favourites::Favourites;
+use super::members_list::MembersList;
 use super::message_editor::MessageEditor;
+use super::message_search::{excerpt, MessageSearch, SearchResult};
 use super::messages_list::MessagesList;
+use super::mutes::{Mute, Mutes};
+use super::outbox::{Outbox, OutboxCommand, OutboxId};
+use super::picker::{Picker, PickerItem, PickerKind};
+use super::prompt::Prompt;
 use super::rooms_list::RoomsList;
+use super::scheduled::Schedule;
+use crate::config::UserConfig;
 use crate::theme::Theme;
 
+/// Value of the mention item notifying everyone in a space.
+pub(crate) const MENTION_ALL: &str = "all";
+
+/// Name of the section of the rooms list with the favourite rooms.
+const FAVOURITES_SECTION: &str = "★ Favourites";
+
+/// Name of the section of the rooms list with the rooms in no other section.
+const OTHER_ROOMS_SECTION: &str = "Other rooms";
+
+/// Maximum number of messages listed in the search results.
+const MAX_SEARCH_RESULTS: usize = 200;
+
 /// State of the application, including
 /// - available `actions`` in the current context
 /// - whether `editing_mode` is enabled or not
@@ -27,24 +60,80 @@ pub(crate) struct AppState<'a> {
     pub(crate) actions: Actions,
     pub(crate) debug: bool,
     pub(crate) is_loading: bool,
+    /// Number of rooms received so far while the list of all rooms is retrieved
+    pub(crate) rooms_loading_progress: Option<usize>,
     pub(crate) messages_to_load: u32,
+    pub(crate) config: UserConfig,
+    /// Room where we last told we were typing, and when
+    pub(crate) typing_notified: Option<(RoomId, Instant)>,
+    /// Query to search the directory for once the user stops typing, and when it was typed
+    pub(crate) people_search: Option<(String, Instant)>,
 
     // Webex
     pub(crate) cache: Cache,
 
+    // Drafts, scheduled messages, the outbox, bookmarks, favourites and mutes
+    pub(crate) drafts: Drafts,
+    pub(crate) schedule: Schedule,
+    pub(crate) outbox: Outbox,
+    pub(crate) bookmarks: Bookmarks,
+    /// Rooms pinned by the user, changed with `toggle_favourite` to update the rooms list
+    pub(crate) favourites: Favourites,
+    /// Rooms muted or snoozed by the user, changed with `mute` and `unmute`
+    pub(crate) mutes: Mutes,
+
     // Theme
     pub(crate) theme: Theme,
 
     // UI
     pub(crate) active_pane: Option<ActivePane>,
+    pub(crate) card_form: Option<CardForm>,
     pub(crate) last_frame_size: Rect,
     pub(crate) log_state: TuiWidgetState,
     pub(crate) message_editor: MessageEditor<'a>,
     pub(crate) messages_list: MessagesList,
+    pub(crate) message_search: Option<MessageSearch>,
+    /// Message to select once it is loaded, older messages are requested until it is
+    pub(crate) message_to_show: Option<(RoomId, MessageId)>,
+    pub(crate) picker: Option<Picker>,
+    /// Text asked to the user to start a conversation or create a space
+    pub(crate) prompt: Option<Prompt>,
     pub(crate) rooms_list: RoomsList,
+    /// How long without activity rooms are recent or inactive, for the rooms list filters
+    pub(crate) activity_thresholds: ActivityThresholds,
+    /// The rooms last shown in the list, until something they depend on changes
+    pub(crate) visible_rooms: RefCell<Option<VisibleRooms>>,
+    /// Time typed by the user to send the message in the editor later
+    pub(crate) send_time_prompt: Option<String>,
     pub(crate) show_help: bool,
     pub(crate) show_logs: bool,
     pub(crate) show_rooms: bool,
+    pub(crate) show_scheduled: bool,
+    pub(crate) show_outbox: bool,
+    pub(crate) show_bookmarks: bool,
+    pub(crate) show_members: bool,
+    pub(crate) members_list: MembersList,
+    pub(crate) show_room_details: bool,
+}
+
+/// The rooms shown in the list, and what they depend on.
+#[derive(Debug)]
+pub(crate) struct VisibleRooms {
+    key: VisibleRoomsKey,
+    /// Positions of the rooms in `Rooms::sorted_rooms`, in the order they are shown
+    indices: Vec<usize>,
+}
+
+/// What the rooms shown in the list depend on, the list is outdated when it changes.
+#[derive(Debug, PartialEq)]
+struct VisibleRoomsKey {
+    rooms: u64,
+    teams: u64,
+    filter: RoomsListFilter,
+    search_query: Option<String>,
+    thresholds: ActivityThresholds,
+    /// Activity and snoozes are relative to the current time
+    minute: i64,
 }
 
 /// The active pane is used by the UI to draw attention to what
@@ -82,24 +171,214 @@ impl AppState<'_> {
         }
     }
 
+    /// Returns whether a message is being written in the room, in the editor or as a draft.
+    pub(crate) fn room_has_draft(&self, room_id: &RoomId) -> bool {
+        match self.drafts.editor_room_id() == Some(room_id) {
+            true => !self.message_editor.draft().is_empty(),
+            false => self.drafts.has_draft(room_id),
+        }
+    }
+
     /// Returns an iterator over all visible rooms with the current filter.
+    /// The list is only filtered and sorted again when it may have changed.
     pub(crate) fn visible_rooms(&self) -> Box<dyn Iterator<Item = &Room> + '_> {
-        if let Some(query) = self.rooms_list.search_query() {
-            if !query.trim().is_empty() {
-                // Return search results (just the rooms, ignoring scores)
-                // Search only within the current filter
-                Box::new(
-                    self.cache
-                        .rooms_matching_search(query, self.rooms_list.filter())
-                        .map(|(room, _score)| room),
-                )
-            } else {
-                // Empty query - show all rooms with current filter
-                Box::new(self.cache.rooms.rooms_filtered_by(self.rooms_list.filter()))
+        let key = self.visible_rooms_key();
+        let is_outdated = self
+            .visible_rooms
+            .borrow()
+            .as_ref()
+            .is_none_or(|visible| visible.key != key);
+        if is_outdated {
+            let indices = self.filter_and_sort_rooms();
+            *self.visible_rooms.borrow_mut() = Some(VisibleRooms { key, indices });
+        }
+        let indices = self
+            .visible_rooms
+            .borrow()
+            .as_ref()
+            .map(|visible| visible.indices.clone())
+            .unwrap_or_default();
+        let rooms = self.cache.rooms.sorted_rooms();
+        Box::new(indices.into_iter().filter_map(|index| rooms.get(index)))
+    }
+
+    /// Returns what the visible rooms depend on, besides the favourites.
+    fn visible_rooms_key(&self) -> VisibleRoomsKey {
+        VisibleRoomsKey {
+            rooms: self.cache.rooms.generation(),
+            teams: self.cache.teams.generation(),
+            filter: self.rooms_list.filter().clone(),
+            search_query: self.rooms_list.search_query().cloned(),
+            thresholds: self.activity_thresholds,
+            minute: Utc::now().timestamp() / 60,
+        }
+    }
+
+    /// Returns the positions in the sorted rooms of the rooms visible with the current filter,
+    /// in the order they are shown.
+    fn filter_and_sort_rooms(&self) -> Vec<usize> {
+        let visible: Vec<&Room> = match self.rooms_list.search_query() {
+            // Search only within the current filter, ignoring scores
+            Some(query) if !query.trim().is_empty() => self
+                .cache
+                .rooms_matching_search(query, self.rooms_filtered_by(self.rooms_list.filter()))
+                .map(|(room, _score)| room)
+                .collect(),
+            // No search, or an empty query - show all rooms with current filter
+            _ => self.grouped_in_sections(self.rooms_filtered_by(self.rooms_list.filter())),
+        };
+        let index_of: HashMap<&RoomId, usize> = self
+            .cache
+            .rooms
+            .sorted_rooms()
+            .iter()
+            .enumerate()
+            .map(|(index, room)| (&room.id, index))
+            .collect();
+        visible
+            .into_iter()
+            .filter_map(|room| index_of.get(&room.id).copied())
+            .collect()
+    }
+
+    /// Returns an iterator over the rooms with the given filter, muted rooms last.
+    pub(crate) fn rooms_filtered_by<'s>(
+        &'s self,
+        filter: &'s RoomsListFilter,
+    ) -> impl Iterator<Item = &'s Room> {
+        self.cache
+            .rooms
+            .rooms_filtered_by(filter, &self.activity_thresholds, move |room| {
+                self.room_marks(room)
+            })
+    }
+
+    /// Returns the state of the room kept outside of it, used by the filters.
+    fn room_marks(&self, room: &Room) -> RoomMarks<'_> {
+        RoomMarks {
+            team_name: room
+                .team_id
+                .as_ref()
+                .and_then(|id| self.cache.teams.team_with_id(id))
+                .and_then(|team| team.name.as_deref()),
+            is_favourite: self.favourites.contains(&room.id),
+            is_muted: self.is_muted(&room.id),
+        }
+    }
+
+    /// Returns the rooms ordered by section, keeping their order within each section.
+    fn grouped_in_sections<'r>(&'r self, rooms: impl Iterator<Item = &'r Room>) -> Vec<&'r Room> {
+        let mut rooms: Vec<_> = rooms.collect();
+        if self.rooms_are_grouped() {
+            rooms.sort_by_cached_key(|room| self.room_section(room).0);
+        }
+        rooms
+    }
+
+    /// Sets the rooms pinned by the user.
+    pub(crate) fn set_favourites(&mut self, favourites: Favourites) {
+        self.favourites = favourites;
+        self.visible_rooms.take();
+    }
+
+    /// Returns whether the room is pinned as a favourite.
+    pub(crate) fn is_favourite(&self, room_id: &RoomId) -> bool {
+        self.favourites.contains(room_id)
+    }
+
+    /// Returns the mute of the room, if it is muted or snoozed now.
+    pub(crate) fn mute_of(&self, room_id: &RoomId) -> Option<Mute> {
+        self.mutes.get(room_id, Utc::now())
+    }
+
+    /// Returns whether the room is muted or snoozed now.
+    pub(crate) fn is_muted(&self, room_id: &RoomId) -> bool {
+        self.mute_of(room_id).is_some()
+    }
+
+    /// Mutes or snoozes the room. Its unread messages are considered read.
+    pub(crate) fn mute(&mut self, room_id: &RoomId, mute: Mute) {
+        self.visible_rooms.take();
+        self.mutes.mute(room_id, mute);
+        self.cache.rooms.mark_read(room_id);
+    }
+
+    /// Unmutes the room. Returns true if it was muted, a snooze that ended does not count.
+    /// The room is unread if there was activity while it was snoozed.
+    pub(crate) fn unmute(&mut self, room_id: &RoomId) -> bool {
+        self.visible_rooms.take();
+        let was_muted = self.is_muted(room_id);
+        if self.mutes.has_missed(room_id) {
+            self.cache.rooms.mark_unread(room_id);
+        }
+        self.mutes.unmute(room_id);
+        was_muted
+    }
+
+    /// Considers read a muted room found unread, e.g. from its read status on the server.
+    /// For a snoozed room, the activity is recorded to make it unread when the snooze ends.
+    pub(crate) fn hide_unread_of_muted_room(&mut self, room_id: &RoomId) {
+        let is_unread = self
+            .cache
+            .rooms
+            .room_with_id(room_id)
+            .is_some_and(|room| room.unread);
+        if is_unread && self.is_muted(room_id) {
+            self.mutes.record_missed(room_id, Utc::now());
+            self.cache.rooms.mark_read(room_id);
+        }
+    }
+
+    /// Ends the snoozes that are over, the rooms with activity meanwhile become unread.
+    fn end_snoozes(&mut self) {
+        let ended = self.mutes.take_ended_snoozes(Utc::now());
+        if ended.is_empty() {
+            return;
+        }
+        self.visible_rooms.take();
+        for (room_id, missed) in ended {
+            debug!("Snooze of room {room_id} ended");
+            if missed {
+                self.cache.rooms.mark_unread(&room_id);
             }
-        } else {
-            // No search active - use normal filtering
-            Box::new(self.cache.rooms.rooms_filtered_by(self.rooms_list.filter()))
+        }
+    }
+
+    /// Pins the room as a favourite, or unpins it if it is one.
+    /// Returns true if the room is now a favourite.
+    pub(crate) fn toggle_favourite(&mut self, room_id: &RoomId) -> bool {
+        self.visible_rooms.take();
+        self.favourites.toggle(room_id)
+    }
+
+    /// Returns whether the rooms list is grouped in sections, with favourites or sections
+    /// defined in the config. Search results are not grouped, they are sorted by relevance.
+    pub(crate) fn rooms_are_grouped(&self) -> bool {
+        let is_searching = self
+            .rooms_list
+            .search_query()
+            .is_some_and(|query| !query.trim().is_empty());
+        !is_searching && (!self.favourites.is_empty() || !self.config.sections.is_empty())
+    }
+
+    /// Returns the position of the section of a room in the rooms list, and its name:
+    /// favourites first, then the sections of the config in order, then the other rooms.
+    pub(crate) fn room_section(&self, room: &Room) -> (usize, &str) {
+        if self.is_favourite(&room.id) {
+            return (0, FAVOURITES_SECTION);
+        }
+        let team_name = room
+            .team_id
+            .as_ref()
+            .and_then(|id| self.cache.teams.team_with_id(id))
+            .and_then(|team| team.name.as_deref());
+        let sections = &self.config.sections;
+        match sections
+            .iter()
+            .position(|section| section.matches(room, team_name))
+        {
+            Some(index) => (index + 1, &sections[index].name),
+            None => (sections.len() + 1, OTHER_ROOMS_SECTION),
         }
     }
 
@@ -110,12 +389,27 @@ impl AppState<'_> {
 
     /// Returns the number of messages in the active room.
     pub(crate) fn num_messages_active_room(&self) -> usize {
+        self.displayed_messages_active_room().count()
+    }
+
+    /// Returns the messages of the active room as shown with the current thread view.
+    pub(crate) fn displayed_messages_active_room(
+        &self,
+    ) -> Box<dyn Iterator<Item = DisplayedMessage<'_>> + '_> {
         match self.rooms_list.active_room_id() {
-            Some(id) => self.cache.nb_messages_in_room(id),
-            None => 0,
+            Some(id) => self
+                .cache
+                .displayed_messages_in_room(id, self.messages_list.thread_view()),
+            None => Box::new(std::iter::empty()),
         }
     }
 
+    /// Returns the index of a message of the active room in the messages list, if it is shown.
+    pub(crate) fn index_of_displayed_message(&self, msg_id: &MessageId) -> Option<usize> {
+        self.displayed_messages_active_room()
+            .position(|displayed| displayed.message.id.as_ref() == Some(msg_id))
+    }
+
     /// Returns the `RoomId` of the room selection in the list.
     /// This is used to set the active room.
     pub(crate) fn id_of_selected_room(&self) -> Option<RoomId> {
@@ -134,15 +428,6 @@ impl AppState<'_> {
         }
     }
 
-    /// Mark the active room as being read.
-    /// Only local storage for now, this is not synced between multiple clients,
-    /// or multiple invocations of the same client.
-    pub(crate) fn mark_active_read(&mut self) {
-        if let Some(id) = self.id_of_selected_room() {
-            self.cache.rooms.mark_read(&id);
-        }
-    }
-
     /// Returns the active pane.
     pub(crate) fn active_pane(&self) -> &Option<ActivePane> {
         &self.active_pane
@@ -164,11 +449,135 @@ impl AppState<'_> {
 
     /// Updates the list of possible actions according to what can be done in the pane
     pub(crate) fn update_actions(&mut self, active_pane: Option<ActivePane>) {
+        // The send time prompt captures the keys until it is closed, printable keys are typed
+        if self.send_time_prompt.is_some() {
+            self.actions = vec![
+                Action::ScheduleMessage,
+                Action::CloseSendTimePrompt,
+                Action::Quit,
+            ]
+            .into();
+            return;
+        }
+        // The prompt captures the keys until it is closed, printable keys are typed
+        if self.prompt.is_some() {
+            self.actions = vec![Action::ConfirmPrompt, Action::ClosePrompt, Action::Quit].into();
+            return;
+        }
+        // An open picker captures the keys until it is closed
+        if let Some(picker) = &self.picker {
+            let mut actions = vec![
+                Action::PickerNext,
+                Action::PickerPrevious,
+                Action::PickerConfirm,
+            ];
+            match picker.kind() {
+                PickerKind::Attachments => actions.push(Action::DownloadAllAttachments),
+                PickerKind::Links => actions.push(Action::CopyLink),
+                PickerKind::Mentions
+                | PickerKind::Forward(..)
+                | PickerKind::People
+                | PickerKind::NewMember(_)
+                | PickerKind::Teams(_) => (),
+            }
+            actions.extend(vec![Action::PickerClose, Action::ToggleHelp, Action::Quit]);
+            self.actions = actions.into();
+            return;
+        }
+        // The card form captures the keys until it is closed, printable keys are typed
+        if self.card_form.is_some() {
+            self.actions = vec![
+                Action::NextCardField,
+                Action::PreviousCardField,
+                Action::ActivateCardField,
+                Action::CloseCardForm,
+                Action::Quit,
+            ]
+            .into();
+            return;
+        }
+        if self.show_bookmarks {
+            self.actions = vec![
+                Action::NextBookmark,
+                Action::PreviousBookmark,
+                Action::OpenBookmark,
+                Action::DeleteBookmark,
+                Action::CloseBookmarks,
+                Action::ToggleHelp,
+                Action::Quit,
+            ]
+            .into();
+            return;
+        }
+        if self.show_room_details {
+            let mut actions = vec![];
+            if self.can_manage_room() {
+                actions.extend(vec![Action::RenameRoom, Action::ToggleRoomLock]);
+            }
+            actions.extend(vec![
+                Action::CloseRoomDetails,
+                Action::ToggleHelp,
+                Action::Quit,
+            ]);
+            self.actions = actions.into();
+            return;
+        }
+        if self.show_members {
+            let mut actions = vec![Action::NextMember, Action::PreviousMember];
+            if self.can_manage_room() {
+                actions.extend(vec![Action::AddMember, Action::RemoveMember]);
+            }
+            actions.extend(vec![Action::CloseMembers, Action::ToggleHelp, Action::Quit]);
+            self.actions = actions.into();
+            return;
+        }
+        if self.show_outbox {
+            self.actions = vec![
+                Action::NextOutboxEntry,
+                Action::PreviousOutboxEntry,
+                Action::ResendOutboxEntry,
+                Action::DiscardOutboxEntry,
+                Action::CloseOutbox,
+                Action::ToggleHelp,
+                Action::Quit,
+            ]
+            .into();
+            return;
+        }
+        if self.show_scheduled {
+            self.actions = vec![
+                Action::NextScheduledMessage,
+                Action::PreviousScheduledMessage,
+                Action::EditScheduledMessage,
+                Action::CancelScheduledMessage,
+                Action::CloseScheduledMessages,
+                Action::ToggleHelp,
+                Action::Quit,
+            ]
+            .into();
+            return;
+        }
+        // The message search captures the keys until it is closed, printable keys are typed
+        if self.message_search.is_some() {
+            self.actions = vec![
+                Action::NextSearchResult,
+                Action::PreviousSearchResult,
+                Action::OpenSearchResult,
+                Action::SearchOlderMessages,
+                Action::EndMessageSearch,
+                Action::Quit,
+            ]
+            .into();
+            return;
+        }
         let actions = match &active_pane {
             Some(ActivePane::Compose) => {
                 vec![
                     Action::EndComposeMessage,
                     Action::SendMessage,
+                    Action::ToggleMarkdown,
+                    Action::OpenExternalEditor,
+                    Action::SendLater,
                     Action::NextPane,
                     Action::PreviousPane,
                     Action::ToggleDebug,
@@ -190,16 +599,56 @@ impl AppState<'_> {
                         Action::JumpToLastMessage,
                     ]);
                 }
+                if self.num_messages_active_room() > 0 {
+                    actions.push(Action::ToggleThreadsCollapsed);
+                    actions.push(Action::ShowLinks);
+                }
+                if self.messages_list.thread_view().focused().is_some() {
+                    actions.push(Action::LeaveThread);
+                }
                 if self.messages_list.has_selection() {
+                    if self.messages_list.thread_view().focused().is_none() {
+                        actions.push(Action::FocusThread);
+                        if self.messages_list.thread_view().is_collapsed() {
+                            actions.push(Action::ToggleThreadExpanded);
+                        }
+                    }
                     actions.push(Action::CopyMessage);
-                    actions.push(Action::RespondMessage);
                     actions.push(Action::UnselectMessage);
+                }
+                // Messages being sent cannot be referred to yet
+                if self.messages_list.has_selection() && !self.selected_message_is_local() {
+                    actions.push(Action::RespondMessage);
+                    actions.push(Action::ForwardMessage);
+                    actions.push(Action::ToggleBookmark);
+                    if self.selected_message_has_files() {
+                        actions.push(Action::ShowAttachments);
+                    }
+                    if self.selected_message_has_card_form() {
+                        actions.push(Action::FillCard);
+                    }
                     if self.selected_message_is_from_me().unwrap_or_default() {
                         actions.push(Action::EditSelectedMessage);
                         actions.push(Action::DeleteMessage);
                     }
                 }
+                if !self.schedule.is_empty() {
+                    actions.push(Action::ShowScheduledMessages);
+                }
+                if !self.outbox.is_empty() {
+                    actions.push(Action::ShowOutbox);
+                }
+                if !self.bookmarks.is_empty() {
+                    actions.push(Action::ShowBookmarks);
+                }
+                if self.active_room().is_some_and(Room::is_space) {
+                    actions.push(Action::ShowMembers);
+                }
+                if self.active_room().is_some() {
+                    actions.push(Action::ShowRoomDetails);
+                }
                 actions.extend(vec![
+                    Action::StartMessageSearch,
                     Action::DumpRoomContentToFile,
                     Action::NextPane,
                     Action::PreviousPane,
@@ -224,12 +673,13 @@ impl AppState<'_> {
                     ]
                 } else {
                     // Normal rooms mode or search filtering mode
-                    let common_actions = vec![
+                    let mut common_actions = vec![
                         Action::NextRoom,
                         Action::PreviousRoom,
                         Action::NextRoomFilter,
                         Action::PreviousRoomFilter,
                         Action::StartRoomSearch,
+                        Action::StartMessageSearch,
                         Action::ToggleRoomSelection,
                         Action::SelectAllVisibleRooms,
                         Action::InvertSelection,
@@ -242,12 +692,26 @@ impl AppState<'_> {
                         Action::ToggleHelp,
                         Action::ToggleLogs,
                         Action::ToggleRooms,
+                        Action::NewDirectChat,
+                        Action::NewSpace,
                         Action::Quit,
                     ];
+                    if !self.schedule.is_empty() {
+                        common_actions.push(Action::ShowScheduledMessages);
+                    }
+                    if !self.outbox.is_empty() {
+                        common_actions.push(Action::ShowOutbox);
+                    }
+                    if !self.bookmarks.is_empty() {
+                        common_actions.push(Action::ShowBookmarks);
+                    }
                     let selection_actions = vec![
                         Action::ComposeNewMessage,
                         Action::MarkRead,
                         Action::SendMessage,
+                        Action::ToggleFavourite,
+                        Action::ToggleMute,
+                        Action::SnoozeRoom,
                     ];
                     match self.rooms_list.has_selection() {
                         true => concat([selection_actions, common_actions]),
@@ -363,6 +827,24 @@ impl AppState<'_> {
 
     pub(crate) fn update_on_tick(&mut self) {
         self.update_actions(self.active_pane.clone());
+        self.cache.typing.remove_expired(Instant::now());
+        self.end_snoozes();
+    }
+
+    /// Returns who is typing in the active room, if anybody.
+    pub(crate) fn typing_status(&self) -> Option<String> {
+        let room_id = self.rooms_list.active_room_id()?;
+        let names: Vec<String> = self
+            .cache
+            .typing
+            .persons_typing_in(room_id, Instant::now())
+            .into_iter()
+            .map(|person_id| match self.cache.persons.get(person_id) {
+                Some(person) => person.display_name.clone(),
+                None => String::from("Someone"),
+            })
+            .collect();
+        typing_description(&names)
     }
 
     /// Returns the selected message, if there is one
@@ -374,7 +856,55 @@ impl AppState<'_> {
             .messages_list
             .selected_index()
             .ok_or(eyre!("No message selected in room {}", room_id))?;
-        self.cache.nth_message_in_room(index, &room_id)
+        self.displayed_messages_active_room()
+            .nth(index)
+            .map(|displayed| displayed.message)
+            .ok_or(eyre!("Message {} not found in room {}", index, room_id))
+    }
+
+    /// Returns whether the selected message is only shown locally, while it is being sent.
+    pub(crate) fn selected_message_is_local(&self) -> bool {
+        self.selected_message().is_ok_and(is_local_message)
+    }
+
+    /// Shows a message being sent by an outbox entry, until Webex returns it.
+    pub(crate) fn add_local_message(&mut self, outbox_id: OutboxId, msg_to_send: &MessageOut) {
+        // The first message to a person has no room yet
+        if msg_to_send.room_id.is_none() {
+            return;
+        }
+        let message = Message {
+            id: Some(local_message_id(outbox_id)),
+            room_id: msg_to_send.room_id.clone(),
+            parent_id: msg_to_send.parent_id.clone(),
+            text: msg_to_send.text.clone(),
+            markdown: msg_to_send.markdown.clone(),
+            person_id: self.cache.me.as_ref().map(|me| me.id.clone()),
+            person_email: self
+                .cache
+                .me
+                .as_ref()
+                .and_then(|me| me.emails.first().cloned()),
+            created: Some(Utc::now().to_rfc3339()),
+            ..Default::default()
+        };
+        if let Err(e) = self.cache.add_message(&message) {
+            warn!("Could not show the message being sent: {e}");
+        }
+    }
+
+    /// Removes the message shown while an outbox entry was sending it, if any.
+    pub(crate) fn remove_local_message(&mut self, outbox_id: OutboxId, command: &OutboxCommand) {
+        if let OutboxCommand::Send(MessageOut {
+            room_id: Some(room_id),
+            ..
+        }) = command
+        {
+            let local_id = local_message_id(outbox_id);
+            if let Err(e) = self.cache.delete_message(&local_id, room_id) {
+                debug!("No message shown for {local_id}: {e}");
+            }
+        }
     }
 
     /// Returns true if the selected message is from me.
@@ -383,6 +913,256 @@ impl AppState<'_> {
         Ok(self.cache.is_me(&message.person_id))
     }
 
+    /// Returns the query typed in the picker, if one is open.
+    pub(crate) fn picker_query(&self) -> Option<&str> {
+        self.picker.as_ref().map(|picker| picker.query())
+    }
+
+    /// Opens the picker, which captures the keys until it is closed.
+    pub(crate) fn open_picker(&mut self, picker: Picker) {
+        self.picker = Some(picker);
+        self.update_actions(self.active_pane.clone());
+    }
+
+    /// Closes the picker and returns it, if one was open.
+    pub(crate) fn close_picker(&mut self) -> Option<Picker> {
+        let picker = self.picker.take();
+        self.update_actions(self.active_pane.clone());
+        picker
+    }
+
+    /// Opens the message search, which captures the keys until it is closed.
+    pub(crate) fn open_message_search(&mut self) {
+        self.message_search = Some(MessageSearch::default());
+        self.update_actions(self.active_pane.clone());
+    }
+
+    /// Closes the message search and returns it, if one was open.
+    pub(crate) fn close_message_search(&mut self) -> Option<MessageSearch> {
+        let search = self.message_search.take();
+        self.update_actions(self.active_pane.clone());
+        search
+    }
+
+    /// Searches the cached messages again with the query of the message search, if it is open.
+    pub(crate) fn update_message_search(&mut self) {
+        let Some(query) = self
+            .message_search
+            .as_ref()
+            .map(|search| search.query().to_string())
+        else {
+            return;
+        };
+        let results: Vec<_> = self
+            .cache
+            .messages_matching_search(&query)
+            .into_iter()
+            .take(MAX_SEARCH_RESULTS)
+            .map(|(room_id, msg)| SearchResult {
+                room_id: room_id.clone(),
+                message_id: msg.id.clone().unwrap_or_default(),
+                room_title: self
+                    .cache
+                    .room_and_team_title(room_id)
+                    .map(|title| title.room_title)
+                    .unwrap_or_default(),
+                author: self.author_name(msg),
+                created: msg.created.clone().unwrap_or_default(),
+                excerpt: excerpt(&searchable_text(msg), &query),
+            })
+            .collect();
+        if let Some(search) = self.message_search.as_mut() {
+            search.set_results(results);
+        }
+    }
+
+    /// Returns the display name of the author of a message, or their email if the person
+    /// is not in cache.
+    pub(crate) fn author_name(&self, msg: &Message) -> String {
+        let person = msg
+            .person_id
+            .as_ref()
+            .and_then(|id| self.cache.persons.get(id));
+        match (person, &msg.person_email) {
+            (Some(person), _) => person.display_name.clone(),
+            (None, Some(email)) => email.clone(),
+            _ => String::from("Unknown"),
+        }
+    }
+
+    /// Returns a label for the attachment at `index` in a message, with its name if known.
+    pub(crate) fn attachment_label(&self, index: usize, url: &str) -> String {
+        match self.cache.attachments.get(url) {
+            Some(info) => info.description(),
+            None => format!("Attachment {}", index + 1),
+        }
+    }
+
+    /// Returns true if the selected message has attached files.
+    pub(crate) fn selected_message_has_files(&self) -> bool {
+        self.selected_message()
+            .map(|msg| msg.files.as_ref().is_some_and(|files| !files.is_empty()))
+            .unwrap_or_default()
+    }
+
+    /// Returns whether the selected message has a card with inputs or actions.
+    pub(crate) fn selected_message_has_card_form(&self) -> bool {
+        self.selected_message()
+            .map(|msg| CardForm::from_message(msg).is_some())
+            .unwrap_or_default()
+    }
+
+    /// Returns the members of the active room that can be mentioned, excluding self,
+    /// preceded by an item to mention everyone in spaces.
+    pub(crate) fn mention_items(&self) -> Vec<PickerItem> {
+        let Some(room) = self.active_room() else {
+            return vec![];
+        };
+        let mut items = vec![];
+        if room.is_space() {
+            items.push(PickerItem::new("all (everyone in the space)", MENTION_ALL));
+        }
+        let members = self.cache.members.get(&room.id).unwrap_or_default();
+        items.extend(
+            members
+                .iter()
+                .filter(|member| !self.cache.is_me(&Some(member.person_id.clone())))
+                .map(|member| {
+                    let label = match (&member.display_name, &member.person_email) {
+                        (Some(name), Some(email)) => format!("{name} <{email}>"),
+                        _ => member.name(),
+                    };
+                    PickerItem::new(label, &member.person_id)
+                }),
+        );
+        items
+    }
+
+    /// Returns the rooms matching the query to forward a message to, best match first.
+    pub(crate) fn forward_items(&self, query: &str) -> Vec<PickerItem> {
+        self.cache
+            .rooms_matching_search(query, self.rooms_filtered_by(&RoomsListFilter::All))
+            .filter_map(|(room, _)| {
+                let title = self.cache.room_and_team_title(&room.id).ok()?;
+                let label = match title.team_name {
+                    Some(team_name) => format!("{} ({team_name})", title.room_title),
+                    None => title.room_title,
+                };
+                Some(PickerItem::new(label, &room.id))
+            })
+            .collect()
+    }
+
+    /// Returns the known persons matching the query to chat with, best match first.
+    pub(crate) fn people_items(&self, query: &str) -> Vec<PickerItem> {
+        self.cache
+            .persons_matching_search(query)
+            .into_iter()
+            .map(|person| {
+                let label = match person.emails.first() {
+                    Some(email) => format!("{} <{email}>", person.display_name),
+                    None => person.display_name.clone(),
+                };
+                PickerItem::new(label, &person.id)
+            })
+            .collect()
+    }
+
+    /// Returns the teams a space can be created in, after the choice of no team.
+    pub(crate) fn team_items(&self) -> Vec<PickerItem> {
+        let no_team = PickerItem::new("No team", "");
+        let teams = self.cache.teams.teams().into_iter().map(|team| {
+            let name = team.name.clone().unwrap_or(team.id.clone());
+            PickerItem::new(name, &team.id)
+        });
+        std::iter::once(no_team).chain(teams).collect()
+    }
+
+    /// Returns the members of the active room, sorted by name, empty until they are retrieved.
+    pub(crate) fn active_room_members(&self) -> &[Member] {
+        self.rooms_list
+            .active_room_id()
+            .and_then(|id| self.cache.members.get(id))
+            .unwrap_or_default()
+    }
+
+    /// Returns the member selected in the members pane, if any.
+    pub(crate) fn selected_member(&self) -> Option<&Member> {
+        self.members_list
+            .selected_index()
+            .and_then(|index| self.active_room_members().get(index))
+    }
+
+    /// Returns the name of a member, as known by the persons cache if the membership has none.
+    pub(crate) fn member_name(&self, member: &Member) -> String {
+        match (
+            &member.display_name,
+            self.cache.persons.get(&member.person_id),
+        ) {
+            (None, Some(person)) => person.display_name.clone(),
+            _ => member.name(),
+        }
+    }
+
+    /// Returns the email of a member, as known by the persons cache if the membership has none.
+    pub(crate) fn member_email(&self, member: &Member) -> Option<String> {
+        member.person_email.clone().or_else(|| {
+            self.cache
+                .persons
+                .get(&member.person_id)
+                .and_then(|person| person.emails.first().cloned())
+        })
+    }
+
+    /// Returns true if we can change the active room and its members: it is a space, and
+    /// we are one of its moderators if it is moderated.
+    pub(crate) fn can_manage_room(&self) -> bool {
+        let Some(room) = self.active_room().filter(|room| room.is_space()) else {
+            return false;
+        };
+        !room.is_moderated()
+            || self.active_room_members().iter().any(|member| {
+                member.is_moderator && self.cache.is_me(&Some(member.person_id.clone()))
+            })
+    }
+
+    /// Returns the known persons matching the query who are not members of the active room,
+    /// best match first. A query that looks like an email can be added as is.
+    pub(crate) fn new_member_items(&self, query: &str) -> Vec<PickerItem> {
+        let members = self.active_room_members();
+        let mut items: Vec<_> = self
+            .cache
+            .persons_matching_search(query)
+            .into_iter()
+            .filter(|person| !members.iter().any(|m| m.person_id == person.id))
+            .filter_map(|person| {
+                let email = person.emails.first()?;
+                Some(PickerItem::new(
+                    format!("{} <{email}>", person.display_name),
+                    email,
+                ))
+            })
+            .collect();
+        let query = query.trim();
+        if query.contains('@') && !items.iter().any(|item| item.value == query) {
+            items.insert(0, PickerItem::new(query, query));
+        }
+        items
+    }
+
+    /// Opens the prompt, which captures the keys until it is closed.
+    pub(crate) fn open_prompt(&mut self, prompt: Prompt) {
+        self.prompt = Some(prompt);
+        self.update_actions(self.active_pane.clone());
+    }
+
+    /// Closes the prompt and returns it, if one was open.
+    pub(crate) fn close_prompt(&mut self) -> Option<Prompt> {
+        let prompt = self.prompt.take();
+        self.update_actions(self.active_pane.clone());
+        prompt
+    }
+
     /// Set the theme for the application
     #[allow(dead_code)]
     pub(crate) fn set_theme(&mut self, theme: Theme) {
@@ -404,18 +1184,100 @@ impl Default for AppState<'_> {
             .into(),
             active_pane: None,
             cache: Cache::default(),
+            drafts: Drafts::default(),
+            schedule: Schedule::default(),
+            outbox: Outbox::default(),
+            bookmarks: Bookmarks::default(),
+            favourites: Favourites::default(),
+            mutes: Mutes::default(),
             theme: Theme::default(),
             debug: false,
             is_loading: false,
+            rooms_loading_progress: None,
             last_frame_size: Rect::new(0, 0, 0, 0),
             log_state,
             messages_to_load: 10,
+            config: UserConfig::default(),
+            typing_notified: None,
+            people_search: None,
             message_editor: MessageEditor::default(),
             messages_list: MessagesList::new(),
+            message_search: None,
+            message_to_show: None,
+            card_form: None,
+            picker: None,
+            prompt: None,
             rooms_list: RoomsList::default(),
+            activity_thresholds: ActivityThresholds::default(),
+            visible_rooms: RefCell::default(),
+            send_time_prompt: None,
             show_help: true,
             show_logs: false,
             show_rooms: true,
+            show_scheduled: false,
+            show_outbox: false,
+            show_bookmarks: false,
+            show_members: false,
+            members_list: MembersList::default(),
+            show_room_details: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_visible_rooms_follow_changes() {
+        let mut state = AppState::default();
+        let ids = |state: &AppState| {
+            state
+                .visible_rooms()
+                .map(|room| room.id.clone())
+                .collect::<Vec<_>>()
+        };
+        for (id, minutes) in [("1", 1), ("2", 2)] {
+            state.cache.rooms.update_with_room(&Room {
+                id: id.to_string(),
+                last_activity: Utc::now() - chrono::Duration::minutes(minutes),
+                ..Default::default()
+            });
         }
+        assert_eq!(ids(&state), vec!["1", "2"]);
+        state.toggle_favourite(&"2".to_string());
+        assert_eq!(ids(&state), vec!["2", "1"]);
+        state.rooms_list.next_filter(&[]);
+        assert_eq!(state.rooms_list.filter(), &RoomsListFilter::Favourites);
+        assert_eq!(ids(&state), vec!["2"]);
+        state
+            .rooms_list
+            .set_search_query(Some("nothing like it".to_string()));
+        assert!(ids(&state).is_empty());
+    }
+
+    #[test]
+    fn test_activity_during_snooze_is_unread_after() {
+        let mut state = AppState::default();
+        let room_id = "room".to_string();
+        state.cache.rooms.update_with_room(&Room {
+            id: room_id.clone(),
+            last_activity: Utc::now(),
+            unread: true,
+            ..Default::default()
+        });
+        let until = Utc::now() + chrono::Duration::hours(1);
+        state.mute(&room_id, Mute::Until(until));
+        assert!(!state.cache.rooms.room_with_id(&room_id).unwrap().unread);
+        assert_eq!(state.rooms_filtered_by(&RoomsListFilter::Muted).count(), 1);
+
+        // The server tells the room was not read
+        let last_seen = Utc::now() - chrono::Duration::hours(1);
+        state.cache.rooms.set_read_status(&room_id, None, last_seen);
+        state.hide_unread_of_muted_room(&room_id);
+        assert!(!state.cache.rooms.room_with_id(&room_id).unwrap().unread);
+
+        assert!(state.unmute(&room_id));
+        assert!(state.cache.rooms.room_with_id(&room_id).unwrap().unread);
     }
 }
@@ -0,0 +1,406 @@
+// app/vim.rs
+
+//! Vim-style modal editing of the message being composed.
+
+use std::fmt::{self, Display};
+use tui_textarea::{CursorMove, Input, Key, TextArea};
+
+/// Largest count accepted before a command, to bound the repetitions.
+const MAX_COUNT: usize = 999;
+
+/// Editing mode, messages are composed in insert mode.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum VimMode {
+    Normal,
+    #[default]
+    Insert,
+    Visual,
+    /// An operator (`d`, `c` or `y`) waiting for a motion
+    Operator(char),
+}
+
+impl Display for VimMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VimMode::Normal => write!(f, "NORMAL"),
+            VimMode::Insert => write!(f, "INSERT"),
+            VimMode::Visual => write!(f, "VISUAL"),
+            VimMode::Operator(op) => write!(f, "NORMAL {op}"),
+        }
+    }
+}
+
+/// State of the modal editing: the mode and the keys typed for a command.
+#[derive(Debug, Default)]
+pub(crate) struct Vim {
+    mode: VimMode,
+    /// Count typed before a command, e.g. `3` in `3w`
+    count: Option<usize>,
+    /// Count typed before the pending operator, e.g. `2` in `2d3w`
+    operator_count: usize,
+    /// Whether a first `g` was typed, for `gg`
+    pending_g: bool,
+}
+
+impl Vim {
+    pub(crate) fn mode(&self) -> VimMode {
+        self.mode
+    }
+
+    /// Returns whether `Esc` is used by the editor, to leave a mode or cancel a command.
+    /// In normal mode without a pending command, it is left to the app.
+    pub(crate) fn captures_escape(&self) -> bool {
+        self.mode != VimMode::Normal || self.count.is_some() || self.pending_g
+    }
+
+    /// Handles a key in the current mode, returns whether the text was modified.
+    pub(crate) fn input(&mut self, input: Input, textarea: &mut TextArea) -> bool {
+        if self.mode == VimMode::Insert {
+            return self.insert_input(input, textarea);
+        }
+        let before = textarea.lines().to_vec();
+        self.command_input(input, textarea);
+        textarea.lines() != before.as_slice()
+    }
+
+    /// Types text in insert mode, `Esc` goes back to normal mode.
+    fn insert_input(&mut self, input: Input, textarea: &mut TextArea) -> bool {
+        if input.key != Key::Esc {
+            return textarea.input(input);
+        }
+        self.mode = VimMode::Normal;
+        // As in vim, the cursor goes back onto the last character typed
+        if textarea.cursor().1 > 0 {
+            textarea.move_cursor(CursorMove::Back);
+        }
+        false
+    }
+
+    /// Handles a key in normal, visual or operator pending mode.
+    fn command_input(&mut self, input: Input, textarea: &mut TextArea) {
+        let Input { key, ctrl, .. } = input;
+        if let Key::Char(c @ '0'..='9') = key {
+            // A leading 0 moves to the start of the line instead
+            if !ctrl && (c != '0' || self.count.is_some()) {
+                let digit = c.to_digit(10).unwrap_or_default() as usize;
+                self.count = Some((self.count.unwrap_or_default() * 10 + digit).min(MAX_COUNT));
+                return;
+            }
+        }
+        let mut count = self.count.take().unwrap_or(1);
+        if let VimMode::Operator(_) = self.mode {
+            count = (count * self.operator_count).min(MAX_COUNT);
+        }
+        let pending_g = std::mem::take(&mut self.pending_g);
+
+        if self.move_cursor(key, ctrl, count, pending_g, textarea) {
+            self.apply_operator(textarea);
+            return;
+        }
+        match (self.mode, key, ctrl) {
+            (_, Key::Char('g'), false) if !pending_g => {
+                self.pending_g = true;
+            }
+            (VimMode::Normal, Key::Esc, _) => (),
+            (VimMode::Visual | VimMode::Operator(_), Key::Esc, _)
+            | (VimMode::Visual, Key::Char('v'), false) => {
+                textarea.cancel_selection();
+                self.mode = VimMode::Normal;
+            }
+            (VimMode::Operator(op), Key::Char(c), false) if c == op => {
+                // `dd`, `cc` and `yy` act on whole lines
+                textarea.cancel_selection();
+                textarea.move_cursor(CursorMove::Head);
+                textarea.start_selection();
+                if op == 'c' {
+                    // The lines are replaced, so the last line break is kept
+                    for _ in 1..count {
+                        textarea.move_cursor(CursorMove::Down);
+                    }
+                    textarea.move_cursor(CursorMove::End);
+                } else {
+                    for _ in 0..count {
+                        let cursor = textarea.cursor();
+                        textarea.move_cursor(CursorMove::Down);
+                        if textarea.cursor() == cursor {
+                            // On the last line, select up to its end instead
+                            textarea.move_cursor(CursorMove::End);
+                            break;
+                        }
+                    }
+                }
+                self.apply_operator(textarea);
+            }
+            (VimMode::Normal, Key::Char(op @ ('d' | 'c' | 'y')), false) => {
+                textarea.start_selection();
+                self.operator_count = count;
+                self.mode = VimMode::Operator(op);
+            }
+            (VimMode::Visual, Key::Char(op @ ('d' | 'c' | 'y' | 'x')), false) => {
+                // The selection includes the character under the cursor
+                textarea.move_cursor(CursorMove::Forward);
+                self.mode = VimMode::Operator(if op == 'x' { 'd' } else { op });
+                self.apply_operator(textarea);
+            }
+            (VimMode::Normal, Key::Char('v'), false) => {
+                textarea.start_selection();
+                self.mode = VimMode::Visual;
+            }
+            (VimMode::Normal, Key::Char('V'), false) => {
+                textarea.move_cursor(CursorMove::Head);
+                textarea.start_selection();
+                textarea.move_cursor(CursorMove::End);
+                self.mode = VimMode::Visual;
+            }
+            (VimMode::Normal, Key::Char(c), false) => self.normal_command(c, count, textarea),
+            (VimMode::Normal, Key::Delete, _) => self.normal_command('x', count, textarea),
+            (VimMode::Normal, Key::Char('r'), true) => {
+                for _ in 0..count {
+                    textarea.redo();
+                }
+            }
+            (VimMode::Operator(_), _, _) => {
+                // Not a motion, the operator is cancelled
+                textarea.cancel_selection();
+                self.mode = VimMode::Normal;
+            }
+            _ => (),
+        }
+    }
+
+    /// Moves the cursor if the key is a motion, returns whether it was.
+    fn move_cursor(
+        &self,
+        key: Key,
+        ctrl: bool,
+        count: usize,
+        pending_g: bool,
+        textarea: &mut TextArea,
+    ) -> bool {
+        if ctrl {
+            return false;
+        }
+        let motion = match key {
+            Key::Char('h') | Key::Left => CursorMove::Back,
+            Key::Char('l') | Key::Right => CursorMove::Forward,
+            Key::Char('j') | Key::Down => CursorMove::Down,
+            Key::Char('k') | Key::Up => CursorMove::Up,
+            // `cw` changes up to the end of the word, like `ce`
+            Key::Char('w') if self.mode == VimMode::Operator('c') => CursorMove::WordEnd,
+            Key::Char('w') => CursorMove::WordForward,
+            Key::Char('b') => CursorMove::WordBack,
+            Key::Char('e') => CursorMove::WordEnd,
+            Key::Char('0' | '^') | Key::Home => CursorMove::Head,
+            Key::Char('$') | Key::End => CursorMove::End,
+            Key::Char('g') if pending_g => CursorMove::Top,
+            Key::Char('G') => CursorMove::Bottom,
+            _ => return false,
+        };
+        let repeat = match motion {
+            CursorMove::Head | CursorMove::End | CursorMove::Top | CursorMove::Bottom => 1,
+            _ => count,
+        };
+        for _ in 0..repeat {
+            textarea.move_cursor(motion);
+        }
+        if matches!(key, Key::Char('e' | 'w')) && matches!(self.mode, VimMode::Operator(_)) {
+            if let CursorMove::WordEnd = motion {
+                // The operator includes the end of the word
+                textarea.move_cursor(CursorMove::Forward);
+            }
+        }
+        true
+    }
+
+    /// Applies the pending operator to the text between the start of the selection and the
+    /// cursor, then leaves the operator pending mode.
+    fn apply_operator(&mut self, textarea: &mut TextArea) {
+        match self.mode {
+            VimMode::Operator('y') => {
+                textarea.copy();
+                textarea.cancel_selection();
+                self.mode = VimMode::Normal;
+            }
+            VimMode::Operator('d') => {
+                textarea.cut();
+                self.mode = VimMode::Normal;
+            }
+            VimMode::Operator(_) => {
+                textarea.cut();
+                self.mode = VimMode::Insert;
+            }
+            _ => (),
+        }
+    }
+
+    /// Runs a command of normal mode that is neither a motion nor an operator.
+    fn normal_command(&mut self, c: char, count: usize, textarea: &mut TextArea) {
+        let (row, col) = textarea.cursor();
+        let line_len = textarea.lines()[row].chars().count();
+        match c {
+            'i' => self.mode = VimMode::Insert,
+            'a' => {
+                if col < line_len {
+                    textarea.move_cursor(CursorMove::Forward);
+                }
+                self.mode = VimMode::Insert;
+            }
+            'A' => {
+                textarea.move_cursor(CursorMove::End);
+                self.mode = VimMode::Insert;
+            }
+            'I' => {
+                textarea.move_cursor(CursorMove::Head);
+                self.mode = VimMode::Insert;
+            }
+            'o' => {
+                textarea.move_cursor(CursorMove::End);
+                textarea.insert_newline();
+                self.mode = VimMode::Insert;
+            }
+            'O' => {
+                textarea.move_cursor(CursorMove::Head);
+                textarea.insert_newline();
+                textarea.move_cursor(CursorMove::Up);
+                self.mode = VimMode::Insert;
+            }
+            'x' | 's' => {
+                // Characters are only deleted up to the end of the line
+                for _ in 0..count.min(line_len.saturating_sub(col)) {
+                    textarea.delete_next_char();
+                }
+                if c == 's' {
+                    self.mode = VimMode::Insert;
+                }
+            }
+            'D' | 'C' => {
+                textarea.delete_line_by_end();
+                if c == 'C' {
+                    self.mode = VimMode::Insert;
+                }
+            }
+            'p' | 'P' => {
+                // `p` puts the text after the character under the cursor
+                if c == 'p' && col < line_len {
+                    textarea.move_cursor(CursorMove::Forward);
+                }
+                for _ in 0..count {
+                    textarea.paste();
+                }
+            }
+            'u' => {
+                for _ in 0..count {
+                    textarea.undo();
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    /// Returns the text and cursor after typing the keys in normal mode, from the start of
+    /// the text. `<` stands for Esc.
+    fn type_keys(text: &str, keys: &str) -> (String, (usize, usize), VimMode) {
+        let mut textarea = TextArea::new(text.split('\n').map(str::to_string).collect());
+        let mut vim = Vim {
+            mode: VimMode::Normal,
+            ..Default::default()
+        };
+        for c in keys.chars() {
+            let key = match c {
+                '<' => Key::Esc,
+                c => Key::Char(c),
+            };
+            vim.input(
+                Input {
+                    key,
+                    ..Default::default()
+                },
+                &mut textarea,
+            );
+        }
+        (textarea.lines().join("\n"), textarea.cursor(), vim.mode())
+    }
+
+    #[rstest(
+        keys,
+        expected_cursor,
+        case("w", (0, 4)),
+        case("2w", (0, 8)),
+        case("$", (0, 15)),
+        case("$0", (0, 0)),
+        case("G", (1, 0)),
+        case("Ggg", (0, 0)),
+        case("jk", (0, 0)),
+        case("3l", (0, 3)),
+        // Unknown commands do not insert text
+        case("zQ!", (0, 0))
+    )]
+    fn test_motions(keys: &str, expected_cursor: (usize, usize)) {
+        let (text, cursor, mode) = type_keys("one two three 4\nfive", keys);
+        assert_eq!(text, "one two three 4\nfive");
+        assert_eq!(cursor, expected_cursor);
+        assert_eq!(mode, VimMode::Normal);
+    }
+
+    #[rstest(
+        keys,
+        expected_text,
+        expected_mode,
+        case("dw", "two three\nfour", VimMode::Normal),
+        case("2dw", "three\nfour", VimMode::Normal),
+        case("d2w", "three\nfour", VimMode::Normal),
+        case("de", " two three\nfour", VimMode::Normal),
+        case("cwONE<", "ONE two three\nfour", VimMode::Normal),
+        case("dd", "four", VimMode::Normal),
+        case("2dd", "", VimMode::Normal),
+        case("yyjP", "one two three\none two three\nfour", VimMode::Normal),
+        case("wvey$p", "one two threetwo\nfour", VimMode::Normal),
+        case("wvd", "one wo three\nfour", VimMode::Normal),
+        case("3x", " two three\nfour", VimMode::Normal),
+        case("D", "\nfour", VimMode::Normal),
+        case("AX<", "one two threeX\nfour", VimMode::Normal),
+        case("oX<", "one two three\nX\nfour", VimMode::Normal),
+        case("ddu", "one two three\nfour", VimMode::Normal),
+        case("d<", "one two three\nfour", VimMode::Normal),
+        case("cc", "\nfour", VimMode::Insert)
+    )]
+    fn test_edits(keys: &str, expected_text: &str, expected_mode: VimMode) {
+        let (text, _, mode) = type_keys("one two three\nfour", keys);
+        assert_eq!(text, expected_text);
+        assert_eq!(mode, expected_mode);
+    }
+
+    #[test]
+    fn test_redo() {
+        let mut textarea = TextArea::new(vec!["one two".to_string()]);
+        let mut vim = Vim::default();
+        let key = |c| Input {
+            key: Key::Char(c),
+            ..Default::default()
+        };
+        vim.input(
+            Input {
+                key: Key::Esc,
+                ..Default::default()
+            },
+            &mut textarea,
+        );
+        assert!(!vim.captures_escape());
+        assert!(vim.input(key('x'), &mut textarea));
+        vim.input(key('u'), &mut textarea);
+        assert_eq!(textarea.lines(), ["one two"]);
+        let redo = Input {
+            key: Key::Char('r'),
+            ctrl: true,
+            ..Default::default()
+        };
+        assert!(vim.input(redo, &mut textarea));
+        assert_eq!(textarea.lines(), ["ne two"]);
+    }
+}
@@ -2,6 +2,7 @@
 
 // credits: iRigellute/spotify-tui
 
+use chrono::{Duration, Utc};
 use color_eyre::eyre::{eyre, Error, Result};
 use log::{info, warn};
 use serde::{Deserialize, Serialize};
@@ -11,6 +12,9 @@ use std::{
     path::{Path, PathBuf},
 };
 
+use crate::app::cache::room_list_filter::{ActivityThresholds, CustomFilter};
+use crate::app::sections::RoomSection;
+
 const DEFAULT_PORT: u16 = 8080;
 const FILE_NAME: &str = "client.yml";
 const USER_FILE_NAME: &str = "config.yml";
@@ -24,6 +28,22 @@ fn default_theme_name() -> String {
 fn default_messages_to_load() -> u32 {
     10
 }
+
+fn default_forward_header() -> bool {
+    true
+}
+
+fn default_mentions_break_mute() -> bool {
+    true
+}
+
+fn default_recent_filter_hours() -> u32 {
+    24
+}
+
+fn default_inactive_filter_days() -> u32 {
+    365
+}
 // const TOKEN_CACHE_FILE: &str = ".webex_token_cache.json";
 
 #[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -46,6 +66,36 @@ pub(crate) struct UserConfig {
     /// Enable debug logging by default
     #[serde(default)]
     pub(crate) debug: bool,
+    /// Directory where downloaded attachments are saved
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) download_dir: Option<PathBuf>,
+    /// Send messages as markdown by default, can be toggled while composing
+    #[serde(default)]
+    pub(crate) markdown: bool,
+    /// Let the room members know when we are typing
+    #[serde(default)]
+    pub(crate) send_typing: bool,
+    /// Edit messages with vim-style normal, insert and visual modes
+    #[serde(default)]
+    pub(crate) vim_mode: bool,
+    /// Start forwarded messages with their author and room
+    #[serde(default = "default_forward_header")]
+    pub(crate) forward_header: bool,
+    /// Mark muted rooms unread when we are mentioned
+    #[serde(default = "default_mentions_break_mute")]
+    pub(crate) mentions_break_mute: bool,
+    /// Sections grouping the rooms in the list, below the favourites
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) sections: Vec<RoomSection>,
+    /// Rooms with activity in the last hours are shown by the Recent filter
+    #[serde(default = "default_recent_filter_hours")]
+    pub(crate) recent_filter_hours: u32,
+    /// Spaces without activity for days are shown by the InactiveSpaces filter
+    #[serde(default = "default_inactive_filter_days")]
+    pub(crate) inactive_filter_days: u32,
+    /// Filters of the rooms list, after the built-in ones
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) room_filters: Vec<CustomFilter>,
 }
 
 impl Default for UserConfig {
@@ -54,6 +104,16 @@ impl Default for UserConfig {
             theme: default_theme_name(),
             messages_to_load: default_messages_to_load(),
             debug: false,
+            download_dir: None,
+            markdown: false,
+            send_typing: false,
+            vim_mode: false,
+            forward_header: default_forward_header(),
+            mentions_break_mute: default_mentions_break_mute(),
+            sections: vec![],
+            recent_filter_hours: default_recent_filter_hours(),
+            inactive_filter_days: default_inactive_filter_days(),
+            room_filters: vec![],
         }
     }
 }
@@ -228,10 +288,12 @@ impl UserConfig {
         // Try environment variable first (nix can set this)
         if let Ok(config_path) = env::var("WEBEX_TUI_CONFIG") {
             info!("Loading user config from WEBEX_TUI_CONFIG: {config_path}");
-            if let Ok(config) = Self::load_from_file(std::path::Path::new(&config_path)) {
-                return config;
+            match Self::load_from_file(std::path::Path::new(&config_path)) {
+                Ok(config) => return config,
+                Err(e) => warn!(
+                    "Failed to load config from WEBEX_TUI_CONFIG, trying default location: {e}"
+                ),
             }
-            warn!("Failed to load config from WEBEX_TUI_CONFIG, trying default location");
         }
 
         // Try standard location
@@ -267,8 +329,9 @@ impl UserConfig {
                 return default_config;
             }
 
-            if let Ok(config) = Self::load_from_file(&config_path) {
-                return config;
+            match Self::load_from_file(&config_path) {
+                Ok(config) => return config,
+                Err(e) => warn!("Invalid user config, using defaults: {e}"),
             }
         }
 
@@ -281,9 +344,46 @@ impl UserConfig {
     fn load_from_file(path: &Path) -> color_eyre::Result<Self> {
         let content = fs::read_to_string(path)?;
         let config: UserConfig = serde_yaml::from_str(&content)?;
+        config.validate()?;
         Ok(config)
     }
 
+    /// Checks the values that cannot be checked by their type.
+    fn validate(&self) -> Result<()> {
+        self.activity_thresholds()?;
+        for filter in &self.room_filters {
+            filter
+                .validate()
+                .map_err(|e| eyre!("Room filter {}: {e}", filter.name))?;
+        }
+        Ok(())
+    }
+
+    /// Returns the directory where attachments are downloaded.
+    /// Defaults to the user's download directory, or the home directory if there is none.
+    pub(crate) fn download_dir(&self) -> PathBuf {
+        self.download_dir
+            .clone()
+            .or_else(dirs::download_dir)
+            .or_else(dirs::home_dir)
+            .unwrap_or_else(|| PathBuf::from("."))
+    }
+
+    /// Returns how long without activity rooms are recent or inactive for the built-in filters.
+    /// Fails if a duration is too long to go back in time from now.
+    pub(crate) fn activity_thresholds(&self) -> Result<ActivityThresholds> {
+        Ok(ActivityThresholds {
+            recent: config_duration(
+                "recent_filter_hours",
+                Duration::try_hours(self.recent_filter_hours.into()),
+            )?,
+            inactive: config_duration(
+                "inactive_filter_days",
+                Duration::try_days(self.inactive_filter_days.into()),
+            )?,
+        })
+    }
+
     /// Save user configuration to the standard location
     pub(crate) fn save(&self) -> color_eyre::Result<()> {
         if let Some(home) = dirs::home_dir() {
@@ -304,6 +404,13 @@ impl UserConfig {
     }
 }
 
+/// Returns a duration set in the config, if it is possible to go that far back from now.
+pub(crate) fn config_duration(name: &str, duration: Option<Duration>) -> Result<Duration> {
+    duration
+        .filter(|duration| Utc::now().checked_sub_signed(*duration).is_some())
+        .ok_or_else(|| eyre!("{name} is too large"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -314,6 +421,92 @@ mod tests {
         assert_eq!(config.theme, "default");
         assert_eq!(config.messages_to_load, 10);
         assert!(!config.debug);
+        assert_eq!(config.download_dir, None);
+        assert!(!config.markdown);
+        assert!(!config.send_typing);
+        assert!(config.forward_header);
+        assert!(config.mentions_break_mute);
+        assert!(config.sections.is_empty());
+        assert_eq!(
+            config.activity_thresholds().unwrap(),
+            ActivityThresholds::default()
+        );
+        assert!(config.room_filters.is_empty());
+    }
+
+    #[test]
+    fn test_user_config_room_filters() {
+        let yaml = r#"
+recent_filter_hours: 48
+room_filters:
+  - name: Work
+    teams: [Engineering]
+    unread: true
+  - name: Standups
+    title_regex: "(?i)standup"
+"#;
+        let config: UserConfig = serde_yaml::from_str(yaml).expect("Failed to deserialize");
+        assert_eq!(
+            config.activity_thresholds().unwrap().recent,
+            Duration::hours(48)
+        );
+        assert_eq!(config.inactive_filter_days, 365);
+        assert_eq!(config.room_filters.len(), 2);
+        assert_eq!(config.room_filters[0].name, "Work");
+        assert_eq!(config.room_filters[0].unread, Some(true));
+        assert!(config.room_filters[1].title_regex.is_some());
+    }
+
+    #[test]
+    fn test_user_config_durations_out_of_range() {
+        let config: UserConfig = serde_yaml::from_str("inactive_filter_days: 4000000000")
+            .expect("Failed to deserialize");
+        assert!(config.validate().is_err());
+        let yaml = r#"
+room_filters:
+  - name: Active
+    active_within_hours: 4000000000
+"#;
+        let config: UserConfig = serde_yaml::from_str(yaml).expect("Failed to deserialize");
+        assert!(config.validate().is_err());
+        assert!(UserConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_user_config_sections() {
+        let yaml = r#"
+sections:
+  - name: Team
+    teams: [Engineering]
+  - name: Standups
+    title_pattern: "*standup*"
+    rooms: [room-id]
+"#;
+        let config: UserConfig = serde_yaml::from_str(yaml).expect("Failed to deserialize");
+        assert_eq!(config.sections.len(), 2);
+        assert_eq!(config.sections[0].name, "Team");
+        assert_eq!(config.sections[0].teams, vec!["Engineering"]);
+        assert_eq!(
+            config.sections[1].title_pattern.as_deref(),
+            Some("*standup*")
+        );
+        assert_eq!(config.sections[1].rooms, vec!["room-id"]);
+    }
+
+    #[test]
+    fn test_user_config_download_dir() {
+        let yaml = "download_dir: /tmp/webex-downloads\n";
+        let config: UserConfig = serde_yaml::from_str(yaml).expect("Failed to deserialize");
+        assert_eq!(config.download_dir(), PathBuf::from("/tmp/webex-downloads"));
+        // Other values keep their defaults
+        assert_eq!(config.messages_to_load, 10);
+    }
+
+    #[test]
+    fn test_user_config_markdown() {
+        let yaml = "markdown: true\n";
+        let config: UserConfig = serde_yaml::from_str(yaml).expect("Failed to deserialize");
+        assert!(config.markdown);
     }
 
     #[test]
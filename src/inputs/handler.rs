@@ -23,6 +23,8 @@ pub(crate) struct EventHandler {
     _tx: tokio::sync::mpsc::Sender<Event>,
     // To stop the loop
     stop_capture: Arc<AtomicBool>,
+    // To leave the terminal input to another program
+    pause_capture: Arc<AtomicBool>,
 }
 
 impl EventHandler {
@@ -31,13 +33,22 @@ impl EventHandler {
     pub(crate) fn new(tick_rate: Duration) -> Self {
         let (tx, rx) = tokio::sync::mpsc::channel(100);
         let stop_capture = Arc::new(AtomicBool::new(false));
+        let pause_capture = Arc::new(AtomicBool::new(false));
 
         let event_tx = tx.clone();
         let event_stop_capture = stop_capture.clone();
+        let event_pause_capture = pause_capture.clone();
         tokio::spawn(async move {
             loop {
+                if event_pause_capture.load(Ordering::Relaxed) {
+                    tokio::time::sleep(tick_rate).await;
+                    continue;
+                }
                 // poll for tick rate duration, if no event, sent tick event.
-                if crossterm::event::poll(tick_rate).unwrap() {
+                // Events arriving after a pause are left to the program using the terminal.
+                if crossterm::event::poll(tick_rate).unwrap()
+                    && !event_pause_capture.load(Ordering::Relaxed)
+                {
                     if let crossterm::event::Event::Key(key_event) =
                         crossterm::event::read().unwrap()
                     {
@@ -59,6 +70,7 @@ impl EventHandler {
             rx,
             _tx: tx,
             stop_capture,
+            pause_capture,
         }
     }
 
@@ -71,4 +83,14 @@ impl EventHandler {
     pub(crate) fn close(&mut self) {
         self.stop_capture.store(true, Ordering::Relaxed)
     }
+
+    /// Stops reading the terminal input until `resume` is called.
+    pub(crate) fn pause(&mut self) {
+        self.pause_capture.store(true, Ordering::Relaxed)
+    }
+
+    /// Reads the terminal input again after a pause.
+    pub(crate) fn resume(&mut self) {
+        self.pause_capture.store(false, Ordering::Relaxed)
+    }
 }
@@ -50,6 +50,8 @@ pub(crate) fn setup_logger(
 pub(crate) fn crate_modules() -> &'static [&'static str] {
     &[
         "webex_tui::app::actions",
+        "webex_tui::app::cache::attachments",
+        "webex_tui::app::cache::members",
         "webex_tui::app::cache::msg_thread",
         "webex_tui::app::cache::room_and_team_title",
         "webex_tui::app::cache::room_content",
@@ -59,8 +61,10 @@ pub(crate) fn crate_modules() -> &'static [&'static str] {
         "webex_tui::app::cache::teams",
         "webex_tui::app::cache",
         "webex_tui::app::callbacks",
+        "webex_tui::app::files",
         "webex_tui::app::mesage_editor",
         "webex_tui::app::messages_list",
+        "webex_tui::app::picker",
         "webex_tui::app::rooms_list",
         "webex_tui::app::state",
         "webex_tui::app",
@@ -69,6 +73,7 @@ pub(crate) fn crate_modules() -> &'static [&'static str] {
         "webex_tui::teams::webex_handler",
         "webex_tui::teams:auth",
         "webex_tui::teams:client",
+        "webex_tui::teams::rest",
         "webex_tui::teams",
         "webex_tui::tui",
         "webex_tui",
@@ -153,16 +153,16 @@ async fn main() -> Result<()> {
     let (app_to_teams_tx_highpri, app_to_teams_rx_highpri) =
         tokio::sync::mpsc::unbounded_channel::<AppCmdEvent>();
     // CLI args override config values
-    let debug = matches.get_flag("debug") || user_config.debug;
-    let messages_to_load = *matches
+    let mut user_config = user_config;
+    user_config.debug = matches.get_flag("debug") || user_config.debug;
+    user_config.messages_to_load = *matches
         .get_one("messages")
         .unwrap_or(&user_config.messages_to_load);
 
     let app = Arc::new(tokio::sync::Mutex::new(App::new(
         app_to_teams_tx_lowpri.clone(),
         app_to_teams_tx_highpri.clone(),
-        debug,
-        messages_to_load,
+        user_config,
         theme,
     )));
     let app_ui = Arc::clone(&app);
@@ -187,16 +187,25 @@ async fn main() -> Result<()> {
 
         // Handle terminal inputs
         let event = tui.events.next().await;
-        {
+        let result = {
             let mut app = app_ui.lock().await;
-            let result = match event {
+            match event {
                 Event::Input(key_event) => app.process_key_event(key_event).await,
                 Event::Tick => app.update_on_tick().await,
-            };
-            if result == AppReturn::Exit {
+            }
+        };
+        match result {
+            AppReturn::Exit => {
+                app_ui.lock().await.save_drafts();
                 tui.events.close();
                 break;
             }
+            // The app is not locked while editing, so that messages keep arriving
+            AppReturn::EditExternally(content) => {
+                let edited = tui.edit_externally(content).await;
+                app_ui.lock().await.load_externally_edited(edited);
+            }
+            AppReturn::Continue => (),
         }
     }
 
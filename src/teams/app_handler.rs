@@ -5,32 +5,52 @@
 //! Callbacks to the `App` are made via mutex.
 
 use super::Teams;
+use crate::app::cache::attachments::AttachmentUrl;
+use crate::app::cache::members::MembershipId;
 use crate::app::cache::persons::PersonId;
 use crate::app::cache::room::RoomId;
 use crate::app::cache::teams::TeamId;
 use crate::app::cache::MessageId;
+use crate::app::files::unique_file_path;
+use crate::app::outbox::{OutboxCommand, OutboxId};
 use crate::app::state::ActivePane;
 use color_eyre::eyre::{eyre, Result};
 use log::*;
+use reqwest::StatusCode;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
 use webex::{
-    GlobalId, GlobalIdType, Message, MessageEditParams, MessageListParams, MessageOut,
-    RoomListParams, SortRoomsBy,
+    error::Error as WebexError, GlobalId, GlobalIdType, Message, MessageEditParams,
+    MessageListParams, MessageOut,
 };
 
 /// Commands the main `App` can send to the `Teams` thread.
 #[derive(Debug, Clone)]
 pub(crate) enum AppCmdEvent {
-    DeleteMessage(MessageId),
-    EditMessage(MessageId, RoomId, String),
+    AcknowledgeMessage(RoomId, MessageId),
+    AddMember(RoomId, String), // room and email of the person to add
+    CreateSpace(String, Option<TeamId>, Vec<String>), // title, optional team and member emails
+    DownloadAttachment(AttachmentUrl, PathBuf), // url and destination directory
+    Deliver(OutboxId, OutboxCommand),
     Initialize(),
     ListAllRooms(),
     ListMessagesInRoom(RoomId, Option<MessageId>, u32),
-    SendMessage(MessageOut),
+    OpenDirectRoom(PersonId),
+    RemoveMember(RoomId, MembershipId),
+    SearchPeople(String),
+    SetTyping(RoomId, bool), // whether we are typing in the room
+    SubmitCard(MessageId, Map<String, Value>), // message with the card and the inputs
+    UpdateAttachmentInfo(AttachmentUrl),
     UpdateRoom(RoomId),
-    UpdateTeam(TeamId, Option<String>), // TeamId and optional room context
+    UpdateRoomSettings(RoomId, String, bool), // room, title and whether it is locked
+    UpdateTeam(TeamId, Option<String>),       // TeamId and optional room context
     UpdateMessage(MessageId),
+    UpdateMeetingInfo(RoomId),
+    UpdateReadStatus(),
     UpdateChildrenMessages(MessageId, RoomId),
     UpdatePerson(PersonId),
+    UploadFile(RoomId, Option<MessageId>, PathBuf), // room, optional parent and local file
+    ListRoomMembers(RoomId),
     LeaveRoom(RoomId),
     WhoAmI(),
 }
@@ -43,6 +63,45 @@ impl AppCmdEvent {
     }
 }
 
+/// Whether an error is likely to go away by itself: network errors, server errors and
+/// rate limiting.
+fn is_transient(error: &WebexError) -> bool {
+    let is_transient_status =
+        |status: StatusCode| status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS;
+    match error {
+        WebexError::Reqwest(e) => e.status().is_none_or(is_transient_status),
+        WebexError::Status(status) | WebexError::StatusText(status, _) => {
+            is_transient_status(*status)
+        }
+        WebexError::Limited(..) | WebexError::Io(_) | WebexError::Closed(_) => true,
+        _ => false,
+    }
+}
+
+/// Returns the error of a `RestClient` call as a `WebexError`, to tell whether it is transient.
+fn webex_error(report: color_eyre::Report) -> WebexError {
+    match report.downcast::<WebexError>() {
+        Ok(error) => error,
+        Err(report) => match report.downcast::<reqwest::Error>() {
+            Ok(error) => WebexError::Reqwest(error),
+            Err(report) => WebexError::Other(report.to_string()),
+        },
+    }
+}
+
+/// Whether a failed command is worth retrying. A message may have been posted before a
+/// timeout, sending it again could post it twice.
+fn should_retry(command: &OutboxCommand, error: &WebexError) -> bool {
+    let is_timeout = match error {
+        WebexError::Reqwest(e) => e.is_timeout(),
+        WebexError::Status(status) | WebexError::StatusText(status, _) => {
+            *status == StatusCode::GATEWAY_TIMEOUT
+        }
+        _ => false,
+    };
+    !(is_timeout && command.posts_new_content()) && is_transient(error)
+}
+
 impl Teams<'_> {
     /// Handle an `AppCmdEvent` dispatched by the App.
     pub(crate) async fn handle_app_event(&mut self, app_cmd_event: AppCmdEvent) {
@@ -51,9 +110,17 @@ impl Teams<'_> {
         }
         let should_retry = app_cmd_event.should_retry_on_failure();
         if let Err(error) = match app_cmd_event {
-            AppCmdEvent::DeleteMessage(msg_id) => self.delete_message(&msg_id).await,
-            AppCmdEvent::EditMessage(msg_id, room_id, text) => {
-                self.do_edit_message(&msg_id, &room_id, &text).await
+            AppCmdEvent::AcknowledgeMessage(room_id, msg_id) => {
+                self.rest.acknowledge_message(&room_id, &msg_id).await
+            }
+            AppCmdEvent::AddMember(room_id, email) => self.do_add_member(&room_id, &email).await,
+            AppCmdEvent::CreateSpace(title, team_id, emails) => {
+                self.do_create_space(&title, team_id.as_deref(), &emails)
+                    .await
+            }
+            AppCmdEvent::Deliver(id, command) => self.do_deliver(id, command).await,
+            AppCmdEvent::DownloadAttachment(url, dir) => {
+                self.do_download_attachment(&url, &dir).await
             }
             AppCmdEvent::Initialize() => self.do_initialize().await,
             AppCmdEvent::ListAllRooms() => self.do_list_all_rooms_with_retry().await,
@@ -61,16 +128,36 @@ impl Teams<'_> {
                 self.do_list_messages_in_room(&room_id, before_id, max)
                     .await
             }
-            AppCmdEvent::SendMessage(msg_to_send) => self.do_send_message(&msg_to_send).await,
+            AppCmdEvent::OpenDirectRoom(person_id) => self.do_open_direct_room(&person_id).await,
+            AppCmdEvent::RemoveMember(room_id, membership_id) => {
+                self.do_remove_member(&room_id, &membership_id).await
+            }
+            AppCmdEvent::SearchPeople(query) => self.do_search_people(&query).await,
+            AppCmdEvent::SetTyping(room_id, is_typing) => {
+                self.rest.set_typing(&room_id, is_typing).await
+            }
+            AppCmdEvent::SubmitCard(msg_id, inputs) => self.do_submit_card(&msg_id, inputs).await,
             AppCmdEvent::UpdateChildrenMessages(msg_id, room_id) => {
                 self.do_update_children_messages(&msg_id, &room_id).await
             }
             AppCmdEvent::UpdateMessage(msg_id) => self.do_update_message(&msg_id).await,
+            AppCmdEvent::UpdateMeetingInfo(room_id) => self.do_update_meeting_info(&room_id).await,
+            AppCmdEvent::UpdateReadStatus() => self.do_update_read_status().await,
+            AppCmdEvent::UpdateAttachmentInfo(url) => self.do_update_attachment_info(&url).await,
             AppCmdEvent::UpdatePerson(person_id) => self.do_update_person(&person_id).await,
             AppCmdEvent::UpdateRoom(room_id) => self.do_refresh_room(&room_id).await,
+            AppCmdEvent::UpdateRoomSettings(room_id, title, is_locked) => {
+                self.do_update_room_settings(&room_id, &title, is_locked)
+                    .await
+            }
             AppCmdEvent::UpdateTeam(team_id, room_context) => {
                 self.do_update_team(&team_id, room_context.as_deref()).await
             }
+            AppCmdEvent::UploadFile(room_id, parent_id, path) => {
+                self.do_upload_file(&room_id, parent_id.as_deref(), &path)
+                    .await
+            }
+            AppCmdEvent::ListRoomMembers(room_id) => self.do_list_room_members(&room_id).await,
             AppCmdEvent::LeaveRoom(room_id) => self.do_leave_room(&room_id).await,
             AppCmdEvent::WhoAmI() => self.get_me_user().await,
         } {
@@ -98,27 +185,219 @@ impl Teams<'_> {
         Ok(())
     }
 
+    /// Delivers a command of the outbox, and tells the app whether it succeeded.
+    /// Failures due to the network or the server are worth retrying.
+    async fn do_deliver(&mut self, id: OutboxId, command: OutboxCommand) -> Result<()> {
+        let result = match &command {
+            OutboxCommand::Send(msg_to_send) => self.do_send_message(id, msg_to_send).await,
+            OutboxCommand::Edit {
+                message_id,
+                room_id,
+                content,
+                is_markdown,
+            } => {
+                self.do_edit_message(message_id, room_id, content, *is_markdown)
+                    .await
+            }
+            OutboxCommand::Delete { message_id, .. } => self.delete_message(message_id).await,
+            OutboxCommand::ForwardAttachment { url, room_id } => {
+                self.do_forward_attachment(url, room_id).await
+            }
+        };
+        match result {
+            Ok(()) => {
+                self.app.lock().await.cb_outbox_delivered(id);
+                Ok(())
+            }
+            Err(e) => {
+                let retry = should_retry(&command, &e);
+                self.app
+                    .lock()
+                    .await
+                    .cb_outbox_failed(id, e.to_string(), retry);
+                Err(eyre!(
+                    "Could not deliver \"{}\": {e}",
+                    command.description()
+                ))
+            }
+        }
+    }
+
     /// Deletes the message with given id
-    async fn delete_message(&self, id: &MessageId) -> Result<()> {
-        let global_id = GlobalId::new(GlobalIdType::Message, id.to_owned()).unwrap();
-        match self.client.delete::<Message>(&global_id).await {
-            Ok(_) => {
-                debug!("Deleted message with id: {global_id:?}");
+    async fn delete_message(&self, id: &MessageId) -> Result<(), WebexError> {
+        let global_id = GlobalId::new(GlobalIdType::Message, id.to_owned())?;
+        self.client.delete::<Message>(&global_id).await?;
+        debug!("Deleted message with id: {global_id:?}");
+        Ok(())
+    }
+
+    /// Sends `msg_to_send` for the outbox entry `id` and calls back `cb_message_sent` on app
+    /// when done.
+    async fn do_send_message(
+        &mut self,
+        id: OutboxId,
+        msg_to_send: &MessageOut,
+    ) -> Result<(), WebexError> {
+        let msg = self.client.send_message(msg_to_send).await?;
+        // The first message to a person starts a 1:1 room, which is opened
+        let room = match (&msg_to_send.to_person_id, &msg.room_id) {
+            (Some(_), Some(room_id)) => self
+                .get_room(room_id)
+                .await
+                .inspect_err(|e| warn!("Could not open the new conversation: {e}"))
+                .ok(),
+            _ => None,
+        };
+        let mut app = self.app.lock().await;
+        if let Some(room) = room {
+            app.cb_room_ready(room);
+        }
+        app.cb_message_sent(Some(id), &msg);
+        debug!("Sent message: {msg_to_send:?}");
+        Ok(())
+    }
+
+    /// Gets the name, size and type of an attached file.
+    async fn do_update_attachment_info(&self, url: &AttachmentUrl) -> Result<()> {
+        match self.rest.file_info(url).await {
+            Ok(info) => {
+                self.app.lock().await.cb_attachment_info_updated(url, info);
                 Ok(())
             }
-            Err(e) => Err(eyre!("Could not delete message: {}", e)),
+            Err(e) => Err(eyre!("Error retrieving attachment info: {}", e)),
         }
     }
 
-    /// Sends `msg_to_send` and calls back `cb_message_sent` on app when done.
-    async fn do_send_message(&mut self, msg_to_send: &MessageOut) -> Result<()> {
-        match self.client.send_message(msg_to_send).await {
+    /// Retrieves the members of a room, used for mentions.
+    async fn do_list_room_members(&self, room_id: &RoomId) -> Result<()> {
+        let members = match GlobalId::new(GlobalIdType::Room, room_id.to_owned()) {
+            Ok(global_id) => self.rest.list_members(global_id.id()).await,
+            Err(e) => Err(e.into()),
+        };
+        match members {
+            Ok(members) => {
+                debug!("Got {} members in room {room_id}", members.len());
+                self.app
+                    .lock()
+                    .await
+                    .cb_room_members_received(room_id, members);
+                Ok(())
+            }
+            Err(e) => {
+                self.app.lock().await.cb_room_members_failed(room_id);
+                Err(eyre!("Error retrieving room members: {}", e))
+            }
+        }
+    }
+
+    /// Adds the person with given email to a room and calls back `cb_member_added` on app.
+    async fn do_add_member(&self, room_id: &RoomId, email: &str) -> Result<()> {
+        let global_id = GlobalId::new(GlobalIdType::Room, room_id.to_owned())?;
+        match self.rest.add_member(global_id.id(), email).await {
+            Ok(member) => {
+                info!("Added {} to the room", member.name());
+                self.app.lock().await.cb_member_added(room_id, member);
+                Ok(())
+            }
+            Err(e) => Err(eyre!("Error adding {email} to the room: {}", e)),
+        }
+    }
+
+    /// Removes a member from a room and calls back `cb_member_removed` on app.
+    async fn do_remove_member(&self, room_id: &RoomId, membership_id: &MembershipId) -> Result<()> {
+        match self.rest.remove_member(membership_id).await {
+            Ok(()) => {
+                info!("Member removed from the room");
+                self.app
+                    .lock()
+                    .await
+                    .cb_member_removed(room_id, membership_id);
+                Ok(())
+            }
+            Err(e) => Err(eyre!("Error removing member: {}", e)),
+        }
+    }
+
+    /// Gets our memberships in all rooms to know which rooms were read, from any client.
+    async fn do_update_read_status(&self) -> Result<()> {
+        match self.rest.list_my_memberships().await {
+            Ok(memberships) => {
+                debug!("Got read status of {} rooms", memberships.len());
+                self.app.lock().await.cb_read_status_received(memberships);
+                Ok(())
+            }
+            Err(e) => Err(eyre!("Error retrieving read status: {}", e)),
+        }
+    }
+
+    /// Downloads an attached file to `dir`, without overwriting existing files.
+    async fn do_download_attachment(&self, url: &AttachmentUrl, dir: &Path) -> Result<()> {
+        debug!("Downloading attachment {url}");
+        let (info, content) = self
+            .rest
+            .download_file(url)
+            .await
+            .map_err(|e| eyre!("Error downloading attachment: {}", e))?;
+        let file_name = info.file_name.clone().unwrap_or("attachment".to_string());
+        tokio::fs::create_dir_all(dir).await?;
+        let path = unique_file_path(dir, &file_name);
+        tokio::fs::write(&path, content)
+            .await
+            .map_err(|e| eyre!("Could not save attachment to {}: {}", path.display(), e))?;
+        info!("Downloaded {} to {}", file_name, path.display());
+        self.app.lock().await.cb_attachment_info_updated(url, info);
+        Ok(())
+    }
+
+    /// Downloads an attached file and posts it to a room, then calls back `cb_message_sent`.
+    async fn do_forward_attachment(
+        &self,
+        url: &AttachmentUrl,
+        room_id: &RoomId,
+    ) -> Result<(), WebexError> {
+        debug!("Forwarding attachment {url} to room {room_id}");
+        let (info, content) = self.rest.download_file(url).await.map_err(webex_error)?;
+        let file_name = info.file_name.unwrap_or("attachment".to_string());
+        let msg = self
+            .rest
+            .post_message_with_content(room_id, None, &file_name, content)
+            .await
+            .map_err(webex_error)?;
+        info!("Forwarded {file_name}");
+        self.app.lock().await.cb_message_sent(None, &msg);
+        Ok(())
+    }
+
+    /// Posts a local file to a room and calls back `cb_message_sent` on app when done.
+    async fn do_upload_file(
+        &self,
+        room_id: &RoomId,
+        parent_id: Option<&str>,
+        path: &Path,
+    ) -> Result<()> {
+        match self
+            .rest
+            .post_message_with_file(room_id, parent_id, path)
+            .await
+        {
             Ok(msg) => {
-                self.app.lock().await.cb_message_sent(&msg);
-                debug!("Sent message: {msg_to_send:?}");
+                info!("Uploaded {}", path.display());
+                self.app.lock().await.cb_message_sent(None, &msg);
                 Ok(())
             }
-            Err(e) => Err(eyre!("Error sending message: {}", e)),
+            Err(e) => Err(eyre!("Error uploading {}: {}", path.display(), e)),
+        }
+    }
+
+    /// Submits the inputs of the card of a message and calls back `cb_card_submitted` on app.
+    async fn do_submit_card(&self, msg_id: &MessageId, inputs: Map<String, Value>) -> Result<()> {
+        match self.rest.submit_card(msg_id, inputs).await {
+            Ok(()) => {
+                info!("Card submitted");
+                self.app.lock().await.cb_card_submitted(msg_id);
+                Ok(())
+            }
+            Err(e) => Err(eyre!("Error submitting card: {}", e)),
         }
     }
 
@@ -172,36 +451,127 @@ impl Teams<'_> {
         &self,
         message_id: &MessageId,
         room_id: &RoomId,
-        text: &str,
-    ) -> Result<()> {
-        let message_id = GlobalId::new(GlobalIdType::Message, message_id.to_owned()).unwrap();
-        let room_id = GlobalId::new(GlobalIdType::Room, room_id.to_owned()).unwrap();
-        let params = MessageEditParams {
+        content: &str,
+        is_markdown: bool,
+    ) -> Result<(), WebexError> {
+        let message_id = GlobalId::new(GlobalIdType::Message, message_id.to_owned())?;
+        let room_id = GlobalId::new(GlobalIdType::Room, room_id.to_owned())?;
+        let mut params = MessageEditParams {
             room_id: room_id.id(),
-            text: Some(text),
             ..Default::default()
         };
-        match self.client.edit_message(&message_id, &params).await {
-            Ok(_) => {
-                debug!("Edited message with id: {message_id:?}");
-                Ok(())
-            }
-            Err(e) => Err(eyre!("Could not edit message: {}", e)),
+        match is_markdown {
+            true => params.markdown = Some(content),
+            false => params.text = Some(content),
         }
+        self.client.edit_message(&message_id, &params).await?;
+        debug!("Edited message with id: {message_id:?}");
+        Ok(())
     }
 
     async fn do_refresh_room(&mut self, id: &RoomId) -> Result<()> {
-        let global_id = GlobalId::new(GlobalIdType::Room, id.to_owned()).unwrap();
+        let webex_room = self.get_room(id).await?;
+        self.app.lock().await.cb_room_updated(webex_room);
+        Ok(())
+    }
+
+    /// Gets the room with given id.
+    async fn get_room(&self, id: &RoomId) -> Result<webex::Room> {
+        let global_id = GlobalId::new(GlobalIdType::Room, id.to_owned())?;
         debug!("Getting room with local id {id} and global id: {global_id:?}");
-        match self.client.get::<webex::Room>(&global_id).await {
-            Ok(webex_room) => {
-                self.app.lock().await.cb_room_updated(webex_room);
+        self.client
+            .get::<webex::Room>(&global_id)
+            .await
+            .map_err(|e| eyre!("Error retrieving room: {}", e))
+    }
+
+    /// Renames a room and locks or unlocks it, then calls back `cb_room_settings_updated`.
+    async fn do_update_room_settings(
+        &self,
+        room_id: &RoomId,
+        title: &str,
+        is_locked: bool,
+    ) -> Result<()> {
+        let global_id = GlobalId::new(GlobalIdType::Room, room_id.to_owned())?;
+        match self
+            .rest
+            .update_room(global_id.id(), title, is_locked)
+            .await
+        {
+            Ok(room) => {
+                info!("Updated the settings of {title}");
+                self.app.lock().await.cb_room_settings_updated(room);
+                Ok(())
+            }
+            Err(e) => Err(eyre!("Error updating the room: {}", e)),
+        }
+    }
+
+    /// Gets the links to join the meeting of a room and calls back `cb_meeting_info_updated`.
+    async fn do_update_meeting_info(&self, room_id: &RoomId) -> Result<()> {
+        let global_id = GlobalId::new(GlobalIdType::Room, room_id.to_owned())?;
+        match self.rest.meeting_info(global_id.id()).await {
+            Ok(info) => {
+                self.app.lock().await.cb_meeting_info_updated(room_id, info);
                 Ok(())
             }
-            Err(e) => Err(eyre!("Error retrieving room: {}", e)),
+            Err(e) => Err(eyre!("Error retrieving meeting info: {}", e)),
         }
     }
 
+    /// Searches the directory for persons and calls back `cb_people_found` on app.
+    async fn do_search_people(&self, query: &str) -> Result<()> {
+        debug!("Searching people matching: {query}");
+        match self.rest.search_people(query).await {
+            Ok(persons) => {
+                self.app.lock().await.cb_people_found(query, persons);
+                Ok(())
+            }
+            Err(e) => Err(eyre!("Error searching people: {}", e)),
+        }
+    }
+
+    /// Opens our 1:1 room with a person with `cb_room_ready`, or calls back
+    /// `cb_no_direct_room` if there is none yet.
+    async fn do_open_direct_room(&self, person_id: &PersonId) -> Result<()> {
+        let room_id = self
+            .rest
+            .direct_room_id(person_id)
+            .await
+            .map_err(|e| eyre!("Error looking for the direct room: {}", e))?;
+        match room_id {
+            Some(room_id) => {
+                let room = self.get_room(&room_id).await?;
+                self.app.lock().await.cb_room_ready(room);
+            }
+            None => self.app.lock().await.cb_no_direct_room(person_id),
+        }
+        Ok(())
+    }
+
+    /// Creates a space, adds the members with given emails to it, then opens it
+    /// with `cb_room_ready`. Members who cannot be added are reported and skipped.
+    async fn do_create_space(
+        &self,
+        title: &str,
+        team_id: Option<&str>,
+        emails: &[String],
+    ) -> Result<()> {
+        let room = self
+            .rest
+            .create_room(title, team_id)
+            .await
+            .map_err(|e| eyre!("Error creating space {title}: {}", e))?;
+        info!("Created space {title}");
+        for email in emails {
+            if let Err(e) = self.rest.add_member(&room.id, email).await {
+                error!("Could not add {email} to {title}: {e}");
+            }
+        }
+        self.app.lock().await.cb_room_ready(room);
+        Ok(())
+    }
+
     /// Gets the team with given id and updates the store.
     /// Many of these calls fail because the user does not have access to the
     /// team details (HTTP 404 when not a team member), so errors are silenced.
@@ -235,22 +605,13 @@ impl Teams<'_> {
         Ok(())
     }
 
-    /// Gets as many rooms as the API allows (1000 as webex-rust does not yet implement paging) rooms.
-    /// Updates the store and sets the active pane to Rooms list.
+    /// Gets all the rooms, page after page, showing how many were received while loading.
     async fn do_list_all_rooms(&mut self) -> Result<()> {
         debug!("Getting all rooms");
-        let params = RoomListParams {
-            sort_by: Some(SortRoomsBy::LastActivity),
-            max: Some(1000),
-            ..Default::default()
-        };
-        self.list_and_add_rooms(params).await?;
-        self.app
-            .lock()
-            .await
-            .state
-            .set_active_pane(Some(ActivePane::Rooms));
-        Ok(())
+        self.app.lock().await.state.rooms_loading_progress = Some(0);
+        let result = self.list_and_add_all_rooms().await;
+        self.app.lock().await.state.rooms_loading_progress = None;
+        result
     }
 
     /// Gets all rooms with retry logic on failure.
@@ -282,17 +643,27 @@ impl Teams<'_> {
         unreachable!()
     }
 
-    /// Gets the rooms as per `params`, updates the store.
-    async fn list_and_add_rooms(&mut self, params: RoomListParams<'_>) -> Result<()> {
-        match self.client.list_with_params::<webex::Room>(params).await {
-            Ok(webex_rooms) => {
-                debug!("Got {} rooms", webex_rooms.len());
-                for webex_room in webex_rooms {
-                    self.app.lock().await.cb_room_updated(webex_room);
-                }
-                Ok(())
+    /// Gets the rooms, following the links to the next pages until there are no more, and
+    /// updates the store with each page as it comes.
+    /// Sets the active pane to Rooms list once the first page is received.
+    async fn list_and_add_all_rooms(&mut self) -> Result<()> {
+        let mut page_url = None;
+        loop {
+            let (webex_rooms, next_page_url) = self
+                .rest
+                .list_rooms_page(page_url.as_deref())
+                .await
+                .map_err(|e| eyre!("Error retrieving rooms: {e}"))?;
+            debug!("Got {} rooms", webex_rooms.len());
+            let mut app = self.app.lock().await;
+            app.cb_rooms_page_received(webex_rooms);
+            if page_url.is_none() {
+                app.state.set_active_pane(Some(ActivePane::Rooms));
+            }
+            match next_page_url {
+                Some(url) => page_url = Some(url),
+                None => return Ok(()),
             }
-            Err(e) => Err(eyre!("Error retrieving rooms: {:#?}", e)),
         }
     }
 
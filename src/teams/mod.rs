@@ -6,10 +6,11 @@
 pub(crate) mod app_handler;
 pub(crate) mod auth;
 mod client;
+mod rest;
 pub(crate) mod token_cache;
 mod webex_handler;
 
-use self::{app_handler::AppCmdEvent, client::get_webex_client};
+use self::{app_handler::AppCmdEvent, client::get_webex_client, rest::RestClient};
 use crate::app::App;
 
 use log::*;
@@ -30,6 +31,7 @@ pub(crate) struct ClientCredentials {
 /// It makes API calls to Webex.
 pub(crate) struct Teams<'a> {
     client: Webex,
+    rest: RestClient,
     app: Arc<tokio::sync::Mutex<App<'a>>>,
 }
 
@@ -38,8 +40,9 @@ impl<'a> Teams<'a> {
         token: AccessToken,
         app: Arc<tokio::sync::Mutex<App<'a>>>,
     ) -> Teams<'a> {
+        let rest = RestClient::new(&token);
         let client = get_webex_client(token).await;
-        Self { client, app }
+        Self { client, rest, app }
     }
 
     /// Spawns a new thread to receive events from Webex
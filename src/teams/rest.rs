@@ -0,0 +1,611 @@
+// teams/rest.rs
+
+//! Direct calls to the Webex REST API for features not covered by `webex-rust`,
+//! such as file transfers.
+
+use base64::{engine::general_purpose::STANDARD_NO_PAD, Engine};
+use color_eyre::{eyre::eyre, Result};
+use log::*;
+use oauth2::AccessToken;
+use reqwest::header::{HeaderMap, CONTENT_DISPOSITION, CONTENT_LENGTH, CONTENT_TYPE, LINK};
+use reqwest::multipart::{Form, Part};
+use reqwest::{Client, Response};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::path::Path;
+use std::sync::OnceLock;
+use webex::{error::Error as WebexError, Message, Person, Room};
+
+use crate::app::cache::attachments::AttachmentInfo;
+use crate::app::cache::meeting_info::MeetingInfo;
+use crate::app::cache::members::Member;
+
+const REST_API_URL: &str = "https://webexapis.com/v1";
+/// Catalog of the services of an organization, including the internal ones
+const U2C_API_URL: &str = "https://u2c.wbx2.com/u2c/api/v1";
+/// Largest page size accepted by the list endpoints
+const MAX_PAGE_SIZE: &str = "1000";
+/// Number of persons returned by a search of the directory
+const PEOPLE_SEARCH_SIZE: &str = "20";
+
+/// Body of the responses of list endpoints.
+#[derive(Deserialize)]
+struct ListResult<T> {
+    items: Vec<T>,
+}
+
+/// Id of an organization, the only field we need.
+#[derive(Deserialize)]
+struct Organization {
+    id: String,
+}
+
+/// Body of the response of the u2c catalog, in the `hostmap` format.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CatalogReply {
+    service_links: ServiceLinks,
+}
+
+/// Urls of the services of an organization, the ones we use.
+#[derive(Deserialize)]
+struct ServiceLinks {
+    conversation: Option<String>,
+}
+
+/// A `reqwest` client authenticated with the user token.
+pub(crate) struct RestClient {
+    client: Client,
+    token: String,
+    /// Url of the internal conversation API of our cluster, once found in the catalog
+    conversation_api_url: OnceLock<String>,
+}
+
+impl RestClient {
+    pub(crate) fn new(token: &AccessToken) -> Self {
+        Self {
+            client: Client::new(),
+            token: token.secret().to_string(),
+            conversation_api_url: OnceLock::new(),
+        }
+    }
+
+    /// Returns the url of the internal conversation API used by the Webex clients,
+    /// for features missing from the public API. It depends on the cluster of our
+    /// organization, so it is looked up in the u2c catalog the first time.
+    async fn conversation_api_url(&self) -> Result<&str> {
+        if let Some(url) = self.conversation_api_url.get() {
+            return Ok(url);
+        }
+        let response = self
+            .client
+            .get(format!("{REST_API_URL}/organizations"))
+            .bearer_auth(&self.token)
+            .send()
+            .await?;
+        let response = check_status(response).await?;
+        let orgs = response.json::<ListResult<Organization>>().await?.items;
+        let org = orgs
+            .first()
+            .ok_or(eyre!("Cannot find the services without an organization"))?;
+        let response = self
+            .client
+            .get(format!("{U2C_API_URL}/limited/catalog"))
+            .query(&[("format", "hostmap"), ("orgId", org.id.as_str())])
+            .bearer_auth(&self.token)
+            .send()
+            .await?;
+        let response = check_status(response).await?;
+        let url = conversation_url_from_catalog(response.json::<CatalogReply>().await?)?;
+        debug!("Conversation API found at {url}");
+        Ok(self.conversation_api_url.get_or_init(|| url))
+    }
+
+    /// Returns the information about the file at `url`, without downloading its content.
+    pub(crate) async fn file_info(&self, url: &str) -> Result<AttachmentInfo> {
+        check_file_url(url)?;
+        let response = self
+            .client
+            .head(url)
+            .bearer_auth(&self.token)
+            .send()
+            .await?;
+        let response = check_status(response).await?;
+        Ok(attachment_info_from_headers(response.headers()))
+    }
+
+    /// Downloads the file at `url`, returns its information and content.
+    pub(crate) async fn download_file(&self, url: &str) -> Result<(AttachmentInfo, Vec<u8>)> {
+        check_file_url(url)?;
+        let response = self.client.get(url).bearer_auth(&self.token).send().await?;
+        let response = check_status(response).await?;
+        let info = attachment_info_from_headers(response.headers());
+        let content = response.bytes().await?.to_vec();
+        Ok((info, content))
+    }
+
+    /// Posts a message with the file at `path` to a room, optionally as a reply.
+    /// Returns the message created by Webex.
+    pub(crate) async fn post_message_with_file(
+        &self,
+        room_id: &str,
+        parent_id: Option<&str>,
+        path: &Path,
+    ) -> Result<Message> {
+        let file_name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or(eyre!("Invalid file name: {}", path.display()))?
+            .to_string();
+        let content = tokio::fs::read(path).await?;
+        self.post_message_with_content(room_id, parent_id, &file_name, content)
+            .await
+    }
+
+    /// Posts a message with a file named `file_name` to a room, optionally as a reply.
+    /// Returns the message created by Webex.
+    pub(crate) async fn post_message_with_content(
+        &self,
+        room_id: &str,
+        parent_id: Option<&str>,
+        file_name: &str,
+        content: Vec<u8>,
+    ) -> Result<Message> {
+        let mime = mime_guess::from_path(file_name).first_or_octet_stream();
+        debug!(
+            "Uploading {file_name} ({} bytes, {mime}) to room {room_id}",
+            content.len()
+        );
+        let part = Part::bytes(content)
+            .file_name(file_name.to_string())
+            .mime_str(mime.as_ref())?;
+        let mut form = Form::new().text("roomId", room_id.to_string());
+        if let Some(parent_id) = parent_id {
+            form = form.text("parentId", parent_id.to_string());
+        }
+        form = form.part("files", part);
+        let response = self
+            .client
+            .post(format!("{REST_API_URL}/messages"))
+            .bearer_auth(&self.token)
+            .multipart(form)
+            .send()
+            .await?;
+        let response = check_status(response).await?;
+        Ok(response.json::<Message>().await?)
+    }
+
+    /// Tells the members of a room that we are typing, or that we stopped.
+    pub(crate) async fn set_typing(&self, room_id: &str, is_typing: bool) -> Result<()> {
+        let conversation_id =
+            uuid_from_api_id(room_id).ok_or(eyre!("Invalid room id: {room_id}"))?;
+        let event_type = match is_typing {
+            true => "status.start_typing",
+            false => "status.stop_typing",
+        };
+        let conversation_api_url = self.conversation_api_url().await?;
+        let response = self
+            .client
+            .post(format!("{conversation_api_url}/status/typing"))
+            .bearer_auth(&self.token)
+            .json(&serde_json::json!({
+                "conversationId": conversation_id,
+                "eventType": event_type,
+            }))
+            .send()
+            .await?;
+        check_status(response).await?;
+        Ok(())
+    }
+
+    /// Returns a page of our rooms, most recently active first, and the url of the next page
+    /// if there are more. The first page is requested when `page_url` is `None`.
+    pub(crate) async fn list_rooms_page(
+        &self,
+        page_url: Option<&str>,
+    ) -> Result<(Vec<Room>, Option<String>)> {
+        let query = [("sortBy", "lastactivity"), ("max", MAX_PAGE_SIZE)];
+        self.list_page(&format!("{REST_API_URL}/rooms"), &query, page_url)
+            .await
+    }
+
+    /// Returns the members of a room, given its API id.
+    pub(crate) async fn list_members(&self, room_id: &str) -> Result<Vec<Member>> {
+        let query = [("roomId", room_id), ("max", MAX_PAGE_SIZE)];
+        self.list_all(&format!("{REST_API_URL}/memberships"), &query)
+            .await
+    }
+
+    /// Returns our own memberships in all rooms, which include what we last read in each room.
+    pub(crate) async fn list_my_memberships(&self) -> Result<Vec<Member>> {
+        let query = [("max", MAX_PAGE_SIZE)];
+        self.list_all(&format!("{REST_API_URL}/memberships"), &query)
+            .await
+    }
+
+    /// Returns a page of a list endpoint and the url of the next page if there are more.
+    /// The first page is requested at `url` with `query`, the others at their `page_url`.
+    async fn list_page<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        query: &[(&str, &str)],
+        page_url: Option<&str>,
+    ) -> Result<(Vec<T>, Option<String>)> {
+        let request = match page_url {
+            // The token must not be sent to a server named by a response
+            Some(page_url) if !is_api_url(page_url) => {
+                return Err(eyre!(
+                    "Refusing to fetch a page outside the API: {page_url}"
+                ))
+            }
+            Some(page_url) => self.client.get(page_url),
+            None => self.client.get(url).query(query),
+        };
+        let response = request.bearer_auth(&self.token).send().await?;
+        let response = check_status(response).await?;
+        let next_page_url = next_page_url(response.headers());
+        let items = response.json::<ListResult<T>>().await?.items;
+        Ok((items, next_page_url))
+    }
+
+    /// Returns the items of all the pages of a list endpoint, following the links to the
+    /// next pages until there are no more.
+    async fn list_all<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        query: &[(&str, &str)],
+    ) -> Result<Vec<T>> {
+        let mut items = Vec::new();
+        let mut page_url = None;
+        loop {
+            let (page, next_page_url) = self.list_page(url, query, page_url.as_deref()).await?;
+            items.extend(page);
+            match next_page_url {
+                Some(url) => page_url = Some(url),
+                None => return Ok(items),
+            }
+        }
+    }
+
+    /// Returns the persons of the directory whose name starts with the query, or with
+    /// the query as email if it contains an `@`.
+    pub(crate) async fn search_people(&self, query: &str) -> Result<Vec<Person>> {
+        let field = match query.contains('@') {
+            true => "email",
+            false => "displayName",
+        };
+        let response = self
+            .client
+            .get(format!("{REST_API_URL}/people"))
+            .bearer_auth(&self.token)
+            .query(&[(field, query), ("max", PEOPLE_SEARCH_SIZE)])
+            .send()
+            .await?;
+        let response = check_status(response).await?;
+        Ok(response.json::<ListResult<Person>>().await?.items)
+    }
+
+    /// Returns the id of our 1:1 room with a person, if we ever exchanged messages.
+    pub(crate) async fn direct_room_id(&self, person_id: &str) -> Result<Option<String>> {
+        let response = self
+            .client
+            .get(format!("{REST_API_URL}/messages/direct"))
+            .bearer_auth(&self.token)
+            .query(&[("personId", person_id)])
+            .send()
+            .await?;
+        let response = check_status(response).await?;
+        let messages = response.json::<ListResult<Message>>().await?.items;
+        Ok(messages.into_iter().find_map(|msg| msg.room_id))
+    }
+
+    /// Creates a space with given title, in a team if given, and returns it.
+    pub(crate) async fn create_room(&self, title: &str, team_id: Option<&str>) -> Result<Room> {
+        let mut body = serde_json::json!({ "title": title });
+        if let Some(team_id) = team_id {
+            body["teamId"] = team_id.into();
+        }
+        let response = self
+            .client
+            .post(format!("{REST_API_URL}/rooms"))
+            .bearer_auth(&self.token)
+            .json(&body)
+            .send()
+            .await?;
+        let response = check_status(response).await?;
+        Ok(response.json::<Room>().await?)
+    }
+
+    /// Renames a room and locks or unlocks it, returns the updated room.
+    pub(crate) async fn update_room(
+        &self,
+        room_id: &str,
+        title: &str,
+        is_locked: bool,
+    ) -> Result<Room> {
+        let response = self
+            .client
+            .put(format!("{REST_API_URL}/rooms/{room_id}"))
+            .bearer_auth(&self.token)
+            .json(&serde_json::json!({
+                "title": title,
+                "isLocked": is_locked,
+            }))
+            .send()
+            .await?;
+        let response = check_status(response).await?;
+        Ok(response.json::<Room>().await?)
+    }
+
+    /// Returns the links to join the meeting of a room.
+    pub(crate) async fn meeting_info(&self, room_id: &str) -> Result<MeetingInfo> {
+        let response = self
+            .client
+            .get(format!("{REST_API_URL}/rooms/{room_id}/meetingInfo"))
+            .bearer_auth(&self.token)
+            .send()
+            .await?;
+        let response = check_status(response).await?;
+        Ok(response.json::<MeetingInfo>().await?)
+    }
+
+    /// Adds the person with given email to a room, returns the new member.
+    pub(crate) async fn add_member(&self, room_id: &str, email: &str) -> Result<Member> {
+        let response = self
+            .client
+            .post(format!("{REST_API_URL}/memberships"))
+            .bearer_auth(&self.token)
+            .json(&serde_json::json!({
+                "roomId": room_id,
+                "personEmail": email,
+            }))
+            .send()
+            .await?;
+        let response = check_status(response).await?;
+        Ok(response.json::<Member>().await?)
+    }
+
+    /// Removes a member from a room, given the id of the membership.
+    pub(crate) async fn remove_member(&self, membership_id: &str) -> Result<()> {
+        let response = self
+            .client
+            .delete(format!("{REST_API_URL}/memberships/{membership_id}"))
+            .bearer_auth(&self.token)
+            .send()
+            .await?;
+        check_status(response).await?;
+        Ok(())
+    }
+
+    /// Tells the server we have read a room up to the given message, given their API ids.
+    /// Our other clients are notified, and the other members see the read receipt.
+    pub(crate) async fn acknowledge_message(&self, room_id: &str, message_id: &str) -> Result<()> {
+        let conversation_id =
+            uuid_from_api_id(room_id).ok_or(eyre!("Invalid room id: {room_id}"))?;
+        let activity_id =
+            uuid_from_api_id(message_id).ok_or(eyre!("Invalid message id: {message_id}"))?;
+        let conversation_api_url = self.conversation_api_url().await?;
+        let response = self
+            .client
+            .post(format!("{conversation_api_url}/activities"))
+            .bearer_auth(&self.token)
+            .json(&serde_json::json!({
+                "objectType": "activity",
+                "verb": "acknowledge",
+                "object": { "objectType": "activity", "id": activity_id },
+                "target": { "objectType": "conversation", "id": conversation_id },
+            }))
+            .send()
+            .await?;
+        check_status(response).await?;
+        Ok(())
+    }
+
+    /// Submits the inputs of the adaptive card of a message, given its API id.
+    /// The bot that posted the card is notified.
+    pub(crate) async fn submit_card(
+        &self,
+        message_id: &str,
+        inputs: Map<String, Value>,
+    ) -> Result<()> {
+        let response = self
+            .client
+            .post(format!("{REST_API_URL}/attachment/actions"))
+            .bearer_auth(&self.token)
+            .json(&serde_json::json!({
+                "type": "submit",
+                "messageId": message_id,
+                "inputs": inputs,
+            }))
+            .send()
+            .await?;
+        check_status(response).await?;
+        Ok(())
+    }
+}
+
+/// Returns the url of the conversation API from the catalog, if it is a https url.
+fn conversation_url_from_catalog(catalog: CatalogReply) -> Result<String> {
+    catalog
+        .service_links
+        .conversation
+        .filter(|url| url.starts_with("https://"))
+        .map(|url| url.trim_end_matches('/').to_string())
+        .ok_or(eyre!(
+            "The conversation service is missing from the catalog"
+        ))
+}
+
+/// Returns the uuid used by the internal API from an API id,
+/// which is the base64 encoding of an uri like `ciscospark://us/ROOM/<uuid>`.
+fn uuid_from_api_id(id: &str) -> Option<String> {
+    let decoded = STANDARD_NO_PAD.decode(id.trim_end_matches('=')).ok()?;
+    let uri = String::from_utf8(decoded).ok()?;
+    uri.rsplit('/')
+        .next()
+        .filter(|uuid| !uuid.is_empty())
+        .map(str::to_string)
+}
+
+/// Returns the response if its status is a success, an error with the status and body otherwise.
+async fn check_status(response: Response) -> Result<Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.text().await.unwrap_or_default();
+    // Kept as a `WebexError` so that callers can tell whether it is worth retrying
+    Err(WebexError::StatusText(status, body).into())
+}
+
+/// Returns the url of the next page of a list, from a `Link` header such as
+/// `<https://webexapis.com/v1/rooms?cursor=abc>; rel="next"`.
+fn next_page_url(headers: &HeaderMap) -> Option<String> {
+    let links = headers.get(LINK)?.to_str().ok()?;
+    links.split(',').find_map(|link| {
+        let mut parts = link.split(';').map(str::trim);
+        let url = parts.next()?.strip_prefix('<')?.strip_suffix('>')?;
+        parts
+            .any(|param| param == "rel=\"next\"" || param == "rel=next")
+            .then(|| url.to_string())
+    })
+}
+
+/// Returns true if `url` is on the REST API, where our token can be sent.
+fn is_api_url(url: &str) -> bool {
+    url.strip_prefix(REST_API_URL)
+        .is_some_and(|rest| rest.starts_with('/') || rest.starts_with('?'))
+}
+
+/// Fails if the url of a file, which comes from a message, is not on the REST API.
+/// Our token must not be sent elsewhere.
+fn check_file_url(url: &str) -> Result<()> {
+    match is_api_url(url) {
+        true => Ok(()),
+        false => Err(eyre!("Refusing to fetch a file outside the API: {url}")),
+    }
+}
+
+/// Extracts file name, size and type from the headers of the contents endpoint.
+fn attachment_info_from_headers(headers: &HeaderMap) -> AttachmentInfo {
+    let header_str = |name| headers.get(name).and_then(|value| value.to_str().ok());
+    AttachmentInfo {
+        file_name: header_str(CONTENT_DISPOSITION).and_then(filename_from_content_disposition),
+        size: header_str(CONTENT_LENGTH).and_then(|len| len.parse().ok()),
+        content_type: header_str(CONTENT_TYPE).map(str::to_string),
+    }
+}
+
+/// Returns the file name from a `Content-Disposition` header value such as
+/// `attachment; filename="report.pdf"`.
+fn filename_from_content_disposition(value: &str) -> Option<String> {
+    value
+        .split(';')
+        .map(str::trim)
+        .find_map(|param| param.strip_prefix("filename="))
+        .map(|name| name.trim_matches('"').to_string())
+        .filter(|name| !name.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+    use rstest::rstest;
+
+    #[rstest(
+        value,
+        expected,
+        case("attachment; filename=\"report.pdf\"", Some("report.pdf")),
+        case("attachment;filename=logs.txt", Some("logs.txt")),
+        case("inline; filename=\"my file.png\"; size=12", Some("my file.png")),
+        case("attachment", None),
+        case("attachment; filename=\"\"", None)
+    )]
+    fn test_filename_from_content_disposition(value: &str, expected: Option<&str>) {
+        assert_eq!(
+            filename_from_content_disposition(value),
+            expected.map(str::to_string)
+        );
+    }
+
+    #[rstest(
+        value,
+        expected,
+        case(
+            "<https://webexapis.com/v1/rooms?cursor=abc&max=1000>; rel=\"next\"",
+            Some("https://webexapis.com/v1/rooms?cursor=abc&max=1000")
+        ),
+        case(
+            "<https://webexapis.com/v1/rooms?cursor=a>; rel=\"prev\", <https://webexapis.com/v1/rooms?cursor=b>; rel=\"next\"",
+            Some("https://webexapis.com/v1/rooms?cursor=b")
+        ),
+        case("<https://webexapis.com/v1/rooms?cursor=a>; rel=\"first\"", None)
+    )]
+    fn test_next_page_url(value: &str, expected: Option<&str>) {
+        let mut headers = HeaderMap::new();
+        headers.insert(LINK, HeaderValue::from_str(value).unwrap());
+        assert_eq!(next_page_url(&headers), expected.map(str::to_string));
+        assert_eq!(next_page_url(&HeaderMap::new()), None);
+    }
+
+    #[rstest(
+        url,
+        expected,
+        case("https://webexapis.com/v1/rooms?cursor=abc", true),
+        case("https://webexapis.com/v1?cursor=abc", true),
+        case("https://webexapis.com/v1.example.com/rooms", false),
+        case("https://example.com/v1/rooms", false),
+        case("http://webexapis.com/v1/rooms", false)
+    )]
+    fn test_is_api_url(url: &str, expected: bool) {
+        assert_eq!(is_api_url(url), expected);
+    }
+
+    #[test]
+    fn test_check_file_url() {
+        assert!(check_file_url("https://webexapis.com/v1/contents/abc").is_ok());
+        assert!(check_file_url("https://example.com/contents/abc").is_err());
+    }
+
+    #[rstest(
+        body,
+        expected,
+        case(
+            r#"{"serviceLinks": {"conversation": "https://conv-r.wbx2.com/conversation/api/v1/", "wdm": "https://wdm-r.wbx2.com/wdm/api/v1"}}"#,
+            Some("https://conv-r.wbx2.com/conversation/api/v1")
+        ),
+        case(r#"{"serviceLinks": {"wdm": "https://wdm-r.wbx2.com/wdm/api/v1"}}"#, None),
+        case(r#"{"serviceLinks": {"conversation": "http://conv-r.wbx2.com"}}"#, None)
+    )]
+    fn test_conversation_url_from_catalog(body: &str, expected: Option<&str>) {
+        let catalog: CatalogReply = serde_json::from_str(body).unwrap();
+        assert_eq!(
+            conversation_url_from_catalog(catalog).ok().as_deref(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_uuid_from_api_id() {
+        let id = STANDARD_NO_PAD.encode("ciscospark://us/ROOM/1234-abcd");
+        assert_eq!(uuid_from_api_id(&id), Some("1234-abcd".to_string()));
+        assert_eq!(uuid_from_api_id("not base64!"), None);
+    }
+
+    #[test]
+    fn test_attachment_info_from_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            CONTENT_DISPOSITION,
+            HeaderValue::from_static("attachment; filename=\"report.pdf\""),
+        );
+        headers.insert(CONTENT_LENGTH, HeaderValue::from_static("1234"));
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/pdf"));
+        let info = attachment_info_from_headers(&headers);
+        assert_eq!(info.file_name.as_deref(), Some("report.pdf"));
+        assert_eq!(info.size, Some(1234));
+        assert_eq!(info.content_type.as_deref(), Some("application/pdf"));
+    }
+}
@@ -12,30 +12,30 @@ use webex::ActivityType::{
 };
 use webex::MessageActivity::{Acknowledged, Deleted, Posted, Shared};
 use webex::SpaceActivity::{Changed, Created, Joined, Left};
-use webex::{Event, MessageActivity};
+use webex::{Event, GlobalId, GlobalIdType, MessageActivity};
 
 use crate::app::cache::room::RoomId;
 
 use super::Teams;
 
+/// Event type not known by `webex-rust`, sent when a person stops typing without posting.
+const STOP_TYPING_EVENT: &str = "status.stop_typing";
+
 impl Teams<'_> {
     /// Handle a webex event.
     pub(crate) async fn handle_webex_event(&mut self, event: Event) -> Result<()> {
         match event.activity_type() {
             Message(activity) => self.handle_message_event(&activity, &event).await?,
             Space(activity) => self.handle_space_event(&activity, &event).await?,
-            AdaptiveCardSubmit => {
-                trace!("Received unhandled adaptive card submit event.");
-            }
+            AdaptiveCardSubmit => self.handle_card_submit_event(&event).await?,
             Locus => { // Call from webex app to webex app
             }
             Janus => {}
-            StartTyping => {
-                trace!("Received unhandled start typing event.");
-            }
+            StartTyping => self.handle_typing_event(&event, true).await?,
             Highlight => {
                 trace!("Received unhandled highlight event.");
             }
+            Unknown(s) if s == STOP_TYPING_EVENT => self.handle_typing_event(&event, false).await?,
             Unknown(s) => {
                 trace!("Received unhandled unknown event: {s:#?}");
             }
@@ -43,6 +43,94 @@ impl Teams<'_> {
         Ok(())
     }
 
+    /// Handle a typing event, `is_typing` is false when the person stopped typing.
+    async fn handle_typing_event(&self, event: &Event, is_typing: bool) -> Result<()> {
+        let conversation_id = event
+            .data
+            .conversation_id
+            .clone()
+            .ok_or(eyre!("Typing event without conversation id: {:?}", event))?;
+        let actor = event
+            .data
+            .actor
+            .as_ref()
+            .ok_or(eyre!("Typing event without actor: {:?}", event))?;
+        // Typing events use uuids, the cache uses API ids
+        let room_id = GlobalId::new(GlobalIdType::Room, conversation_id)?
+            .id()
+            .to_string() as RoomId;
+        let person_id = GlobalId::new(GlobalIdType::Person, actor.id.clone())?
+            .id()
+            .to_string();
+        trace!("Received typing event ({is_typing}) for person {person_id} in room {room_id}");
+        let mut app = self.app.lock().await;
+        match is_typing {
+            true => app.cb_typing_started(&room_id, &person_id),
+            false => app.cb_typing_stopped(&room_id, &person_id),
+        }
+        Ok(())
+    }
+
+    /// Handle a message acknowledged event. When it comes from one of our other clients,
+    /// the room was read there and is marked read here too.
+    async fn handle_acknowledged_event(&self, event: &Event) -> Result<()> {
+        let activity = event
+            .data
+            .activity
+            .as_ref()
+            .ok_or(eyre!("Acknowledged event without activity: {:?}", event))?;
+        let target = activity
+            .target
+            .as_ref()
+            .ok_or(eyre!("Acknowledged event without target: {:?}", event))?;
+        // Activities use uuids, the cache uses API ids
+        let person_id = GlobalId::new(GlobalIdType::Person, activity.actor.id.clone())?
+            .id()
+            .to_string();
+        let mut app = self.app.lock().await;
+        if !app.state.cache.is_me(&Some(person_id)) {
+            trace!("Ignoring message acknowledged by another person.");
+            return Ok(());
+        }
+        let room_id = GlobalId::new(GlobalIdType::Room, target.id.clone())?
+            .id()
+            .to_string() as RoomId;
+        trace!("Room {room_id} was read in another client");
+        app.cb_room_read_elsewhere(&room_id);
+        Ok(())
+    }
+
+    /// Handle an adaptive card submit event. When we submitted the card, possibly from
+    /// another client, the card is shown as submitted.
+    async fn handle_card_submit_event(&self, event: &Event) -> Result<()> {
+        let actor = event
+            .data
+            .actor
+            .as_ref()
+            .ok_or(eyre!("Card submit event without actor: {:?}", event))?;
+        // Events use uuids, the cache uses API ids
+        let person_id = GlobalId::new(GlobalIdType::Person, actor.id.clone())?
+            .id()
+            .to_string();
+        if !self.app.lock().await.state.cache.is_me(&Some(person_id)) {
+            trace!("Ignoring card submitted by another person.");
+            return Ok(());
+        }
+        let global_id = event
+            .try_global_id()
+            .map_err(|e| eyre!("Error getting id from {:?} card submit event: {}", event, e))?;
+        let action = self
+            .client
+            .get::<webex::AttachmentAction>(&global_id)
+            .await?;
+        let message_id = action
+            .message_id
+            .ok_or(eyre!("Card submit event without message id: {:?}", event))?;
+        trace!("Card of message {message_id} was submitted");
+        self.app.lock().await.cb_card_submitted(&message_id);
+        Ok(())
+    }
+
     /// Handle a message event.
     async fn handle_message_event(
         &mut self,
@@ -50,10 +138,7 @@ impl Teams<'_> {
         event: &Event,
     ) -> Result<()> {
         match activity {
-            Acknowledged => {
-                trace!("Received unhandled message acknowledged event.");
-                // trace!("Message acknowledged event: {:#?}", event);
-            }
+            Acknowledged => self.handle_acknowledged_event(event).await?,
             Posted | Shared => {
                 let global_id = event.try_global_id().map_err(|e| {
                     eyre!(
@@ -98,6 +98,14 @@ pub struct Roles {
     pub log_trace: SerializableColor,
     #[serde(default = "default_compose_status")]
     pub compose_status: SerializableColor,
+    #[serde(default = "default_msg_code")]
+    pub msg_code: SerializableColor,
+    #[serde(default = "default_msg_code_bg")]
+    pub msg_code_bg: SerializableColor,
+    #[serde(default = "default_msg_link")]
+    pub msg_link: SerializableColor,
+    #[serde(default = "default_msg_quote")]
+    pub msg_quote: SerializableColor,
 }
 
 impl Roles {
@@ -161,6 +169,18 @@ impl Roles {
     pub fn compose_status(&self) -> Color {
         self.compose_status.0
     }
+    pub fn msg_code(&self) -> Color {
+        self.msg_code.0
+    }
+    pub fn msg_code_bg(&self) -> Color {
+        self.msg_code_bg.0
+    }
+    pub fn msg_link(&self) -> Color {
+        self.msg_link.0
+    }
+    pub fn msg_quote(&self) -> Color {
+        self.msg_quote.0
+    }
 }
 
 impl Theme {
@@ -226,6 +246,10 @@ impl Default for Roles {
             log_debug: default_log_debug(),
             log_trace: default_log_trace(),
             compose_status: default_compose_status(),
+            msg_code: default_msg_code(),
+            msg_code_bg: default_msg_code_bg(),
+            msg_link: default_msg_link(),
+            msg_quote: default_msg_quote(),
         }
     }
 }
@@ -338,6 +362,18 @@ fn default_log_trace() -> SerializableColor {
 fn default_compose_status() -> SerializableColor {
     SerializableColor(Color::Yellow)
 }
+fn default_msg_code() -> SerializableColor {
+    SerializableColor(Color::LightYellow)
+}
+fn default_msg_code_bg() -> SerializableColor {
+    SerializableColor(Color::DarkGray)
+}
+fn default_msg_link() -> SerializableColor {
+    SerializableColor(Color::LightBlue)
+}
+fn default_msg_quote() -> SerializableColor {
+    SerializableColor(Color::Gray)
+}
 
 fn default_user_colors() -> Vec<SerializableColor> {
     vec![
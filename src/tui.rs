@@ -1,6 +1,6 @@
 // tui.rs
 
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{eyre, Result};
 use crossterm::{
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
@@ -11,6 +11,7 @@ use ratatui::Terminal;
 use std::time::Duration;
 use std::{io, panic};
 
+use crate::app::external_editor::edit_in_external_editor;
 use crate::app::App;
 use crate::inputs::handler::EventHandler;
 use crate::ui::render;
@@ -85,6 +86,35 @@ impl Tui {
         Ok(())
     }
 
+    /// Suspends the interface to edit `content` in an external editor, returns the result.
+    ///
+    /// The terminal is handed over to the editor, then restored and redrawn in full.
+    /// The editor runs on a blocking thread, so that the teams task keeps running meanwhile.
+    pub(crate) async fn edit_externally(&mut self, content: String) -> Result<String> {
+        self.events.pause();
+        // Let a pending poll of the terminal input complete
+        tokio::time::sleep(Duration::from_millis(BACKEND_TICK_TIME_MS)).await;
+        let edited = match self.exit() {
+            Ok(()) => tokio::task::spawn_blocking(move || edit_in_external_editor(&content))
+                .await
+                .unwrap_or_else(|e| Err(eyre!("The editor task failed: {e}"))),
+            Err(e) => Err(e),
+        };
+        // Whatever happened, the interface must come back or it would freeze
+        let restored = self.restore();
+        self.events.resume();
+        restored.and(edited)
+    }
+
+    /// Takes the terminal back after it was handed over, and clears it for a full redraw.
+    fn restore(&mut self) -> Result<()> {
+        enable_raw_mode()?;
+        execute!(io::stderr(), EnterAlternateScreen)?;
+        self.terminal.hide_cursor()?;
+        self.terminal.clear()?;
+        Ok(())
+    }
+
     /// Exits the terminal interface.
     ///
     /// It disables the raw mode and reverts back the terminal properties.
@@ -0,0 +1,79 @@
+// ui/bookmarks.rs
+
+//! A panel listing the bookmarked messages of all rooms, in place of the active room.
+
+use ratatui::layout::Constraint;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::block::{Block, BorderType};
+use ratatui::widgets::{Borders, Cell, Row, Table};
+
+use super::messages::human_timestamp;
+use crate::app::state::AppState;
+
+const CREATED_WIDTH: u16 = 18;
+const ROOM_WIDTH: u16 = 20;
+const AUTHOR_WIDTH: u16 = 16;
+
+/// Marker shown next to the author of bookmarked messages.
+pub(crate) const BOOKMARK_MARKER: &str = "★";
+
+/// Draws the bookmarks as a table, most recently added first.
+pub(crate) fn draw_bookmarks<'a>(state: &AppState) -> Table<'a> {
+    let rows: Vec<_> = state
+        .bookmarks
+        .bookmarks()
+        .iter()
+        .map(|bookmark| {
+            let room_title = state
+                .cache
+                .rooms
+                .room_with_id(&bookmark.room_id)
+                .and_then(|room| room.title.clone())
+                .unwrap_or_default();
+            let created = bookmark
+                .created
+                .as_deref()
+                .map(human_timestamp)
+                .unwrap_or_default();
+            Row::new(vec![
+                Cell::from(Span::styled(
+                    created,
+                    Style::default().fg(state.theme.roles.msg_timestamp()),
+                )),
+                Cell::from(room_title),
+                Cell::from(bookmark.author.clone()),
+                Cell::from(bookmark.snippet.clone()),
+            ])
+        })
+        .collect();
+
+    let title = Line::from(vec![
+        Span::raw(format!("Bookmarks ({})", rows.len())),
+        Span::styled(
+            "  (enter: go to message, d: remove, esc: close)",
+            Style::default().fg(state.theme.roles.hint()),
+        ),
+    ]);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(state.theme.roles.border_active()))
+        .title(title);
+    Table::new(
+        rows,
+        &[
+            Constraint::Length(CREATED_WIDTH),
+            Constraint::Length(ROOM_WIDTH),
+            Constraint::Length(AUTHOR_WIDTH),
+            Constraint::Fill(1),
+        ],
+    )
+    .block(block)
+    .row_highlight_style(
+        Style::default()
+            .bg(state.theme.roles.selection_bg())
+            .fg(state.theme.roles.selection_fg())
+            .add_modifier(Modifier::BOLD),
+    )
+}
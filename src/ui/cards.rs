@@ -0,0 +1,80 @@
+// ui/cards.rs
+
+//! Adaptive cards as text, in the messages list and in the form used to fill them.
+
+use ratatui::layout::Constraint;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::block::{Block, BorderType};
+use ratatui::widgets::{Borders, Cell, Row, Table};
+
+use crate::app::cards::{CardForm, CardPart};
+use crate::app::state::AppState;
+use crate::theme::models::Roles;
+
+/// Returns the lines of a card in the messages list, wrapped with the given options.
+/// Inputs are shown with their current value, they are filled in the card form.
+pub(crate) fn card_lines<'a>(
+    parts: &[CardPart],
+    options: &textwrap::Options,
+    roles: &Roles,
+) -> Vec<Line<'a>> {
+    parts
+        .iter()
+        .flat_map(|part| {
+            let (content, style) = part_content(part, roles);
+            textwrap::wrap(&content, options)
+                .into_iter()
+                .map(|line| Line::styled(line.into_owned(), style))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Returns the text of a card part and its style, inputs and actions stand out.
+fn part_content(part: &CardPart, roles: &Roles) -> (String, Style) {
+    match part {
+        CardPart::Text(text) => (text.clone(), Style::default()),
+        CardPart::Input(input) => (input.description(), Style::default().fg(roles.accent())),
+        CardPart::Action(action) => (action.description(), Style::default().fg(roles.msg_link())),
+    }
+}
+
+/// Draws the card being filled with one row per part, in place of the messages.
+pub(crate) fn draw_card_form<'a>(state: &AppState) -> Table<'a> {
+    let parts = state
+        .card_form
+        .as_ref()
+        .map(CardForm::parts)
+        .unwrap_or_default();
+    let rows: Vec<_> = parts
+        .iter()
+        .map(|part| {
+            let (content, style) = part_content(part, &state.theme.roles);
+            Row::new(vec![Cell::from(Span::styled(
+                content.replace('\n', " "),
+                style,
+            ))])
+        })
+        .collect();
+    let title = Line::from(vec![
+        Span::raw("Fill card"),
+        Span::styled(
+            "  (type to fill, space to switch, enter to submit)",
+            Style::default().fg(state.theme.roles.hint()),
+        ),
+    ]);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(state.theme.roles.border_active()))
+        .title(title);
+    Table::new(rows, &[Constraint::Percentage(100)])
+        .block(block)
+        .row_highlight_style(
+            Style::default()
+                .bg(state.theme.roles.selection_bg())
+                .fg(state.theme.roles.selection_fg())
+                .add_modifier(Modifier::BOLD),
+        )
+}
@@ -0,0 +1,69 @@
+// ui/members.rs
+
+//! A panel listing the members of the active room, in place of its messages.
+
+use ratatui::layout::Constraint;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::block::{Block, BorderType};
+use ratatui::widgets::{Borders, Cell, Row, Table};
+
+use crate::app::state::AppState;
+
+const NAME_WIDTH: u16 = 30;
+const MODERATOR_WIDTH: u16 = 10;
+
+/// Draws the members of the active room as a table, sorted by name.
+pub(crate) fn draw_members<'a>(state: &AppState) -> Table<'a> {
+    let rows: Vec<_> = state
+        .active_room_members()
+        .iter()
+        .map(|member| {
+            let moderator = match member.is_moderator {
+                true => "moderator",
+                false => "",
+            };
+            Row::new(vec![
+                Cell::from(state.member_name(member)),
+                Cell::from(state.member_email(member).unwrap_or_default()),
+                Cell::from(Span::styled(
+                    moderator,
+                    Style::default().fg(state.theme.roles.accent()),
+                )),
+            ])
+        })
+        .collect();
+
+    let room_title = state
+        .active_room()
+        .and_then(|room| room.title.clone())
+        .unwrap_or_default();
+    let hint = match state.can_manage_room() {
+        true => "  (a: add, d: remove, esc: close)",
+        false => "  (esc: close)",
+    };
+    let title = Line::from(vec![
+        Span::raw(format!("Members of {room_title} ({})", rows.len())),
+        Span::styled(hint, Style::default().fg(state.theme.roles.hint())),
+    ]);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(state.theme.roles.border_active()))
+        .title(title);
+    Table::new(
+        rows,
+        &[
+            Constraint::Length(NAME_WIDTH),
+            Constraint::Fill(1),
+            Constraint::Length(MODERATOR_WIDTH),
+        ],
+    )
+    .block(block)
+    .row_highlight_style(
+        Style::default()
+            .bg(state.theme.roles.selection_bg())
+            .fg(state.theme.roles.selection_fg())
+            .add_modifier(Modifier::BOLD),
+    )
+}
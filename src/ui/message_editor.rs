@@ -4,11 +4,14 @@
 
 use ratatui::{
     style::Style,
-    text::Span,
-    widgets::{Block, BorderType, Borders},
+    text::{Span, Text},
+    widgets::{Block, BorderType, Borders, Paragraph, Wrap},
 };
 use tui_textarea::TextArea;
 
+use super::rich_text::html_to_lines;
+use crate::app::markdown::markdown_to_html;
+use crate::app::scheduled::SEND_TIME_EXAMPLES;
 use crate::app::state::{ActivePane, AppState};
 
 pub(crate) const MSG_INPUT_BLOCK_HEIGHT: u16 = 5;
@@ -16,14 +19,54 @@ pub(crate) const MSG_INPUT_BLOCK_HEIGHT: u16 = 5;
 // Draws a text editor where the user can type a message.
 pub(crate) fn draw_message_editor<'a>(state: &'a AppState<'a>) -> TextArea<'a> {
     // Update title when in editing mode
+    let mode = match state.message_editor.is_markdown() {
+        true => "[markdown] ",
+        false => "[text] ",
+    };
+    // Show the editing mode first when modal editing is enabled
+    let mode = match state.message_editor.vim_mode() {
+        Some(vim_mode) => format!("-- {vim_mode} -- {mode}"),
+        None => mode.to_string(),
+    };
+    let mode = Span::styled(mode, Style::default().fg(state.theme.roles.accent()));
     let hint = Span::styled(
-        " Enter: send, Alt-Enter: new line, Esc: cancel.",
+        " Enter: send, Alt-Enter: new line, Alt-m: markdown, Alt-e: editor, Alt-s: send later, Esc: cancel.",
         Style::default().fg(state.theme.roles.hint()),
     );
-    let title = if state.message_editor.is_composing() {
-        if let Some(orig_msg) = state.message_editor.response_to() {
+    let title = if let Some(input) = &state.send_time_prompt {
+        // Asking when to send the message
+        vec![
+            Span::styled(
+                format!("Send at: {input}"),
+                Style::default().fg(state.theme.roles.compose_status()),
+            ),
+            Span::styled(
+                format!("  e.g. {SEND_TIME_EXAMPLES}. Enter: schedule, Esc: back."),
+                Style::default().fg(state.theme.roles.hint()),
+            ),
+        ]
+    } else if state.message_editor.is_composing() {
+        if state.message_editor.is_upload_command() {
+            // Uploading a file, show the possible completions if any
+            let candidates = state.message_editor.completion_candidates();
+            let status = match candidates.is_empty() {
+                true => "Upload a file.".to_string(),
+                false => candidates.join("  "),
+            };
+            vec![
+                Span::styled(
+                    status,
+                    Style::default().fg(state.theme.roles.compose_status()),
+                ),
+                Span::styled(
+                    " Tab: complete path, Enter: upload, Esc: cancel.",
+                    Style::default().fg(state.theme.roles.hint()),
+                ),
+            ]
+        } else if let Some(orig_msg) = state.message_editor.response_to() {
             // Responding to a message
             vec![
+                mode,
                 Span::styled(
                     format!(
                         "Responding to {0}'s message.",
@@ -39,6 +82,7 @@ pub(crate) fn draw_message_editor<'a>(state: &'a AppState<'a>) -> TextArea<'a> {
         } else if let Some(orig_msg) = state.message_editor.editing_of() {
             // Editing a message
             vec![
+                mode,
                 Span::styled(
                     format!(
                         "Editing {0}'s message.",
@@ -54,8 +98,9 @@ pub(crate) fn draw_message_editor<'a>(state: &'a AppState<'a>) -> TextArea<'a> {
         } else {
             // Composing a new message
             vec![
+                mode,
                 Span::styled(
-                    "Type your new message.",
+                    "Type your new message, @ to mention, or /upload <path>.",
                     Style::default().fg(state.theme.roles.compose_status()),
                 ),
                 hint,
@@ -86,3 +131,31 @@ pub(crate) fn draw_message_editor<'a>(state: &'a AppState<'a>) -> TextArea<'a> {
     textarea.set_cursor_line_style(Style::default());
     textarea
 }
+
+/// Whether the markdown preview is shown next to the editor.
+pub(crate) fn should_show_preview(state: &AppState) -> bool {
+    let editor = &state.message_editor;
+    editor.is_composing() && editor.is_markdown() && !editor.is_upload_command()
+}
+
+/// Draws the message being composed as it will be rendered once sent as markdown.
+pub(crate) fn draw_markdown_preview<'a>(state: &AppState, width: u16) -> Paragraph<'a> {
+    let markdown = state.message_editor.lines().join("\n");
+    let html = markdown_to_html(&markdown);
+    // Borders take two columns
+    let text_width = width.saturating_sub(2).max(1) as usize;
+    let preview = html_to_lines(&html, text_width, "", &state.theme.roles)
+        .map(Text::from)
+        .unwrap_or_else(|_| Text::from("Failed to render markdown"));
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(state.theme.roles.border()))
+        .border_type(BorderType::Rounded)
+        .title(Span::styled(
+            "Preview",
+            Style::default().fg(state.theme.roles.hint()),
+        ));
+    Paragraph::new(preview)
+        .block(block)
+        .wrap(Wrap { trim: false })
+}
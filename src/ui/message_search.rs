@@ -0,0 +1,73 @@
+// ui/message_search.rs
+
+//! A panel listing the messages matching the search, in place of the active room.
+
+use ratatui::layout::Constraint;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::block::{Block, BorderType};
+use ratatui::widgets::{Borders, Cell, Row, Table};
+
+use super::messages::human_timestamp;
+use crate::app::state::AppState;
+
+const TIMESTAMP_WIDTH: u16 = 16;
+const ROOM_WIDTH: u16 = 20;
+const AUTHOR_WIDTH: u16 = 16;
+
+/// Draws the search query in the title and the matching messages as a table.
+pub(crate) fn draw_message_search<'a>(state: &AppState) -> Table<'a> {
+    let (query, results) = match &state.message_search {
+        Some(search) => (search.query(), search.results()),
+        None => ("", &[][..]),
+    };
+    let rows: Vec<_> = results
+        .iter()
+        .map(|result| {
+            Row::new(vec![
+                Cell::from(Span::styled(
+                    human_timestamp(&result.created),
+                    Style::default().fg(state.theme.roles.msg_timestamp()),
+                )),
+                Cell::from(result.room_title.clone()),
+                Cell::from(result.author.clone()),
+                Cell::from(result.excerpt.clone()),
+            ])
+        })
+        .collect();
+
+    let mut title = Line::from(vec![
+        Span::raw("Search messages: "),
+        Span::styled(
+            query.to_string(),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+    ]);
+    if !query.is_empty() {
+        title.spans.push(Span::styled(
+            format!("  ({} found)", results.len()),
+            Style::default().fg(state.theme.roles.hint()),
+        ));
+    }
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(state.theme.roles.border_active()))
+        .title(title);
+    Table::new(
+        rows,
+        &[
+            Constraint::Length(TIMESTAMP_WIDTH),
+            Constraint::Length(ROOM_WIDTH),
+            Constraint::Length(AUTHOR_WIDTH),
+            Constraint::Fill(1),
+        ],
+    )
+    .block(block)
+    .row_highlight_style(
+        Style::default()
+            .bg(state.theme.roles.selection_bg())
+            .fg(state.theme.roles.selection_fg())
+            .add_modifier(Modifier::BOLD),
+    )
+}
@@ -12,7 +12,6 @@ pub(crate) const ROOM_MIN_HEIGHT: u16 = 8;
 
 use crate::app::state::{ActivePane, AppState};
 use base64::Engine;
-use html2text::from_read;
 use ratatui::prelude::Rect;
 use webex::Message;
 
@@ -26,7 +25,15 @@ use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
 use textwrap::fill;
 
+use super::bookmarks::BOOKMARK_MARKER;
+use super::cards::card_lines;
+use super::outbox::status_span;
+use super::rich_text::html_to_lines;
 use super::style::line_for_room_and_team_title;
+use crate::app::cache::outbox_id_of_local_message;
+use crate::app::cards::card_parts;
+use crate::app::markdown::markdown_to_html;
+use crate::app::outbox::{OutboxCommand, OutboxEntry};
 
 /// Assigns a color/style to each message sender, spreading over the palette
 /// while ensuring each user always gets the same style for consistency.
@@ -49,10 +56,12 @@ fn hash_string_to_number(s: &str, upper: u64) -> u64 {
     hash % upper
 }
 
-/// Returns a human friendly view of the timestamp.
-/// Panics if the timestamp cannot be parsed.
-fn human_timestamp(datetime_str: &str) -> String {
-    let datetime = DateTime::parse_from_rfc3339(datetime_str).unwrap();
+/// Returns a human friendly view of the timestamp, or the timestamp as is if it cannot
+/// be parsed.
+pub(crate) fn human_timestamp(datetime_str: &str) -> String {
+    let Ok(datetime) = DateTime::parse_from_rfc3339(datetime_str) else {
+        return datetime_str.to_string();
+    };
 
     // Display more detail for further dates
     let now = Utc::now();
@@ -76,14 +85,39 @@ fn trim_newline(s: &mut String) {
     }
 }
 
+/// Returns plain text wrapped with the given options.
+fn plain_text<'a>(mut content: String, options: textwrap::Options) -> Text<'a> {
+    trim_newline(&mut content);
+    Text::from(fill(&content, options))
+}
+
 /// Returns a row with the formatted message and the number of lines.
-fn row_for_message<'a>(state: &AppState, msg: Message, width: u16) -> (Row<'a>, usize) {
+/// `hidden_replies` is the number of replies hidden under the message when its thread is collapsed.
+fn row_for_message<'a>(
+    state: &AppState,
+    msg: Message,
+    hidden_replies: usize,
+    width: u16,
+) -> (Row<'a>, usize) {
     // Offset messages that are part of a conversation
     let (title_indent, content_indent) = match msg.parent_id {
         None => (TITLE_INDENT, CONTENT_INDENT),
         Some(_) => (TITLE_INDENT_REPLY, CONTENT_INDENT_REPLY),
     };
 
+    // Cards sent by bots, read before the message is taken apart
+    let card_parts = card_parts(&msg);
+    let card_submitted = msg
+        .id
+        .as_ref()
+        .is_some_and(|id| state.cache.submitted_cards.contains(id));
+    // Messages being sent show their delivery status instead of a timestamp
+    let outbox_entry = msg
+        .id
+        .as_ref()
+        .and_then(outbox_id_of_local_message)
+        .and_then(|id| state.outbox.entries().iter().find(|entry| entry.id == id));
+
     // One line for the author and timestamp
     let mut title_line = Line::default();
     title_line.spans.push(title_indent.into());
@@ -104,19 +138,41 @@ fn row_for_message<'a>(state: &AppState, msg: Message, width: u16) -> (Row<'a>,
         sender,
         style_for_user(&msg.person_id, &state.theme.user_colors()),
     ));
+    if msg
+        .id
+        .as_ref()
+        .is_some_and(|id| state.bookmarks.contains(id))
+    {
+        title_line.spans.push(Span::styled(
+            format!(" {BOOKMARK_MARKER}"),
+            Style::default().fg(state.theme.roles.accent()),
+        ));
+    }
 
-    // Add message timestamp
+    // Add message timestamp, or delivery status
     title_line.spans.push(Span::from("  "));
-    let mut stamp = String::new();
-    if let Some(updated) = &msg.updated {
-        stamp = format!("{} (edited)", human_timestamp(updated));
-    } else if let Some(created) = &msg.created {
-        stamp = human_timestamp(created);
+    if let Some(entry) = outbox_entry {
+        title_line
+            .spans
+            .push(status_span(entry, &state.theme.roles));
+        if let Some(error) = &entry.last_error {
+            title_line.spans.push(Span::styled(
+                format!("  {error}"),
+                Style::default().fg(state.theme.roles.hint()),
+            ));
+        }
+    } else {
+        let mut stamp = String::new();
+        if let Some(updated) = &msg.updated {
+            stamp = format!("{} (edited)", human_timestamp(updated));
+        } else if let Some(created) = &msg.created {
+            stamp = human_timestamp(created);
+        }
+        title_line.spans.push(Span::styled(
+            stamp,
+            Style::default().fg(state.theme.roles.msg_timestamp()),
+        ));
     }
-    title_line.spans.push(Span::styled(
-        stamp,
-        Style::default().fg(state.theme.roles.msg_timestamp()),
-    ));
 
     // Add message id
     if state.debug {
@@ -128,37 +184,78 @@ fn row_for_message<'a>(state: &AppState, msg: Message, width: u16) -> (Row<'a>,
     let options = textwrap::Options::new(text_width)
         .initial_indent(content_indent)
         .subsequent_indent(content_indent);
-    let mut content = match (msg.html, msg.markdown, msg.text) {
-        (None, None, None) => String::from("No content"),
-        (Some(html), _, _) => {
+    let html = match (msg.html, msg.markdown) {
+        (Some(html), _) => {
             if state.debug {
                 title_line.spans.push(Span::from(" (HTML)"));
             }
-            from_read(html.as_bytes(), text_width)
-                .unwrap_or_else(|_| String::from("Failed to parse HTML"))
+            Some(html)
         }
-        (_, Some(markdown), _) => {
+        (None, Some(markdown)) => {
             if state.debug {
                 title_line.spans.push(Span::from("  (MD)"));
             }
-            markdown
+            Some(markdown_to_html(&markdown))
         }
-        (_, _, Some(text)) => text,
+        (None, None) => None,
+    };
+    let content_lines = match (html, msg.text) {
+        (Some(html), text) => {
+            let html_width = text_width
+                .saturating_sub(content_indent.chars().count())
+                .max(1);
+            html_to_lines(&html, html_width, content_indent, &state.theme.roles)
+                .map(Text::from)
+                .unwrap_or_else(|_| {
+                    plain_text(text.unwrap_or("Failed to parse HTML".to_string()), options)
+                })
+        }
+        (None, Some(text)) => plain_text(text, options),
+        // Cards are listed below
+        (None, None) if !card_parts.is_empty() => Text::default(),
+        (None, None) => plain_text(String::from("No content"), options),
     };
-    trim_newline(&mut content);
 
     let mut text = Text::default();
     // One empty line, with a conversation marker if applicable
     text.extend(Text::from(format!("{content_indent}\n")));
     text.extend(Text::from(title_line));
-    text.extend(Text::from(fill(&content, options)));
+    text.extend(content_lines);
 
-    // Indicate the presence of attachments
+    // List the attachments, with their name when known
     if let Some(files) = msg.files {
-        text.extend(Text::from(format!(
-            "-- {} attachment{}",
-            files.len(),
-            if files.len() > 1 { "s" } else { "" }
+        for (index, url) in files.iter().enumerate() {
+            text.extend(Text::from(format!(
+                "{content_indent}-- {}",
+                state.attachment_label(index, url)
+            )));
+        }
+    }
+
+    // Content of the cards, and whether we answered
+    if !card_parts.is_empty() {
+        let options = textwrap::Options::new(text_width)
+            .initial_indent(content_indent)
+            .subsequent_indent(content_indent);
+        text.extend(card_lines(&card_parts, &options, &state.theme.roles));
+        if card_submitted {
+            text.extend(Text::from(Line::styled(
+                format!("{content_indent}Submitted"),
+                Style::default().fg(state.theme.roles.hint()),
+            )));
+        }
+    }
+
+    // Summary of a collapsed thread
+    if hidden_replies > 0 {
+        let plural = if hidden_replies == 1 {
+            "reply"
+        } else {
+            "replies"
+        };
+        text.extend(Text::from(Line::styled(
+            format!("{CONTENT_INDENT_REPLY}{hidden_replies} {plural}"),
+            Style::default().fg(state.theme.roles.hint()),
         )));
     }
 
@@ -168,6 +265,37 @@ fn row_for_message<'a>(state: &AppState, msg: Message, width: u16) -> (Row<'a>,
     (row, height)
 }
 
+/// Returns a row for a change to the room not yet delivered, with its status, and the number
+/// of lines.
+fn row_for_outbox_entry<'a>(state: &AppState, entry: &OutboxEntry, width: u16) -> (Row<'a>, usize) {
+    let is_reply = matches!(&entry.command, OutboxCommand::Send(msg) if msg.parent_id.is_some());
+    let (title_indent, content_indent) = match is_reply {
+        false => (TITLE_INDENT, CONTENT_INDENT),
+        true => (TITLE_INDENT_REPLY, CONTENT_INDENT_REPLY),
+    };
+    let mut title_line = Line::from(title_indent);
+    title_line
+        .spans
+        .push(status_span(entry, &state.theme.roles));
+    if let Some(error) = &entry.last_error {
+        title_line.spans.push(Span::styled(
+            format!("  {error}"),
+            Style::default().fg(state.theme.roles.hint()),
+        ));
+    }
+    let options = textwrap::Options::new((width - CONTENT_RIGHT_MARGIN) as usize)
+        .initial_indent(content_indent)
+        .subsequent_indent(content_indent);
+    let mut text = Text::from(format!("{content_indent}\n"));
+    text.extend(Text::from(title_line));
+    text.extend(plain_text(entry.command.description(), options));
+    let height = text.height();
+    (
+        Row::new(vec![Cell::from(text)]).height(height as u16),
+        height,
+    )
+}
+
 /// Adds the decoded message uuid to the line.
 fn add_uuid_to_line(id: Option<String>, line: &mut Line<'_>) {
     if let Some(id) = id
@@ -211,18 +339,54 @@ pub(crate) fn draw_msg_table<'a>(state: &AppState, rect: &Rect) -> (Table<'a>, u
             add_uuid_to_line(Some(room.id.clone()), &mut title_line);
         }
 
+        // show who is typing
+        if let Some(status) = state.typing_status() {
+            title_line.spans.push(Span::styled(
+                format!("  {status}"),
+                Style::default().fg(state.theme.roles.hint()),
+            ));
+        }
+
+        // show how threads are presented
+        let thread_view = state.messages_list.thread_view();
+        let view_status = match (thread_view.focused(), thread_view.is_collapsed()) {
+            (Some(_), _) => Some("[thread]"),
+            (None, true) => Some("[collapsed]"),
+            (None, false) => None,
+        };
+        if let Some(view_status) = view_status {
+            title_line.spans.push(Span::styled(
+                format!("  {view_status}"),
+                Style::default().fg(state.theme.roles.hint()),
+            ));
+        }
+
         // get the formatted messages for the room
         rows = state
-            .cache
-            .messages_in_room(&room.id)
-            .map(|msg| {
-                let (row, height) = row_for_message(state, msg.clone(), rect.width - 2);
+            .displayed_messages_active_room()
+            .map(|displayed| {
+                let (row, height) = row_for_message(
+                    state,
+                    displayed.message.clone(),
+                    displayed.hidden_replies,
+                    rect.width - 2,
+                );
                 nb_lines += height;
                 row
             })
             .collect();
     };
+    // Only messages can be selected, what is not delivered yet comes after them
     let nb_rows = rows.len();
+    if let Some(room) = state.active_room() {
+        // Messages being sent are shown with the others
+        let entries = state.outbox.entries_in_room(&room.id);
+        for entry in entries.filter(|entry| !matches!(entry.command, OutboxCommand::Send(_))) {
+            let (row, height) = row_for_outbox_entry(state, entry, rect.width - 2);
+            nb_lines += height;
+            rows.push(row);
+        }
+    }
 
     // Highlight pane if active
     let border_style = match state.active_pane() {
@@ -245,3 +409,19 @@ pub(crate) fn draw_msg_table<'a>(state: &AppState, rect: &Rect) -> (Table<'a>, u
         nb_lines,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_human_timestamp_keeps_invalid_dates() {
+        assert_eq!(human_timestamp(""), "");
+        assert_eq!(human_timestamp("yesterday"), "yesterday");
+        let today = Utc::now().with_timezone(&Local);
+        assert_eq!(
+            human_timestamp(&today.to_rfc3339()),
+            today.format("%H:%M").to_string()
+        );
+    }
+}
@@ -4,23 +4,44 @@
 
 use log::*;
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
-use ratatui::widgets::{Scrollbar, ScrollbarOrientation};
+use ratatui::widgets::{Clear, Scrollbar, ScrollbarOrientation};
 use ratatui::Frame;
 
+mod bookmarks;
+mod cards;
 mod help;
 mod logs;
+mod members;
 mod message_editor;
+mod message_search;
 mod messages;
+mod outbox;
+mod picker;
+mod prompt;
+mod rich_text;
+mod room_details;
 mod rooms;
+mod scheduled;
 mod style;
 mod title;
 
 use crate::app::state::AppState;
+use bookmarks::draw_bookmarks;
+use cards::draw_card_form;
 use help::{draw_help, HELP_WIDTH};
 use logs::{draw_logs, LOG_BLOCK_PERCENTAGE};
-use message_editor::{draw_message_editor, MSG_INPUT_BLOCK_HEIGHT};
+use members::draw_members;
+use message_editor::{
+    draw_markdown_preview, draw_message_editor, should_show_preview, MSG_INPUT_BLOCK_HEIGHT,
+};
+use message_search::draw_message_search;
 use messages::{draw_msg_table, ACTIVE_ROOM_MIN_WIDTH, ROOM_MIN_HEIGHT};
+use outbox::draw_outbox;
+use picker::{draw_picker, picker_area};
+use prompt::{draw_prompt, prompt_area};
+use room_details::{draw_room_details, room_details_area};
 use rooms::{draw_rooms_table, ROOMS_LIST_WIDTH};
+use scheduled::draw_scheduled_messages;
 use title::{draw_title, TITLE_BLOCK_HEIGHT};
 
 /// Render all blocks.
@@ -90,26 +111,63 @@ pub(crate) fn render(rect: &mut Frame, state: &mut AppState) {
         .constraints(room_constraints)
         .split(body_columns[messages_column_index]);
 
-    // Messages list
+    // Messages list, the card being filled, the outbox, the scheduled messages, the bookmarks,
+    // the members of the room or the messages matching the search
     let messages_area = room_rows[0];
-    let (msg_table, nb_messages, nb_lines) = draw_msg_table(state, &messages_area);
-    state.messages_list.set_nb_messages(nb_messages);
-    rect.render_stateful_widget(
-        msg_table,
-        messages_area,
-        state.messages_list.table_state_mut(),
-    );
-    // Display scrollbar
-    state.messages_list.set_nb_lines(nb_lines);
-    state.messages_list.scroll_to_selection();
-    rect.render_stateful_widget(
-        Scrollbar::default()
-            .orientation(ScrollbarOrientation::VerticalRight)
-            .begin_symbol(Some("↑"))
-            .end_symbol(Some("↓")),
-        messages_area,
-        state.messages_list.scroll_state_mut(),
-    );
+    if state.show_members {
+        let members_table = draw_members(state);
+        rect.render_stateful_widget(
+            members_table,
+            messages_area,
+            state.members_list.table_state_mut(),
+        );
+    } else if state.show_bookmarks {
+        let bookmarks_table = draw_bookmarks(state);
+        rect.render_stateful_widget(
+            bookmarks_table,
+            messages_area,
+            state.bookmarks.table_state_mut(),
+        );
+    } else if state.show_outbox {
+        let outbox_table = draw_outbox(state);
+        rect.render_stateful_widget(outbox_table, messages_area, state.outbox.table_state_mut());
+    } else if state.show_scheduled {
+        let scheduled_table = draw_scheduled_messages(state);
+        rect.render_stateful_widget(
+            scheduled_table,
+            messages_area,
+            state.schedule.table_state_mut(),
+        );
+    } else if state.card_form.is_some() {
+        let card_table = draw_card_form(state);
+        if let Some(form) = state.card_form.as_mut() {
+            rect.render_stateful_widget(card_table, messages_area, form.table_state_mut());
+        }
+    } else if state.message_search.is_some() {
+        let search_table = draw_message_search(state);
+        if let Some(search) = state.message_search.as_mut() {
+            rect.render_stateful_widget(search_table, messages_area, search.table_state_mut());
+        }
+    } else {
+        let (msg_table, nb_messages, nb_lines) = draw_msg_table(state, &messages_area);
+        state.messages_list.set_nb_messages(nb_messages);
+        rect.render_stateful_widget(
+            msg_table,
+            messages_area,
+            state.messages_list.table_state_mut(),
+        );
+        // Display scrollbar
+        state.messages_list.set_nb_lines(nb_lines);
+        state.messages_list.scroll_to_selection();
+        rect.render_stateful_widget(
+            Scrollbar::default()
+                .orientation(ScrollbarOrientation::VerticalRight)
+                .begin_symbol(Some("↑"))
+                .end_symbol(Some("↓")),
+            messages_area,
+            state.messages_list.scroll_state_mut(),
+        );
+    }
 
     // Help
     if let Some(help_idx) = help_column_index {
@@ -127,9 +185,41 @@ pub(crate) fn render(rect: &mut Frame, state: &mut AppState) {
         rect.render_widget(logs, app_rows[2]);
     }
 
-    // Message input
+    // Message input, with a preview on the right when composing markdown
+    let mut editor_area = room_rows[1];
+    if should_show_preview(state) {
+        let editor_columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(editor_area);
+        editor_area = editor_columns[0];
+        let preview = draw_markdown_preview(state, editor_columns[1].width);
+        rect.render_widget(preview, editor_columns[1]);
+    }
     let editor = draw_message_editor(state);
-    rect.render_widget(&editor, room_rows[1]);
+    rect.render_widget(&editor, editor_area);
+
+    // Room details popup
+    if let Some(room) = state.active_room().filter(|_| state.show_room_details) {
+        let details_area = room_details_area(area);
+        rect.render_widget(Clear, details_area);
+        rect.render_widget(draw_room_details(state, room), details_area);
+    }
+
+    // Picker popup, over everything else
+    if let Some(picker) = state.picker.as_mut() {
+        let picker_area = picker_area(area, picker);
+        let picker_table = draw_picker(picker, &state.theme);
+        rect.render_widget(Clear, picker_area);
+        rect.render_stateful_widget(picker_table, picker_area, picker.table_state_mut());
+    }
+
+    // Prompt popup
+    if let Some(prompt) = &state.prompt {
+        let prompt_area = prompt_area(area);
+        rect.render_widget(Clear, prompt_area);
+        rect.render_widget(draw_prompt(prompt, &state.theme), prompt_area);
+    }
 }
 
 /// Logs warnings when terminal size constraints are not respected.
@@ -0,0 +1,86 @@
+// ui/outbox.rs
+
+//! A panel listing what is waiting to be delivered to Webex, in place of the active room.
+
+use chrono::Local;
+use ratatui::layout::Constraint;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::block::{Block, BorderType};
+use ratatui::widgets::{Borders, Cell, Row, Table};
+
+use crate::app::outbox::{DeliveryStatus, OutboxEntry};
+use crate::app::state::AppState;
+use crate::theme::models::Roles;
+
+const STATUS_WIDTH: u16 = 20;
+const ROOM_WIDTH: u16 = 20;
+
+/// Returns the delivery status of an entry, styled as a hint or as an error once given up on.
+pub(crate) fn status_span<'a>(entry: &OutboxEntry, roles: &Roles) -> Span<'a> {
+    match &entry.status {
+        DeliveryStatus::InFlight => Span::styled("⟳ sending", Style::default().fg(roles.hint())),
+        DeliveryStatus::Waiting { retry_at } => Span::styled(
+            format!(
+                "⟳ retry at {}",
+                retry_at.with_timezone(&Local).format("%H:%M:%S")
+            ),
+            Style::default().fg(roles.hint()),
+        ),
+        DeliveryStatus::Failed => Span::styled("✗ failed", Style::default().fg(roles.log_error())),
+    }
+}
+
+/// Draws the outbox as a table, oldest first.
+pub(crate) fn draw_outbox<'a>(state: &AppState) -> Table<'a> {
+    let rows: Vec<_> = state
+        .outbox
+        .entries()
+        .iter()
+        .map(|entry| {
+            let room_title = entry
+                .command
+                .room_id()
+                .and_then(|id| state.cache.rooms.room_with_id(id))
+                .and_then(|room| room.title.clone())
+                .unwrap_or_default();
+            let mut description = entry.command.description().replace('\n', " ");
+            if let Some(error) = &entry.last_error {
+                description.push_str(&format!("  ({error})"));
+            }
+            Row::new(vec![
+                Cell::from(status_span(entry, &state.theme.roles)),
+                Cell::from(room_title),
+                Cell::from(description),
+            ])
+        })
+        .collect();
+
+    let title = Line::from(vec![
+        Span::raw(format!("Outbox ({})", rows.len())),
+        Span::styled(
+            "  (enter: resend now, d: discard, esc: close)",
+            Style::default().fg(state.theme.roles.hint()),
+        ),
+    ]);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(state.theme.roles.border_active()))
+        .title(title);
+    Table::new(
+        rows,
+        &[
+            Constraint::Length(STATUS_WIDTH),
+            Constraint::Length(ROOM_WIDTH),
+            Constraint::Fill(1),
+        ],
+    )
+    .block(block)
+    .row_highlight_style(
+        Style::default()
+            .bg(state.theme.roles.selection_bg())
+            .fg(state.theme.roles.selection_fg())
+            .add_modifier(Modifier::BOLD),
+    )
+}
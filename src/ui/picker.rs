@@ -0,0 +1,49 @@
+// ui/picker.rs
+
+//! Popup listing the items the user can pick from.
+
+use ratatui::layout::{Constraint, Flex, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::block::{Block, BorderType};
+use ratatui::widgets::{Borders, Cell, Row, Table};
+
+use crate::app::picker::Picker;
+use crate::theme::Theme;
+
+const PICKER_WIDTH_PERCENTAGE: u16 = 60;
+const PICKER_MAX_HEIGHT: u16 = 20;
+
+/// Returns the area of the popup, centered in `area` and high enough for the items.
+pub(crate) fn picker_area(area: Rect, picker: &Picker) -> Rect {
+    // Items plus the borders
+    let height = (picker.visible_items().len() as u16 + 2).clamp(3, PICKER_MAX_HEIGHT);
+    let [area] = Layout::vertical([Constraint::Length(height)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [area] = Layout::horizontal([Constraint::Percentage(PICKER_WIDTH_PERCENTAGE)])
+        .flex(Flex::Center)
+        .areas(area);
+    area
+}
+
+/// Draws the picker items as a table.
+pub(crate) fn draw_picker<'a>(picker: &Picker, theme: &Theme) -> Table<'a> {
+    let rows: Vec<_> = picker
+        .visible_items()
+        .into_iter()
+        .map(|item| Row::new(vec![Cell::from(item.label.clone())]))
+        .collect();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(theme.roles.border_active()))
+        .title(picker.title().to_string());
+    Table::new(rows, &[Constraint::Percentage(100)])
+        .block(block)
+        .row_highlight_style(
+            Style::default()
+                .bg(theme.roles.selection_bg())
+                .fg(theme.roles.selection_fg())
+                .add_modifier(Modifier::BOLD),
+        )
+}
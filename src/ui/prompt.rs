@@ -0,0 +1,54 @@
+// ui/prompt.rs
+
+//! Popup asking the user for a line of text.
+
+use ratatui::layout::{Constraint, Flex, Layout, Rect};
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::block::{Block, BorderType};
+use ratatui::widgets::{Borders, Paragraph};
+
+use crate::app::prompt::Prompt;
+use crate::theme::Theme;
+
+const PROMPT_WIDTH_PERCENTAGE: u16 = 60;
+/// The input line plus the borders
+const PROMPT_HEIGHT: u16 = 3;
+
+/// Returns the area of the popup, centered in `area`.
+pub(crate) fn prompt_area(area: Rect) -> Rect {
+    let [area] = Layout::vertical([Constraint::Length(PROMPT_HEIGHT)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [area] = Layout::horizontal([Constraint::Percentage(PROMPT_WIDTH_PERCENTAGE)])
+        .flex(Flex::Center)
+        .areas(area);
+    area
+}
+
+/// Draws the question as title and the text typed so far.
+pub(crate) fn draw_prompt<'a>(prompt: &Prompt, theme: &Theme) -> Paragraph<'a> {
+    let title = Line::from(vec![
+        Span::raw(prompt.title()),
+        Span::styled(
+            "  (enter: confirm, esc: cancel)",
+            Style::default().fg(theme.roles.hint()),
+        ),
+    ]);
+    let mut block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(theme.roles.border_active()))
+        .title(title);
+    if let Some(error) = prompt.error() {
+        block = block.title_bottom(Span::styled(
+            error.to_string(),
+            Style::default().fg(theme.roles.log_error()),
+        ));
+    }
+    let input = Line::from(vec![
+        Span::raw(prompt.input().to_string()),
+        Span::styled("█", Style::default().fg(theme.roles.accent())),
+    ]);
+    Paragraph::new(input).block(block)
+}
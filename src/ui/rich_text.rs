@@ -0,0 +1,167 @@
+// ui/rich_text.rs
+
+//! Renders the HTML subset used by Webex messages as styled lines.
+
+use color_eyre::Result;
+use html2text::render::RichAnnotation;
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+
+use crate::theme::models::Roles;
+
+/// Prefix added by `html2text` to the lines of a blockquote.
+const QUOTE_PREFIX: &str = "> ";
+
+/// Converts `html` to lines wrapped to `width` columns, each prefixed with `indent`.
+/// Formatting is kept: emphasis, code (with a background, spanning the whole width for blocks),
+/// links, quotes, lists and tables.
+pub(crate) fn html_to_lines(
+    html: &str,
+    width: usize,
+    indent: &str,
+    roles: &Roles,
+) -> Result<Vec<Line<'static>>> {
+    let tagged_lines = html2text::config::rich().lines_from_read(html.as_bytes(), width)?;
+    let mut lines: Vec<Line<'static>> = tagged_lines
+        .iter()
+        .map(|tagged_line| {
+            let mut spans = vec![Span::raw(indent.to_string())];
+            let mut line_width = 0;
+            let mut is_code_block = false;
+            for tagged_string in tagged_line.tagged_strings() {
+                is_code_block |= tagged_string
+                    .tag
+                    .iter()
+                    .any(|a| matches!(a, RichAnnotation::Preformat(_)));
+                line_width += tagged_string.width();
+                spans.push(Span::styled(
+                    tagged_string.s.clone(),
+                    style_for_annotations(&tagged_string.tag, roles),
+                ));
+            }
+            // Extend the background of code blocks to the full width
+            if is_code_block && line_width < width {
+                spans.push(Span::styled(
+                    " ".repeat(width - line_width),
+                    code_style(roles),
+                ));
+            }
+            let is_quote = tagged_line
+                .tagged_strings()
+                .map(|tagged_string| tagged_string.s.as_str())
+                .collect::<String>()
+                .starts_with(QUOTE_PREFIX);
+            let mut line = Line::from(spans);
+            if is_quote {
+                line = line.style(Style::default().fg(roles.msg_quote()));
+            }
+            line
+        })
+        .collect();
+    // Drop the trailing empty lines added after the last paragraph
+    while lines.last().is_some_and(|line| {
+        line.spans
+            .iter()
+            .skip(1)
+            .all(|s| s.content.trim().is_empty())
+    }) {
+        lines.pop();
+    }
+    Ok(lines)
+}
+
+/// Style of inline code and code blocks.
+fn code_style(roles: &Roles) -> Style {
+    Style::default()
+        .fg(roles.msg_code())
+        .bg(roles.msg_code_bg())
+}
+
+/// Combines the styles of nested annotations, outer annotations first.
+fn style_for_annotations(annotations: &[RichAnnotation], roles: &Roles) -> Style {
+    annotations
+        .iter()
+        .fold(Style::default(), |style, annotation| match annotation {
+            RichAnnotation::Link(_) => style
+                .fg(roles.msg_link())
+                .add_modifier(Modifier::UNDERLINED),
+            RichAnnotation::Image(_) => style.fg(roles.msg_link()),
+            RichAnnotation::Emphasis => style.add_modifier(Modifier::ITALIC),
+            RichAnnotation::Strong => style.add_modifier(Modifier::BOLD),
+            RichAnnotation::Strikeout => style.add_modifier(Modifier::CROSSED_OUT),
+            RichAnnotation::Code | RichAnnotation::Preformat(_) => style.patch(code_style(roles)),
+            RichAnnotation::Colour(c) => style.fg(Color::Rgb(c.r, c.g, c.b)),
+            RichAnnotation::BgColour(c) => style.bg(Color::Rgb(c.r, c.g, c.b)),
+            // Default text, and annotations added by future versions of html2text
+            _ => style,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(html: &str) -> Vec<Line<'static>> {
+        html_to_lines(html, 40, "  ", &Roles::default()).unwrap()
+    }
+
+    fn span_with<'a>(lines: &'a [Line<'static>], content: &str) -> &'a Span<'static> {
+        lines
+            .iter()
+            .flat_map(|line| line.spans.iter())
+            .find(|span| span.content == content)
+            .unwrap_or_else(|| panic!("no span with content {content:?} in {lines:?}"))
+    }
+
+    #[test]
+    fn test_inline_styles() {
+        let roles = Roles::default();
+        let lines = render("<p><strong>bold</strong> <em>it</em> <code>x = 1</code></p>");
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].spans[0].content, "  ");
+        assert!(span_with(&lines, "bold")
+            .style
+            .add_modifier
+            .contains(Modifier::BOLD));
+        assert!(span_with(&lines, "it")
+            .style
+            .add_modifier
+            .contains(Modifier::ITALIC));
+        assert_eq!(span_with(&lines, "x = 1").style, code_style(&roles));
+    }
+
+    #[test]
+    fn test_link() {
+        let lines = render("<a href=\"https://example.com\">site</a>");
+        let span = span_with(&lines, "site");
+        assert_eq!(span.style.fg, Some(Roles::default().msg_link()));
+        assert!(span.style.add_modifier.contains(Modifier::UNDERLINED));
+    }
+
+    #[test]
+    fn test_code_block_fills_width() {
+        let lines = render("<pre>let a = 1;\nlet b = 2;</pre>");
+        assert_eq!(lines.len(), 2);
+        for line in &lines {
+            // indent + full width
+            assert_eq!(line.width(), 2 + 40);
+            assert_eq!(
+                line.spans.last().unwrap().style.bg,
+                Some(Roles::default().msg_code_bg())
+            );
+        }
+    }
+
+    #[test]
+    fn test_quote_and_list() {
+        let lines = render("<blockquote>quoted</blockquote><ul><li>one</li><li>two</li></ul>");
+        let text: Vec<String> = lines
+            .iter()
+            .map(|line| line.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect();
+        assert_eq!(text[0], "  > quoted");
+        assert_eq!(lines[0].style.fg, Some(Roles::default().msg_quote()));
+        assert!(text.contains(&"  * one".to_string()));
+        assert!(text.contains(&"  * two".to_string()));
+    }
+}
@@ -0,0 +1,112 @@
+// ui/room_details.rs
+
+//! Popup with the details of the active room.
+
+use ratatui::layout::{Constraint, Flex, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::block::{Block, BorderType};
+use ratatui::widgets::{Borders, Paragraph};
+
+use crate::app::cache::room::Room;
+use crate::app::state::AppState;
+
+const ROOM_DETAILS_WIDTH_PERCENTAGE: u16 = 60;
+/// The detail lines plus the borders
+const ROOM_DETAILS_HEIGHT: u16 = 10;
+/// Width of the labels column, including the separator
+const LABEL_WIDTH: usize = 13;
+
+/// Returns the area of the popup, centered in `area`.
+pub(crate) fn room_details_area(area: Rect) -> Rect {
+    let [area] = Layout::vertical([Constraint::Length(ROOM_DETAILS_HEIGHT)])
+        .flex(Flex::Center)
+        .areas(area);
+    let [area] = Layout::horizontal([Constraint::Percentage(ROOM_DETAILS_WIDTH_PERCENTAGE)])
+        .flex(Flex::Center)
+        .areas(area);
+    area
+}
+
+/// Returns the details of the room as label and value lines, `…` while they are retrieved.
+fn room_details_lines<'a>(state: &AppState, room: &Room) -> Vec<Line<'a>> {
+    let unknown = || String::from("…");
+    let kind = match room.is_direct() {
+        true => "Direct conversation",
+        false => "Space",
+    };
+    let team = room
+        .team_id
+        .as_ref()
+        .and_then(|id| state.cache.teams.team_with_id(id))
+        .and_then(|team| team.name.clone())
+        .unwrap_or(String::from("None"));
+    let creator = state
+        .cache
+        .persons
+        .get(&room.creator_id)
+        .map(|person| person.display_name.clone())
+        .unwrap_or_else(unknown);
+    let created = room
+        .created
+        .map(|created| created.format("%a %d %b %Y, %H:%M").to_string())
+        .unwrap_or_else(unknown);
+    let members = state
+        .cache
+        .members
+        .get(&room.id)
+        .map(|members| members.len().to_string())
+        .unwrap_or_else(unknown);
+    let moderation = match room.is_moderated() {
+        true => "Moderated",
+        false => "Not moderated",
+    };
+    let meeting_info = state.cache.meeting_infos.get(&room.id);
+    let meeting_link = meeting_info
+        .map(|info| info.meeting_link.clone().unwrap_or_default())
+        .unwrap_or_else(unknown);
+    let sip_address = meeting_info
+        .map(|info| info.sip_address.clone().unwrap_or_default())
+        .unwrap_or_else(unknown);
+
+    let label_style = Style::default().fg(state.theme.roles.accent());
+    [
+        ("Type", kind.to_string()),
+        ("Team", team),
+        ("Created by", creator),
+        ("Created", created),
+        ("Members", members),
+        ("Moderation", moderation.to_string()),
+        ("Meeting", meeting_link),
+        ("SIP address", sip_address),
+    ]
+    .into_iter()
+    .map(|(label, value)| {
+        Line::from(vec![
+            Span::styled(format!("{label:<LABEL_WIDTH$}"), label_style),
+            Span::raw(value),
+        ])
+    })
+    .collect()
+}
+
+/// Draws the details of the room, with the room title as title.
+pub(crate) fn draw_room_details<'a>(state: &AppState, room: &Room) -> Paragraph<'a> {
+    let hint = match state.can_manage_room() {
+        true => "  (r: rename, L: lock/unlock, esc: close)",
+        false => "  (esc: close)",
+    };
+    let title = Line::from(vec![
+        Span::styled(
+            room.title.clone().unwrap_or_default(),
+            Style::default().add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(hint, Style::default().fg(state.theme.roles.hint())),
+    ]);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(state.theme.roles.border_active()))
+        .title(title);
+    Paragraph::new(room_details_lines(state, room)).block(block)
+}
@@ -1,9 +1,11 @@
 //! Panel with a list of rooms
 
+use crate::app::mutes::Mute;
 use crate::app::state::{ActivePane, AppState};
 
 use ratatui::layout::Constraint;
 use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Text};
 use ratatui::widgets::block::{Block, BorderType};
 use ratatui::widgets::{Borders, Cell, Row, Table};
 
@@ -42,12 +44,12 @@ pub(crate) fn draw_rooms_table<'a>(state: &AppState) -> Table<'a> {
             let selected_count = state.rooms_list.selected_room_count();
             if selected_count > 0 {
                 format!(
-                    "Filter: {:?} ({} selected)",
+                    "Filter: {} ({} selected)",
                     state.rooms_list.filter(),
                     selected_count
                 )
             } else {
-                format!("Filter: {:?}", state.rooms_list.filter())
+                format!("Filter: {}", state.rooms_list.filter())
             }
         }
     };
@@ -60,7 +62,7 @@ pub(crate) fn draw_rooms_table<'a>(state: &AppState) -> Table<'a> {
             let selected_count = state.rooms_list.selected_room_count();
             if selected_count > 0 {
                 format!(
-                    "Filter: {:?} ({} selected)",
+                    "Filter: {} ({} selected)",
                     state.rooms_list.filter(),
                     selected_count
                 )
@@ -68,6 +70,8 @@ pub(crate) fn draw_rooms_table<'a>(state: &AppState) -> Table<'a> {
                 title
             }
         });
+    // Rooms are preceded by the name of their section, if they are grouped
+    let mut previous_section = None;
     let items: Vec<_> = state
         .visible_rooms()
         .map(|room| {
@@ -89,8 +93,33 @@ pub(crate) fn draw_rooms_table<'a>(state: &AppState) -> Table<'a> {
                 "☐ "
             };
 
-            let cell_content = format!("{selection_indicator}{line}");
-            Row::new(vec![Cell::from(cell_content)])
+            let draft_indicator = if state.room_has_draft(&room.id) {
+                "✎ "
+            } else {
+                ""
+            };
+
+            let mute_indicator = match state.mute_of(&room.id) {
+                Some(Mute::Always) => "⊘ ",
+                Some(Mute::Until(_)) => "☾ ",
+                None => "",
+            };
+
+            let cell_content =
+                format!("{selection_indicator}{draft_indicator}{mute_indicator}{line}");
+            let section = state.room_section(room);
+            if !state.rooms_are_grouped() || previous_section == Some(section.0) {
+                return Row::new(vec![Cell::from(cell_content)]);
+            }
+            previous_section = Some(section.0);
+            let header = Line::styled(
+                section.1.to_string(),
+                Style::default()
+                    .fg(state.theme.roles.accent())
+                    .add_modifier(Modifier::BOLD),
+            );
+            let text = Text::from(vec![header, Line::raw(cell_content)]);
+            Row::new(vec![Cell::from(text)]).height(2)
         })
         .collect();
     Table::new(items, &[Constraint::Length(ROOMS_LIST_WIDTH)])
@@ -0,0 +1,77 @@
+// ui/scheduled.rs
+
+//! A panel listing the messages scheduled to be sent later, in place of the active room.
+
+use chrono::Local;
+use ratatui::layout::Constraint;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::block::{Block, BorderType};
+use ratatui::widgets::{Borders, Cell, Row, Table};
+
+use crate::app::state::AppState;
+
+const DUE_WIDTH: u16 = 18;
+const ROOM_WIDTH: u16 = 20;
+
+/// Draws the scheduled messages as a table, the next one due first.
+pub(crate) fn draw_scheduled_messages<'a>(state: &AppState) -> Table<'a> {
+    let rows: Vec<_> = state
+        .schedule
+        .messages()
+        .iter()
+        .map(|scheduled| {
+            let room_title = state
+                .cache
+                .rooms
+                .room_with_id(&scheduled.room_id)
+                .and_then(|room| room.title.clone())
+                .unwrap_or_default();
+            let text = scheduled.draft.text.replace('\n', " ");
+            let text = match scheduled.draft.response_to {
+                Some(_) => format!("↳ {text}"),
+                None => text,
+            };
+            Row::new(vec![
+                Cell::from(Span::styled(
+                    scheduled
+                        .due
+                        .with_timezone(&Local)
+                        .format("%a %d %b, %H:%M")
+                        .to_string(),
+                    Style::default().fg(state.theme.roles.msg_timestamp()),
+                )),
+                Cell::from(room_title),
+                Cell::from(text),
+            ])
+        })
+        .collect();
+
+    let title = Line::from(vec![
+        Span::raw(format!("Scheduled messages ({})", rows.len())),
+        Span::styled(
+            "  (enter: edit, d: cancel, esc: close)",
+            Style::default().fg(state.theme.roles.hint()),
+        ),
+    ]);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(state.theme.roles.border_active()))
+        .title(title);
+    Table::new(
+        rows,
+        &[
+            Constraint::Length(DUE_WIDTH),
+            Constraint::Length(ROOM_WIDTH),
+            Constraint::Fill(1),
+        ],
+    )
+    .block(block)
+    .row_highlight_style(
+        Style::default()
+            .bg(state.theme.roles.selection_bg())
+            .fg(state.theme.roles.selection_fg())
+            .add_modifier(Modifier::BOLD),
+    )
+}
@@ -13,9 +13,10 @@ pub(crate) const TITLE_BLOCK_HEIGHT: u16 = 3;
 
 /// Draws the application title panel.
 pub(crate) fn draw_title<'a>(state: &AppState) -> Paragraph<'a> {
-    let title = match state.is_loading {
-        true => "webex-tui (loading)",
-        false => "webex-tui",
+    let title = match (state.rooms_loading_progress, state.is_loading) {
+        (Some(nb_rooms), _) => format!("webex-tui (loading rooms: {nb_rooms})"),
+        (None, true) => String::from("webex-tui (loading)"),
+        (None, false) => String::from("webex-tui"),
     };
     Paragraph::new(title)
         .style(Style::default().fg(state.theme.roles.title()))